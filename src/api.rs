@@ -4,6 +4,10 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 
+use crate::i18n::gettext;
+use crate::library::unix_now;
+use crate::offline_cache::ResultCache;
+
 const HLTB_BASE_URL: &str = "https://howlongtobeat.com";
 const DEBUG_LOG_MAX_CHARS: usize = 500;
 const ERROR_RESPONSE_MAX_CHARS: usize = 200;
@@ -38,8 +42,7 @@ pub struct SearchRequest {
     pub search_options: SearchOptions,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[derive(Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchOptions {
     pub games: GameSearchOptions,
     pub users: UserSearchOptions,
@@ -80,8 +83,7 @@ pub struct ListSearchOptions {
     pub sort_category: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[derive(Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RangeTime {
     pub min: Option<u32>,
     pub max: Option<u32>,
@@ -90,15 +92,13 @@ pub struct RangeTime {
 // Note: RangeYear uses String types (not numeric) to match the API's expected format
 // The HowLongToBeat API accepts year strings like "2020", "2024", or empty strings ""
 // This differs from RangeTime which uses Option<u32> for time values in seconds
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[derive(Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RangeYear {
     pub min: String,
     pub max: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[derive(Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Gameplay {
     pub perspective: String,
     pub flow: String,
@@ -106,7 +106,7 @@ pub struct Gameplay {
     pub difficulty: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct SearchResponse {
     pub color: String,
@@ -119,22 +119,25 @@ pub struct SearchResponse {
     pub data: Vec<Game>,
 }
 
-impl Default for SearchResponse {
-    fn default() -> Self {
-        Self {
-            color: String::new(),
-            title: String::new(),
-            category: String::new(),
-            count: 0,
-            pageCurrent: 0,
-            pageTotal: 0,
-            pageSize: 0,
-            data: Vec::new(),
+/// A way of playing a game, as flagged by HowLongToBeat
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayMode {
+    SinglePlayer,
+    CoOp,
+    Multiplayer,
+}
+
+impl PlayMode {
+    pub fn label(self) -> String {
+        match self {
+            PlayMode::SinglePlayer => gettext("Single-Player"),
+            PlayMode::CoOp => gettext("Co-Op"),
+            PlayMode::Multiplayer => gettext("Multiplayer"),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Game {
     pub count: u32,
@@ -157,6 +160,19 @@ pub struct Game {
     pub comp_plus_count: u32,
     pub comp_100_count: u32,
     pub comp_all_count: u32,
+    // Spread of submitted times, only on game pages: average, median, rushed and leisure
+    pub comp_main_avg: u32,
+    pub comp_main_med: u32,
+    pub comp_main_l: u32,
+    pub comp_main_h: u32,
+    pub comp_plus_avg: u32,
+    pub comp_plus_med: u32,
+    pub comp_plus_l: u32,
+    pub comp_plus_h: u32,
+    pub comp_100_avg: u32,
+    pub comp_100_med: u32,
+    pub comp_100_l: u32,
+    pub comp_100_h: u32,
     pub invested_co: u32,
     pub invested_mp: u32,
     pub invested_co_count: u32,
@@ -175,49 +191,6 @@ pub struct Game {
     pub release_world: u64,
 }
 
-impl Default for Game {
-    fn default() -> Self {
-        Self {
-            count: 0,
-            game_id: 0,
-            game_name: String::new(),
-            game_name_date: 0,
-            game_alias: String::new(),
-            game_type: String::new(),
-            game_image: String::new(),
-            comp_lvl_combine: 0,
-            comp_lvl_sp: 0,
-            comp_lvl_co: 0,
-            comp_lvl_mp: 0,
-            comp_lvl_spd: 0,
-            comp_main: 0,
-            comp_plus: 0,
-            comp_100: 0,
-            comp_all: 0,
-            comp_main_count: 0,
-            comp_plus_count: 0,
-            comp_100_count: 0,
-            comp_all_count: 0,
-            invested_co: 0,
-            invested_mp: 0,
-            invested_co_count: 0,
-            invested_mp_count: 0,
-            count_comp: 0,
-            count_speedrun: 0,
-            count_backlog: 0,
-            count_review: 0,
-            review_score: 0,
-            count_playing: 0,
-            count_retired: 0,
-            profile_dev: String::new(),
-            profile_popular: 0,
-            profile_steam: 0,
-            profile_platform: String::new(),
-            release_world: 0,
-        }
-    }
-}
-
 impl Game {
     /// Get the main story completion time in hours
     pub fn main_story_hours(&self) -> f64 {
@@ -239,6 +212,39 @@ impl Game {
         self.comp_all as f64 / 3600.0
     }
 
+    /// Get the average time played in co-op in hours
+    pub fn co_op_hours(&self) -> f64 {
+        self.invested_co as f64 / 3600.0
+    }
+
+    /// Get the average time played in multiplayer in hours
+    pub fn multiplayer_hours(&self) -> f64 {
+        self.invested_mp as f64 / 3600.0
+    }
+
+    /// The users' rating out of 100, or `None` if nobody has reviewed the game
+    pub fn rating(&self) -> Option<u32> {
+        (self.review_score > 0 && self.count_review > 0).then_some(self.review_score.min(100))
+    }
+
+    /// The ways the game can be played
+    pub fn play_modes(&self) -> Vec<PlayMode> {
+        [
+            (self.comp_lvl_sp, PlayMode::SinglePlayer),
+            (self.comp_lvl_co, PlayMode::CoOp),
+            (self.comp_lvl_mp, PlayMode::Multiplayer),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| *flag > 0)
+        .map(|(_, mode)| mode)
+        .collect()
+    }
+
+    /// Whether anyone has submitted a speedrun. A speedrun category alone has nothing to show.
+    pub fn has_speedruns(&self) -> bool {
+        self.count_speedrun > 0
+    }
+
     /// Get the image URL
     #[allow(dead_code)]
     pub fn image_url(&self) -> String {
@@ -263,6 +269,26 @@ impl Default for SearchRequest {
     }
 }
 
+impl SearchRequest {
+    pub fn new(query: &str, options: GameSearchOptions) -> Self {
+        Self {
+            // Split query into words like the website does
+            search_terms: query.split_whitespace().map(|s| s.to_string()).collect(),
+            search_options: SearchOptions {
+                games: options,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Asks for one random match instead of a page of results
+    pub fn randomized(mut self) -> Self {
+        self.search_options.randomizer = 1;
+        self
+    }
+}
+
 impl Default for GameSearchOptions {
     fn default() -> Self {
         Self {
@@ -297,6 +323,130 @@ impl Default for ListSearchOptions {
 pub struct HltbClient {
     client: reqwest::Client,
     api_keys: Arc<Mutex<Option<ApiKeys>>>,
+    cache: Option<ResultCache>,
+}
+
+/// A response, and when it was fetched if it was served from the offline cache
+#[derive(Debug, Clone)]
+pub struct Fetched<T> {
+    pub value: T,
+    /// Unix timestamp of the cached copy, `None` when it came from the network
+    pub cached_at: Option<u64>,
+}
+
+/// Context of network errors that had nothing cached to fall back on
+#[derive(Debug)]
+pub struct OfflineError;
+
+impl std::fmt::Display for OfflineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HowLongToBeat can't be reached, and this wasn't saved for offline use")
+    }
+}
+
+/// HowLongToBeat answered with an error status
+#[derive(Debug)]
+pub struct StatusError {
+    pub status: u16,
+    message: String,
+}
+
+impl std::fmt::Display for StatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+/// HowLongToBeat answered with something the app can't make sense of, which usually means the
+/// website changed under it
+#[derive(Debug)]
+pub struct SiteChangedError(String);
+
+impl std::fmt::Display for SiteChangedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SiteChangedError {}
+
+/// Why a request failed, as far as the user is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// HowLongToBeat couldn't be reached
+    Offline,
+    /// HowLongToBeat asked the app to slow down
+    RateLimited,
+    /// HowLongToBeat answered with something the app can't read
+    SiteChanged,
+    /// HowLongToBeat had trouble of its own
+    ServerError,
+    Other,
+}
+
+impl FailureKind {
+    pub fn of(error: &anyhow::Error) -> Self {
+        if error.downcast_ref::<OfflineError>().is_some() || is_network_error(error) {
+            return FailureKind::Offline;
+        }
+        for cause in error.chain() {
+            if let Some(status_error) = cause.downcast_ref::<StatusError>() {
+                return match status_error.status {
+                    429 => FailureKind::RateLimited,
+                    500..=599 => FailureKind::ServerError,
+                    _ => FailureKind::Other,
+                };
+            }
+            if cause.is::<SiteChangedError>() || cause.is::<serde_json::Error>() {
+                return FailureKind::SiteChanged;
+            }
+        }
+        FailureKind::Other
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            FailureKind::Offline => gettext("You Are Offline"),
+            FailureKind::RateLimited => gettext("Too Many Searches"),
+            FailureKind::SiteChanged => gettext("HowLongToBeat Has Changed"),
+            FailureKind::ServerError => gettext("HowLongToBeat Is Having Trouble"),
+            FailureKind::Other => gettext("Search Failed"),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            FailureKind::Offline => {
+                gettext("HowLongToBeat can't be reached, and nothing was saved for this search")
+            }
+            FailureKind::RateLimited => gettext("HowLongToBeat asked to slow down; try again in a minute"),
+            FailureKind::SiteChanged => gettext(
+                "Its answer couldn't be read, so the app may need an update. Copy the details to report it.",
+            ),
+            FailureKind::ServerError => gettext("The site answered with an error; try again in a while"),
+            FailureKind::Other => gettext("Something went wrong while searching"),
+        }
+    }
+
+    pub fn icon_name(&self) -> &'static str {
+        match self {
+            FailureKind::Offline => "network-offline-symbolic",
+            FailureKind::RateLimited => "preferences-system-time-symbolic",
+            FailureKind::SiteChanged | FailureKind::ServerError | FailureKind::Other => "dialog-warning-symbolic",
+        }
+    }
+}
+
+/// Whether a request failed because HowLongToBeat couldn't be reached at all, as opposed to
+/// answering with an error
+pub fn is_network_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect() || e.is_timeout())
+    })
 }
 
 impl HltbClient {
@@ -307,9 +457,16 @@ impl HltbClient {
                 .build()
                 .expect("Failed to create HTTP client"),
             api_keys: Arc::new(Mutex::new(None)),
+            cache: None,
         }
     }
 
+    /// Keeps search results and game details in `cache`, to fall back on when offline
+    pub fn with_cache(mut self, cache: ResultCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     /// Fetches the main page and extracts dynamic API keys
     async fn fetch_api_keys(&self) -> Result<ApiKeys> {
         // Fetch the main HowLongToBeat page
@@ -340,7 +497,7 @@ impl HltbClient {
                     None
                 }
             })
-            .ok_or_else(|| anyhow::anyhow!("Could not find _app.js path in HTML"))?;
+            .ok_or_else(|| SiteChangedError("Could not find _app.js path in HTML".to_string()))?;
         
         // Fetch the _app.js file
         let app_js_url = format!("{}{}", HLTB_BASE_URL, app_js_path);
@@ -412,7 +569,7 @@ impl HltbClient {
         }
         
         let fetch_pos = fetch_pos
-            .ok_or_else(|| anyhow::anyhow!("Could not find API search endpoint in JavaScript. The HLTB website structure may have changed."))?;
+            .ok_or_else(|| SiteChangedError("Could not find API search endpoint in JavaScript. The HLTB website structure may have changed.".to_string()))?;
         
         if sub_page.is_empty() {
             sub_page = "search".to_string();
@@ -453,11 +610,13 @@ impl HltbClient {
         }
         
         if search_key.is_empty() {
-            return Err(anyhow::anyhow!(
+            return Err(SiteChangedError(
                 "Could not extract API search key from .concat patterns. \
                 The HowLongToBeat website structure may have changed. \
                 Please report this issue at https://github.com/JustAdreamerFL/howlongtobeat-adwaita-app/issues"
-            ));
+                    .to_string(),
+            )
+            .into());
         }
         
         if debug_enabled {
@@ -474,14 +633,103 @@ impl HltbClient {
     }
 
     pub async fn search(&self, query: &str) -> Result<Vec<Game>> {
+        self.search_with_options(query, GameSearchOptions::default()).await
+    }
+
+    /// Searches with filters and sorting, e.g. from a structured query
+    pub async fn search_with_options(
+        &self,
+        query: &str,
+        options: GameSearchOptions,
+    ) -> Result<Vec<Game>> {
+        self.send_search(&SearchRequest::new(query, options)).await
+    }
+
+    /// Picks a random game matching `query` and `options`, through the site's randomizer.
+    /// `None` if nothing matches.
+    pub async fn random_game(&self, query: &str, options: GameSearchOptions) -> Result<Option<Game>> {
+        let games = self.send_search(&SearchRequest::new(query, options).randomized()).await?;
+        Ok(games.into_iter().next())
+    }
+
+    /// Searches like [`Self::search_with_options`], but serves the results cached last time
+    /// when HowLongToBeat can't be reached. With `online` false, as when the network monitor
+    /// reports no connection, the cache is tried first and the network only if it misses.
+    pub async fn search_or_cached(
+        &self,
+        query: &str,
+        options: GameSearchOptions,
+        online: bool,
+    ) -> Result<Fetched<Vec<Game>>> {
+        let request = SearchRequest::new(query, options);
+        let cached = || {
+            let cached = self.cache.as_ref()?.search(&request)?;
+            Some(Fetched {
+                value: cached.data.data,
+                cached_at: Some(cached.fetched),
+            })
+        };
+        if !online {
+            if let Some(cached) = cached() {
+                return Ok(cached);
+            }
+        }
+        match self.send_search(&request).await {
+            Ok(games) => Ok(Fetched { value: games, cached_at: None }),
+            Err(e) if is_network_error(&e) => {
+                eprintln!("Search failed, trying the offline cache: {}", e);
+                cached().ok_or_else(|| e.context(OfflineError))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Looks up a game like [`Self::game_by_id`], falling back on the offline cache the same
+    /// way as [`Self::search_or_cached`]
+    pub async fn game_by_id_or_cached(&self, game_id: u64, online: bool) -> Result<Fetched<Game>> {
+        let cached = || {
+            let cached = self.cache.as_ref()?.game(game_id)?;
+            Some(Fetched {
+                value: cached.data,
+                cached_at: Some(cached.fetched),
+            })
+        };
+        if !online {
+            if let Some(cached) = cached() {
+                return Ok(cached);
+            }
+        }
+        match self.game_by_id(game_id).await {
+            Ok(game) => Ok(Fetched { value: game, cached_at: None }),
+            Err(e) if is_network_error(&e) => {
+                eprintln!("Loading game {} failed, trying the offline cache: {}", game_id, e);
+                cached().ok_or_else(|| e.context(OfflineError))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Keeps a page of results for offline use. Random picks aren't worth keeping.
+    fn remember(&self, request: &SearchRequest, response: SearchResponse) -> Vec<Game> {
+        if let Some(cache) = &self.cache {
+            if request.search_options.randomizer == 0 {
+                if let Err(e) = cache.store_search(request, &response, unix_now()) {
+                    eprintln!("Failed to cache search results: {}", e);
+                }
+            }
+        }
+        response.data
+    }
+
+    async fn send_search(&self, request: &SearchRequest) -> Result<Vec<Game>> {
         // Try to get cached API keys, or fetch new ones
-        let api_keys = {
-            let cached = self.api_keys.lock()
-                .expect("Failed to acquire API keys lock");
-            if let Some(keys) = cached.as_ref() {
-                keys.clone()
-            } else {
-                drop(cached);
+        // The lock must not be held across the await below, otherwise the future isn't Send
+        let cached_keys = self.api_keys.lock()
+            .expect("Failed to acquire API keys lock")
+            .clone();
+        let api_keys = match cached_keys {
+            Some(keys) => keys,
+            None => {
                 // Fetch new keys
                 let new_keys = self.fetch_api_keys().await?;
                 let mut cache = self.api_keys.lock()
@@ -497,22 +745,10 @@ impl HltbClient {
             HLTB_BASE_URL, api_keys.sub_page, api_keys.search_key
         );
         
-        // Split query into words like the website does
-        let search_terms: Vec<String> = query
-            .trim()
-            .split_whitespace()
-            .map(|s| s.to_string())
-            .collect();
-        
-        let request = SearchRequest {
-            search_terms,
-            ..Default::default()
-        };
-
         // Log the request for debugging
         if std::env::var("HLTB_DEBUG").is_ok() {
             eprintln!("API URL: {}", api_url);
-            if let Ok(json_str) = serde_json::to_string_pretty(&request) {
+            if let Ok(json_str) = serde_json::to_string_pretty(request) {
                 eprintln!("Request payload:\n{}", json_str);
             }
         }
@@ -522,7 +758,7 @@ impl HltbClient {
             .post(&api_url)
             .header("Referer", format!("{}/", HLTB_BASE_URL))
             .header("Origin", HLTB_BASE_URL)
-            .json(&request)
+            .json(request)
             .send()
             .await?;
 
@@ -567,7 +803,7 @@ impl HltbClient {
                 .post(&fresh_api_url)
                 .header("Referer", format!("{}/", HLTB_BASE_URL))
                 .header("Origin", HLTB_BASE_URL)
-                .json(&request)
+                .json(request)
                 .send()
                 .await?;
             
@@ -584,21 +820,30 @@ impl HltbClient {
             }
             
             if !retry_status.is_success() {
-                return Err(anyhow::anyhow!(
+                let message = format!(
                     "HowLongToBeat API request failed with status {}: {}",
                     retry_status,
                     truncate_str(&retry_text, ERROR_RESPONSE_MAX_CHARS)
-                ));
+                );
+                // Still not found with fresh keys: the endpoint moved somewhere we can't find
+                if retry_status.as_u16() == 404 {
+                    return Err(SiteChangedError(message).into());
+                }
+                return Err(StatusError {
+                    status: retry_status.as_u16(),
+                    message,
+                }
+                .into());
             }
             
             // Parse retry response
             let search_response: SearchResponse = serde_json::from_str(&retry_text)
                 .map_err(|e| {
-                    anyhow::anyhow!(
+                    SiteChangedError(format!(
                         "Failed to parse API response: {}. Response was: {}",
                         e,
                         truncate_str(&retry_text, ERROR_RESPONSE_MAX_CHARS)
-                    )
+                    ))
                 })?;
             
             // Cache the fresh keys
@@ -608,31 +853,85 @@ impl HltbClient {
                 *cache = Some(fresh_keys);
             }
             
-            return Ok(search_response.data);
+            return Ok(self.remember(request, search_response));
         }
         
         if !status.is_success() {
-            return Err(anyhow::anyhow!(
-                "HowLongToBeat API returned error status {}: {}",
-                status,
-                truncate_str(&response_text, ERROR_RESPONSE_MAX_CHARS)
-            ));
+            return Err(StatusError {
+                status: status.as_u16(),
+                message: format!(
+                    "HowLongToBeat API returned error status {}: {}",
+                    status,
+                    truncate_str(&response_text, ERROR_RESPONSE_MAX_CHARS)
+                ),
+            }
+            .into());
         }
 
         // Try to parse the response
         let search_response: SearchResponse = serde_json::from_str(&response_text)
             .map_err(|e| {
-                anyhow::anyhow!(
+                SiteChangedError(format!(
                     "Failed to parse API response: {}. Response was: {}",
                     e,
                     truncate_str(&response_text, ERROR_RESPONSE_MAX_CHARS)
-                )
+                ))
             })?;
         
-        Ok(search_response.data)
+        Ok(self.remember(request, search_response))
+    }
+
+    /// Looks up a single game by its HLTB id, using the data embedded in its game page
+    pub async fn game_by_id(&self, game_id: u64) -> Result<Game> {
+        let url = format!("{}/game/{}", HLTB_BASE_URL, game_id);
+        let response = self.client.get(&url).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(StatusError {
+                status: status.as_u16(),
+                message: format!("HowLongToBeat returned status {} for game {}", status, game_id),
+            }
+            .into());
+        }
+
+        let html = response.text().await?;
+        let game = parse_game_page(&html)?;
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.store_game(&game, unix_now()) {
+                eprintln!("Failed to cache game {}: {}", game_id, e);
+            }
+        }
+        Ok(game)
     }
 }
 
+/// Extracts the game from the Next.js data embedded in a game page
+pub fn parse_game_page(html: &str) -> Result<Game> {
+    let marker = r#"<script id="__NEXT_DATA__" type="application/json">"#;
+    let start = html
+        .find(marker)
+        .map(|pos| pos + marker.len())
+        .ok_or_else(|| SiteChangedError("Could not find game data in the page. The HowLongToBeat website structure may have changed.".to_string()))?;
+    let end = html[start..]
+        .find("</script>")
+        .map(|pos| start + pos)
+        .ok_or_else(|| SiteChangedError("Game data in the page is incomplete".to_string()))?;
+
+    let data: serde_json::Value = serde_json::from_str(&html[start..end])?;
+    let mut game = data
+        .pointer("/props/pageProps/game/data/game/0")
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("The page doesn't contain a game"))?;
+
+    // The page has the full release date ("2015-05-19") where search results only have the year
+    if let Some(release) = game.get("release_world").and_then(|r| r.as_str()) {
+        let year: u64 = release.get(..4).and_then(|y| y.parse().ok()).unwrap_or(0);
+        game["release_world"] = year.into();
+    }
+
+    Ok(serde_json::from_value(game)?)
+}
+
 /// Truncates a string to a maximum length
 fn truncate_str(s: &str, max_len: usize) -> &str {
     if s.len() > max_len {
@@ -646,6 +945,52 @@ fn truncate_str(s: &str, max_len: usize) -> &str {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_failure_kinds() {
+        let status = |status| -> anyhow::Error {
+            StatusError {
+                status,
+                message: format!("status {}", status),
+            }
+            .into()
+        };
+        assert_eq!(FailureKind::of(&status(429)), FailureKind::RateLimited);
+        assert_eq!(FailureKind::of(&status(503)), FailureKind::ServerError);
+        assert_eq!(FailureKind::of(&status(403)), FailureKind::Other);
+        assert_eq!(FailureKind::of(&status(502).context("While searching")), FailureKind::ServerError);
+        assert_eq!(FailureKind::of(&status(500).context(OfflineError)), FailureKind::Offline);
+
+        let unreadable = serde_json::from_str::<SearchResponse>("<html>").unwrap_err();
+        assert_eq!(FailureKind::of(&unreadable.into()), FailureKind::SiteChanged);
+        assert_eq!(FailureKind::of(&parse_game_page("<html></html>").unwrap_err()), FailureKind::SiteChanged);
+        assert_eq!(FailureKind::of(&anyhow::anyhow!("Something else")), FailureKind::Other);
+        // The message stays what went wrong, for the details
+        assert_eq!(status(429).to_string(), "status 429");
+    }
+
+    #[test]
+    fn test_parse_game_page() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("hltb")
+            .join("game_page.html");
+        let html = std::fs::read_to_string(path).unwrap();
+
+        let game = parse_game_page(&html).unwrap();
+        assert_eq!(game.game_id, 10270);
+        assert_eq!(game.game_name, "The Witcher 3: Wild Hunt");
+        assert_eq!(game.comp_main, 186195);
+        assert_eq!(game.release_world, 2015, "Full release dates are reduced to the year");
+        assert_eq!((game.comp_main_l, game.comp_main_avg, game.comp_main_h), (118800, 192240, 334800));
+        assert_eq!(game.profile_platform, "Nintendo Switch, PC, PlayStation 4, PlayStation 5, Xbox One, Xbox Series X/S");
+    }
+
+    #[test]
+    fn test_parse_game_page_without_data() {
+        assert!(parse_game_page("<html><body>Not found</body></html>").is_err());
+    }
+
     #[test]
     fn test_game_deserialization_complete() {
         let json = r#"{
@@ -752,6 +1097,37 @@ mod tests {
         assert_eq!(game.all_styles_hours(), 20.0);
     }
 
+    #[test]
+    fn test_game_stats() {
+        let game = Game {
+            comp_lvl_sp: 1,
+            comp_lvl_co: 0,
+            comp_lvl_mp: 1,
+            comp_lvl_spd: 1,
+            invested_co: 0,
+            invested_mp: 9000,
+            review_score: 85,
+            count_review: 120,
+            ..Default::default()
+        };
+
+        assert_eq!(game.play_modes(), vec![PlayMode::SinglePlayer, PlayMode::Multiplayer]);
+        assert_eq!(game.co_op_hours(), 0.0);
+        assert_eq!(game.multiplayer_hours(), 2.5);
+        assert_eq!(game.rating(), Some(85));
+        assert!(!game.has_speedruns(), "A speedrun category without runs shows nothing");
+
+        // A score nobody submitted isn't a rating
+        let unrated = Game {
+            review_score: 50,
+            count_speedrun: 3,
+            ..Default::default()
+        };
+        assert_eq!(unrated.rating(), None);
+        assert!(unrated.play_modes().is_empty());
+        assert!(unrated.has_speedruns());
+    }
+
     #[test]
     fn test_game_urls() {
         let game = Game {
@@ -842,6 +1218,24 @@ mod tests {
         assert!(json.contains("zelda"));
     }
 
+    #[test]
+    fn test_randomized_search_request() {
+        let options = GameSearchOptions {
+            platform: "PC".to_string(),
+            ..Default::default()
+        };
+        let request = SearchRequest::new("  the  witness ", options.clone());
+        assert_eq!(request.search_terms, vec!["the", "witness"]);
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["searchOptions"]["randomizer"], 0);
+        assert_eq!(json["searchOptions"]["games"]["platform"], "PC");
+
+        let json = serde_json::to_value(SearchRequest::new("", options).randomized()).unwrap();
+        assert_eq!(json["searchOptions"]["randomizer"], 1);
+        assert_eq!(json["searchTerms"], serde_json::json!([]));
+        assert_eq!(json["searchOptions"]["games"]["platform"], "PC", "Filters still apply");
+    }
+
     #[test]
     fn test_game_default() {
         let game = Game::default();
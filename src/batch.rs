@@ -0,0 +1,342 @@
+use anyhow::Result;
+use std::future::Future;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::api::{FailureKind, Game, HltbClient};
+use crate::matcher::{self, MatchQuery};
+use crate::time_format::TimeFormatter;
+
+// Lookups allowed in flight at once
+pub const DEFAULT_CONCURRENCY: usize = 3;
+// Pause after each lookup before its slot is handed to the next title
+const LOOKUP_DELAY: Duration = Duration::from_millis(300);
+// Header names recognised as the title column when the input is a CSV file
+const TITLE_COLUMNS: &[&str] = &["title", "name", "game", "game name"];
+
+/// Outcome of looking up one title from the list
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    /// The title as it appeared in the input
+    pub query: String,
+    pub game: Option<Game>,
+    /// Match confidence between 0.0 and 1.0
+    pub confidence: f64,
+    /// All ranked candidates, for picking a different match by hand
+    pub candidates: Vec<(Game, f64)>,
+    /// Why the lookup failed, like when HLTB couldn't be reached. A failed title isn't
+    /// unmatched; looking it up again may find it.
+    pub failed: Option<String>,
+}
+
+impl BatchResult {
+    /// The best of `results`, HLTB's answer for `query`
+    fn new(query: String, results: Result<Vec<Game>>) -> Self {
+        let candidates = match results {
+            Ok(results) => matcher::rank(results, &MatchQuery::new(&query)),
+            Err(e) => {
+                eprintln!("Lookup failed for '{}': {:#}", query, e);
+                return Self::failed(query, FailureKind::of(&e).title());
+            }
+        };
+        let (game, confidence) = match candidates.first() {
+            Some((game, confidence)) => (Some(game.clone()), *confidence),
+            None => (None, 0.0),
+        };
+        Self {
+            query,
+            game,
+            confidence,
+            candidates,
+            failed: None,
+        }
+    }
+
+    fn failed(query: String, reason: String) -> Self {
+        Self {
+            query,
+            game: None,
+            confidence: 0.0,
+            candidates: Vec::new(),
+            failed: Some(reason),
+        }
+    }
+
+    /// Whether the best match is too uncertain to use without asking the user
+    pub fn is_ambiguous(&self) -> bool {
+        matcher::is_ambiguous(&self.candidates)
+    }
+
+    /// Replaces the match with one the user picked; their choice is certain
+    pub fn pick(&mut self, game: Game) {
+        self.game = Some(game);
+        self.confidence = 1.0;
+        self.candidates.clear();
+    }
+}
+
+/// Extracts game titles from pasted text or file contents.
+///
+/// CSV input with a title-like header column uses that column; anything else is treated as one
+/// title per line. Blank lines and `#` comments are skipped and duplicates are dropped.
+pub fn parse_title_list(text: &str) -> Vec<String> {
+    let titles: Vec<String> = match csv_title_column(text) {
+        Some(titles) => titles,
+        None => text.lines().map(|line| line.to_string()).collect(),
+    };
+
+    let mut seen = std::collections::HashSet::new();
+    titles
+        .into_iter()
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty() && !title.starts_with('#'))
+        .filter(|title| seen.insert(title.to_lowercase()))
+        .collect()
+}
+
+fn csv_title_column(text: &str) -> Option<Vec<String>> {
+    let first_line = text.lines().next()?;
+    if !first_line.contains(',') {
+        return None;
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let column = reader
+        .headers()
+        .ok()?
+        .iter()
+        .position(|header| TITLE_COLUMNS.contains(&header.to_lowercase().as_str()))?;
+
+    Some(
+        reader
+            .records()
+            .flatten()
+            .filter_map(|record| record.get(column).map(|title| title.to_string()))
+            .collect(),
+    )
+}
+
+/// Looks up every title on HLTB with at most `concurrency` requests in flight.
+/// Results come back in input order; `progress` is called with (done, total) as lookups finish.
+pub async fn lookup_titles(
+    client: Arc<HltbClient>,
+    titles: Vec<String>,
+    concurrency: usize,
+    progress: impl FnMut(usize, usize),
+) -> Vec<BatchResult> {
+    let lookup = move |title: String| {
+        let client = client.clone();
+        async move {
+            let results = client.search(&title).await;
+            tokio::time::sleep(LOOKUP_DELAY).await;
+            results
+        }
+    };
+    lookup_all(titles, concurrency, lookup, progress).await
+}
+
+/// Runs `lookup` for every title, at most `concurrency` at once, and ranks what it finds
+async fn lookup_all<F, Fut>(
+    titles: Vec<String>,
+    concurrency: usize,
+    lookup: F,
+    mut progress: impl FnMut(usize, usize),
+) -> Vec<BatchResult>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<Game>>> + Send + 'static,
+{
+    let total = titles.len();
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+
+    for (index, title) in titles.iter().cloned().enumerate() {
+        let semaphore = semaphore.clone();
+        let lookup = lookup(title.clone());
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.ok();
+            (index, BatchResult::new(title, lookup.await))
+        });
+    }
+
+    let mut results: Vec<Option<BatchResult>> = vec![None; total];
+    let mut done = 0;
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, result)) = joined {
+            results[index] = Some(result);
+        }
+        done += 1;
+        progress(done, total);
+    }
+
+    // A panicked task leaves a gap; report it as failed rather than dropping the title
+    results
+        .into_iter()
+        .zip(titles)
+        .map(|(result, query)| {
+            result.unwrap_or_else(|| BatchResult::failed(query, FailureKind::Other.title()))
+        })
+        .collect()
+}
+
+/// Writes the review table as CSV, with times in the user's format. Titles whose lookup failed
+/// say why in the last column, so they can't be mistaken for titles HLTB doesn't know.
+pub fn write_results_csv(writer: impl Write, results: &[BatchResult], time_formatter: &TimeFormatter) -> Result<()> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    csv_writer.write_record([
+        "Query",
+        "Match",
+        "Main Story",
+        "Main + Extras",
+        "Completionist",
+        "Confidence",
+        "URL",
+        "Error",
+    ])?;
+
+    let hours = |value: f64| {
+        if value > 0.0 {
+            time_formatter.format(value)
+        } else {
+            String::new()
+        }
+    };
+
+    for result in results {
+        let record = match &result.game {
+            Some(game) => [
+                result.query.clone(),
+                game.game_name.clone(),
+                hours(game.main_story_hours()),
+                hours(game.main_plus_hours()),
+                hours(game.completionist_hours()),
+                format!("{:.0}%", result.confidence * 100.0),
+                game.game_url(),
+                String::new(),
+            ],
+            None => [
+                result.query.clone(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                result.failed.clone().unwrap_or_default(),
+            ],
+        };
+        csv_writer.write_record(&record)?;
+    }
+
+    csv_writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_format::TimeFormat;
+
+    #[test]
+    fn test_parse_title_list_lines() {
+        let titles = parse_title_list("Celeste\n\n  Hades  \n# skip me\nceleste\nOuter Wilds\n");
+        assert_eq!(titles, vec!["Celeste", "Hades", "Outer Wilds"]);
+    }
+
+    #[test]
+    fn test_parse_title_list_csv_column() {
+        let titles = parse_title_list("Rank,Game Name,Votes\n1,Hades,40\n2,\"Pokémon Red, Blue\",31\n3,,2\n");
+        assert_eq!(titles, vec!["Hades", "Pokémon Red, Blue"]);
+    }
+
+    #[test]
+    fn test_parse_title_list_commas_without_title_header() {
+        // A title containing a comma shouldn't be mistaken for CSV
+        let titles = parse_title_list("Warhammer 40,000: Space Marine\nCeleste\n");
+        assert_eq!(titles, vec!["Warhammer 40,000: Space Marine", "Celeste"]);
+    }
+
+    #[test]
+    fn test_write_results_csv() {
+        let results = vec![
+            BatchResult {
+                query: "hades".to_string(),
+                game: Some(Game {
+                    game_id: 62941,
+                    game_name: "Hades".to_string(),
+                    comp_main: 80640,
+                    comp_plus: 0,
+                    comp_100: 344340,
+                    ..Default::default()
+                }),
+                confidence: 1.0,
+                candidates: Vec::new(),
+                failed: None,
+            },
+            BatchResult::new("not a game".to_string(), Ok(Vec::new())),
+            BatchResult::failed("celeste".to_string(), "You Are Offline".to_string()),
+        ];
+
+        let mut output = Vec::new();
+        // Hours and minutes don't depend on the locale's separators below 1000h
+        let time_formatter = TimeFormatter::new(TimeFormat::HoursMinutes, 3);
+        write_results_csv(&mut output, &results, &time_formatter).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Query,Match,Main Story,Main + Extras,Completionist,Confidence,URL,Error");
+        assert_eq!(lines[1], "hades,Hades,22h24m,,95h39m,100%,https://howlongtobeat.com/game/62941,");
+        assert_eq!(lines[2], "not a game,,,,,,,");
+        assert_eq!(lines[3], "celeste,,,,,,,You Are Offline");
+    }
+
+    #[test]
+    fn test_pick_replaces_ambiguous_match() {
+        let remake = Game { game_id: 2, game_name: "Tomb Raider".to_string(), ..Default::default() };
+        let original = Game { game_id: 1, game_name: "Tomb Raider".to_string(), ..Default::default() };
+        let mut result = BatchResult {
+            query: "Tomb Raider".to_string(),
+            game: Some(original.clone()),
+            confidence: 1.0,
+            candidates: vec![(original, 1.0), (remake.clone(), 1.0)],
+            failed: None,
+        };
+        assert!(result.is_ambiguous());
+
+        result.pick(remake);
+        assert!(!result.is_ambiguous());
+        assert_eq!(result.game.unwrap().game_id, 2);
+        assert_eq!(result.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_failed_lookups_are_not_unmatched() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let titles = vec!["Hades".to_string(), "Not On HLTB".to_string(), "Celeste".to_string()];
+        let mut progress = Vec::new();
+        let results = runtime.block_on(lookup_all(
+            titles,
+            2,
+            |title| async move {
+                match title.as_str() {
+                    "Hades" => Ok(vec![Game { game_id: 62941, game_name: title, ..Default::default() }]),
+                    "Not On HLTB" => Ok(Vec::new()),
+                    _ => Err(anyhow::anyhow!("HowLongToBeat can't be reached")),
+                }
+            },
+            |done, total| progress.push((done, total)),
+        ));
+
+        assert_eq!(results[0].game.as_ref().unwrap().game_id, 62941);
+        assert!(results[0].failed.is_none());
+        assert!(results[1].game.is_none() && results[1].failed.is_none(), "No match isn't a failure");
+        assert_eq!(results[2].query, "Celeste");
+        assert!(results[2].game.is_none() && results[2].failed.is_some());
+        assert_eq!(progress, vec![(1, 3), (2, 3), (3, 3)]);
+    }
+}
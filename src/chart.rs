@@ -0,0 +1,291 @@
+use std::f64::consts::TAU;
+
+use crate::api::Game;
+use crate::settings::TimeCategory;
+
+/// Categories compared in the chart, in the order they're drawn
+const CATEGORIES: [TimeCategory; 3] = [TimeCategory::Main, TimeCategory::MainExtras, TimeCategory::Completionist];
+
+// Share of each row taken up by its bar
+const BAR_FILL: f64 = 0.6;
+// Keeps the spread's end ticks from being cut off at the edges
+const MARGIN: f64 = 1.5;
+const SPREAD_LINE_WIDTH: f64 = 2.0;
+
+// Adwaita's blue, purple and orange
+const BAR_COLORS: [Rgba; 3] = [
+    (0x35 as f64 / 255.0, 0x84 as f64 / 255.0, 0xe4 as f64 / 255.0, 1.0),
+    (0x91 as f64 / 255.0, 0x41 as f64 / 255.0, 0xac as f64 / 255.0, 1.0),
+    (0xe6 as f64 / 255.0, 0x61 as f64 / 255.0, 0x00 as f64 / 255.0, 1.0),
+];
+
+/// Red, green, blue and alpha, each from 0 to 1
+pub type Rgba = (f64, f64, f64, f64);
+
+/// How long players took in one category, from the quickest to the slowest, in hours
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spread {
+    pub rushed: f64,
+    pub average: f64,
+    pub leisure: f64,
+}
+
+/// One bar of the chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartBar {
+    pub category: TimeCategory,
+    pub hours: f64,
+    /// Only known for games fetched from their own page
+    pub spread: Option<Spread>,
+}
+
+/// Colors of the chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    pub bars: [Rgba; 3],
+    pub track: Rgba,
+    pub spread: Rgba,
+}
+
+impl Palette {
+    /// The category colors, with the track and spread in shades of the text color
+    pub fn new(foreground: Rgba) -> Self {
+        let (red, green, blue, _) = foreground;
+        Self {
+            bars: BAR_COLORS,
+            track: (red, green, blue, 0.1),
+            spread: (red, green, blue, 0.8),
+        }
+    }
+}
+
+/// The bars for a game's completion times, leaving out categories nobody submitted
+pub fn chart_bars(game: &Game) -> Vec<ChartBar> {
+    CATEGORIES
+        .iter()
+        .filter_map(|&category| {
+            Some(ChartBar {
+                category,
+                hours: category.hours(game)?,
+                spread: spread(game, category),
+            })
+        })
+        .collect()
+}
+
+fn spread(game: &Game, category: TimeCategory) -> Option<Spread> {
+    let (rushed, average, leisure) = match category {
+        TimeCategory::Main => (game.comp_main_l, game.comp_main_avg, game.comp_main_h),
+        TimeCategory::MainExtras => (game.comp_plus_l, game.comp_plus_avg, game.comp_plus_h),
+        TimeCategory::Completionist => (game.comp_100_l, game.comp_100_avg, game.comp_100_h),
+        TimeCategory::AllStyles => return None,
+    };
+    (rushed > 0 && average > 0 && leisure >= rushed).then(|| Spread {
+        rushed: rushed as f64 / 3600.0,
+        average: average as f64 / 3600.0,
+        leisure: leisure as f64 / 3600.0,
+    })
+}
+
+/// Draws one horizontal bar per category, all on the scale of the longest time so they
+/// compare at a glance. A spread is drawn over its bar as a line from the rushed to the
+/// leisure time, with a dot at the average.
+pub fn draw(
+    cr: &cairo::Context,
+    width: f64,
+    height: f64,
+    bars: &[ChartBar],
+    palette: &Palette,
+) -> Result<(), cairo::Error> {
+    let longest = bars
+        .iter()
+        .map(|bar| bar.spread.map_or(bar.hours, |spread| spread.leisure.max(bar.hours)))
+        .fold(0.0, f64::max);
+    if longest <= 0.0 || width <= 2.0 * MARGIN || height <= 0.0 {
+        return Ok(());
+    }
+
+    let plot_width = width - 2.0 * MARGIN;
+    let x = |hours: f64| MARGIN + plot_width * hours / longest;
+    let row_height = height / bars.len() as f64;
+    let thickness = row_height * BAR_FILL;
+
+    for (row, bar) in bars.iter().enumerate() {
+        let top = row as f64 * row_height + (row_height - thickness) / 2.0;
+        let color = CATEGORIES
+            .iter()
+            .position(|category| *category == bar.category)
+            .map_or(palette.bars[0], |index| palette.bars[index]);
+
+        set_source(cr, palette.track);
+        rounded_rectangle(cr, MARGIN, top, plot_width, thickness);
+        cr.fill()?;
+
+        set_source(cr, color);
+        rounded_rectangle(cr, MARGIN, top, x(bar.hours) - MARGIN, thickness);
+        cr.fill()?;
+
+        if let Some(spread) = bar.spread {
+            let middle = top + thickness / 2.0;
+            set_source(cr, palette.spread);
+            cr.set_line_width(SPREAD_LINE_WIDTH);
+            cr.move_to(x(spread.rushed), middle);
+            cr.line_to(x(spread.leisure), middle);
+            for hours in [spread.rushed, spread.leisure] {
+                cr.move_to(x(hours), top);
+                cr.line_to(x(hours), top + thickness);
+            }
+            cr.stroke()?;
+
+            cr.arc(x(spread.average), middle, thickness / 4.0, 0.0, TAU);
+            cr.fill()?;
+        }
+    }
+    Ok(())
+}
+
+fn set_source(cr: &cairo::Context, (red, green, blue, alpha): Rgba) {
+    cr.set_source_rgba(red, green, blue, alpha);
+}
+
+fn rounded_rectangle(cr: &cairo::Context, x: f64, y: f64, width: f64, height: f64) {
+    let radius = (height / 2.0).min(width / 2.0).max(0.0);
+    cr.new_sub_path();
+    cr.arc(x + width - radius, y + radius, radius, -TAU / 4.0, 0.0);
+    cr.arc(x + width - radius, y + height - radius, radius, 0.0, TAU / 4.0);
+    cr.arc(x + radius, y + height - radius, radius, TAU / 4.0, TAU / 2.0);
+    cr.arc(x + radius, y + radius, radius, TAU / 2.0, TAU * 3.0 / 4.0);
+    cr.close_path();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: i32 = 200;
+    const HEIGHT: i32 = 60;
+
+    fn game() -> Game {
+        Game {
+            comp_main: 36000,  // 10 hours
+            comp_plus: 72000,  // 20 hours
+            comp_100: 144000,  // 40 hours
+            ..Default::default()
+        }
+    }
+
+    /// Renders the chart offscreen and reads it back from a PNG file
+    fn render(bars: &[ChartBar], name: &str) -> cairo::ImageSurface {
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, WIDTH, HEIGHT).unwrap();
+        let cr = cairo::Context::new(&surface).unwrap();
+        draw(&cr, WIDTH as f64, HEIGHT as f64, bars, &Palette::new((0.0, 0.0, 0.0, 1.0))).unwrap();
+        drop(cr);
+
+        let path = std::env::temp_dir().join(format!("hltb-chart-{}-{}.png", name, std::process::id()));
+        surface.write_to_png(&mut std::fs::File::create(&path).unwrap()).unwrap();
+        let png = std::fs::read(&path).unwrap();
+        assert!(png.starts_with(b"\x89PNG"), "The chart should be written as a PNG");
+
+        let rendered = cairo::ImageSurface::create_from_png(&mut png.as_slice()).unwrap();
+        std::fs::remove_file(&path).ok();
+        rendered
+    }
+
+    /// The pixel at (x, y) as premultiplied red, green, blue and alpha
+    fn pixel(surface: &mut cairo::ImageSurface, x: i32, y: i32) -> [u8; 4] {
+        let stride = surface.stride();
+        let data = surface.data().unwrap();
+        let offset = (y * stride + x * 4) as usize;
+        // ARGB32 is stored as a native-endian u32
+        let argb = u32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap());
+        let [blue, green, red, alpha] = argb.to_le_bytes();
+        [red, green, blue, alpha]
+    }
+
+    fn bar_color(index: usize) -> [u8; 4] {
+        // The bar colors are opaque, so premultiplying doesn't change them
+        let (red, green, blue, alpha) = BAR_COLORS[index];
+        [red, green, blue, alpha].map(|channel| (channel * 255.0).round() as u8)
+    }
+
+    #[test]
+    fn test_chart_bars() {
+        let bars = chart_bars(&game());
+        assert_eq!(
+            bars.iter().map(|bar| (bar.category, bar.hours)).collect::<Vec<_>>(),
+            vec![
+                (TimeCategory::Main, 10.0),
+                (TimeCategory::MainExtras, 20.0),
+                (TimeCategory::Completionist, 40.0),
+            ]
+        );
+        assert!(bars.iter().all(|bar| bar.spread.is_none()));
+
+        let detailed = Game {
+            comp_plus: 0,
+            comp_main_l: 28800,
+            comp_main_avg: 36000,
+            comp_main_h: 54000,
+            ..game()
+        };
+        let bars = chart_bars(&detailed);
+        assert_eq!(bars.len(), 2, "Categories without times are left out");
+        assert_eq!(
+            bars[0].spread,
+            Some(Spread {
+                rushed: 8.0,
+                average: 10.0,
+                leisure: 15.0
+            })
+        );
+        assert_eq!(bars[1].spread, None);
+    }
+
+    #[test]
+    fn test_render_bars() {
+        let mut surface = render(&chart_bars(&game()), "bars");
+        let row_middle = |row: i32| row * HEIGHT / 3 + HEIGHT / 6;
+
+        // Each bar is as long as its share of the longest time
+        assert_eq!(pixel(&mut surface, 20, row_middle(0)), bar_color(0));
+        assert_eq!(pixel(&mut surface, 90, row_middle(1)), bar_color(1));
+        assert_eq!(pixel(&mut surface, 190, row_middle(2)), bar_color(2));
+
+        // Past its end the faint track shows through
+        let track = pixel(&mut surface, 90, row_middle(0));
+        assert!((20..=30).contains(&track[3]), "Expected the track, got {:?}", track);
+
+        // Between rows nothing is drawn
+        assert_eq!(pixel(&mut surface, 20, HEIGHT / 3), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_render_spread() {
+        let detailed = Game {
+            comp_main_l: 28800,   // 8 hours
+            comp_main_avg: 36000, // 10 hours
+            comp_main_h: 72000,   // 20 hours
+            ..game()
+        };
+        let bars = chart_bars(&detailed);
+        let mut surface = render(&bars, "spread");
+        let middle = HEIGHT / 6;
+
+        // The spread line runs past the end of the main story bar to the leisure time
+        let past_bar = pixel(&mut surface, 70, middle);
+        assert!(past_bar[3] > 150, "Expected the spread line, got {:?}", past_bar);
+        assert_ne!(past_bar, bar_color(0));
+
+        // and stops there
+        let past_leisure = pixel(&mut surface, 120, middle);
+        assert!(past_leisure[3] < 50, "Expected the track, got {:?}", past_leisure);
+    }
+
+    #[test]
+    fn test_render_nothing() {
+        let mut surface = render(&[], "empty");
+        for (x, y) in [(0, 0), (WIDTH / 2, HEIGHT / 2), (WIDTH - 1, HEIGHT - 1)] {
+            assert_eq!(pixel(&mut surface, x, y), [0, 0, 0, 0]);
+        }
+    }
+}
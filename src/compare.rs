@@ -0,0 +1,166 @@
+use crate::api::Game;
+use crate::i18n::{gettext, gettext_f};
+use crate::settings::TimeCategory;
+use crate::time_format::TimeFormatter;
+
+/// How many games can be compared at once
+pub const MIN_GAMES: usize = 2;
+pub const MAX_GAMES: usize = 5;
+
+/// Which value of a row stands out
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Best {
+    Lowest,
+    Highest,
+}
+
+/// One line of the comparison table, with a cell per game
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonRow {
+    pub label: String,
+    /// `None` where the game has no value
+    pub cells: Vec<Option<String>>,
+    /// Whether each cell holds the best value of the row; ties are all marked
+    pub best: Vec<bool>,
+}
+
+/// Builds the comparison table: every completion category, its rating count, the review
+/// score, platforms and release year. The shortest times and highest counts and scores are
+/// marked as best.
+pub fn compare(games: &[Game], time_formatter: &TimeFormatter) -> Vec<ComparisonRow> {
+    let mut rows = Vec::new();
+
+    for category in TimeCategory::ALL {
+        rows.push(numeric_row(
+            category.label(),
+            games,
+            |game| category.hours(game),
+            Best::Lowest,
+            |hours| time_formatter.format(hours),
+        ));
+    }
+
+    for category in TimeCategory::ALL {
+        rows.push(numeric_row(
+            gettext_f("{category} Ratings", &[("category", &category.label())]),
+            games,
+            |game| Some(category.count(game)).filter(|&count| count > 0).map(f64::from),
+            Best::Highest,
+            |count| format!("{}", count as u64),
+        ));
+    }
+
+    rows.push(numeric_row(
+        gettext("Review Score"),
+        games,
+        |game| game.rating().map(f64::from),
+        Best::Highest,
+        |score| format!("{}%", score as u32),
+    ));
+
+    rows.push(text_row(gettext("Platforms"), games, |game| {
+        Some(game.profile_platform.clone()).filter(|platforms| !platforms.is_empty())
+    }));
+
+    rows.push(text_row(gettext("Release Year"), games, |game| {
+        (game.release_world > 0).then(|| game.release_world.to_string())
+    }));
+
+    rows
+}
+
+/// A row of values that aren't ranked
+fn text_row(label: String, games: &[Game], value: impl Fn(&Game) -> Option<String>) -> ComparisonRow {
+    ComparisonRow {
+        label,
+        cells: games.iter().map(value).collect(),
+        best: vec![false; games.len()],
+    }
+}
+
+fn numeric_row(
+    label: String,
+    games: &[Game],
+    value: impl Fn(&Game) -> Option<f64>,
+    best: Best,
+    format: impl Fn(f64) -> String,
+) -> ComparisonRow {
+    let values: Vec<Option<f64>> = games.iter().map(value).collect();
+    let known = values.iter().flatten().copied();
+    let best_value = match best {
+        Best::Lowest => known.reduce(f64::min),
+        Best::Highest => known.reduce(f64::max),
+    };
+    // A single known value isn't better than anything
+    let comparable = values.iter().flatten().count() >= MIN_GAMES;
+
+    ComparisonRow {
+        label,
+        cells: values.iter().map(|value| value.map(&format)).collect(),
+        best: values
+            .iter()
+            .map(|value| comparable && value.is_some() && *value == best_value)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(name: &str, main_hours: u32, main_count: u32, review_score: u32) -> Game {
+        Game {
+            game_name: name.to_string(),
+            comp_main: main_hours * 3600,
+            comp_main_count: main_count,
+            review_score,
+            count_review: if review_score > 0 { 10 } else { 0 },
+            profile_platform: "PC".to_string(),
+            release_world: 2020,
+            ..Default::default()
+        }
+    }
+
+    fn row<'a>(rows: &'a [ComparisonRow], label: &str) -> &'a ComparisonRow {
+        rows.iter().find(|row| row.label == label).unwrap()
+    }
+
+    #[test]
+    fn test_compare() {
+        let time_formatter = TimeFormatter::default();
+        let games = [game("Long", 40, 100, 80), game("Short", 10, 500, 0), game("Medium", 20, 500, 90)];
+        let rows = compare(&games, &time_formatter);
+
+        let main = row(&rows, "Main Story");
+        assert_eq!(
+            main.cells,
+            vec![
+                Some(time_formatter.format(40.0)),
+                Some(time_formatter.format(10.0)),
+                Some(time_formatter.format(20.0))
+            ]
+        );
+        assert_eq!(main.best, vec![false, true, false], "The shortest time is best");
+
+        // Ties are all highlighted
+        assert_eq!(row(&rows, "Main Story Ratings").best, vec![false, true, true]);
+
+        // Unknown values are left empty and never best
+        let score = row(&rows, "Review Score");
+        assert_eq!(score.cells, vec![Some("80%".to_string()), None, Some("90%".to_string())]);
+        assert_eq!(score.best, vec![false, false, true]);
+
+        // Nothing to compare when only one game has a time
+        assert_eq!(row(&rows, "Completionist").best, vec![false, false, false]);
+
+        assert_eq!(row(&rows, "Platforms").cells, vec![Some("PC".to_string()); 3]);
+        assert_eq!(row(&rows, "Release Year").best, vec![false; 3]);
+    }
+
+    #[test]
+    fn test_compare_one_known_value() {
+        let games = [game("Timed", 10, 5, 0), game("Untimed", 0, 0, 0)];
+        let rows = compare(&games, &TimeFormatter::default());
+        assert_eq!(row(&rows, "Main Story").best, vec![false, false]);
+    }
+}
@@ -0,0 +1,195 @@
+use std::cmp::Reverse;
+
+use crate::api::{Game, GameSearchOptions, RangeTime};
+use crate::i18n::gettext_f;
+use crate::reliability::Reliability;
+use crate::settings::TimeCategory;
+use crate::time_format::TimeFormatter;
+
+/// Genres HowLongToBeat can filter on, as it names them
+pub const GENRES: &[&str] = &[
+    "Action",
+    "Adventure",
+    "Arcade",
+    "Battle Arena",
+    "Beat em Up",
+    "Board Game",
+    "Breakout",
+    "Card Game",
+    "City-Building",
+    "Educational",
+    "Fighting",
+    "First-Person",
+    "Flight",
+    "Hack and Slash",
+    "Horror",
+    "Interactive Art",
+    "Life Simulation",
+    "Management",
+    "MOBA",
+    "Music/Rhythm",
+    "Open World",
+    "Party",
+    "Pinball",
+    "Platform",
+    "Point-and-Click",
+    "Puzzle",
+    "Racing/Driving",
+    "Real-Time",
+    "Role-Playing",
+    "Roguelike",
+    "Sandbox",
+    "Shooter",
+    "Simulation",
+    "Sports",
+    "Stealth",
+    "Strategy/Tactical",
+    "Survival",
+    "Third-Person",
+    "Tower Defense",
+    "Turn-Based",
+    "Visual Novel",
+];
+
+/// What the player has time for: games whose `category` time fits in `hours`, optionally on
+/// one platform and in one genre
+#[derive(Debug, Clone, PartialEq)]
+pub struct Budget {
+    pub hours: f64,
+    pub category: TimeCategory,
+    /// Any platform when empty
+    pub platform: String,
+    /// Any genre when empty
+    pub genre: String,
+}
+
+/// The budget in a few words, like "Main Story in 10 Hours or less · PC · Puzzle"
+pub fn describe(budget: &Budget, time_formatter: &TimeFormatter) -> String {
+    let mut parts = vec![gettext_f(
+        "{category} in {time} or less",
+        &[("category", &budget.category.label()), ("time", &time_formatter.format(budget.hours))],
+    )];
+    parts.extend([&budget.platform, &budget.genre].into_iter().filter(|part| !part.is_empty()).cloned());
+    parts.join(" · ")
+}
+
+/// HowLongToBeat's name for a category in `rangeCategory`
+fn range_category(category: TimeCategory) -> &'static str {
+    match category {
+        TimeCategory::Main => "main",
+        TimeCategory::MainExtras => "mainp",
+        TimeCategory::Completionist => "comp",
+        TimeCategory::AllStyles => "all",
+    }
+}
+
+/// Search options that have HowLongToBeat do the filtering, best rated first
+pub fn search_options(budget: &Budget) -> GameSearchOptions {
+    let mut options = GameSearchOptions {
+        platform: budget.platform.clone(),
+        sort_category: "rating".to_string(),
+        range_category: range_category(budget.category).to_string(),
+        range_time: RangeTime {
+            min: None,
+            max: Some((budget.hours * 3600.0).round() as u32),
+        },
+        ..Default::default()
+    };
+    options.gameplay.genre = budget.genre.clone();
+    options
+}
+
+/// The games that fit the budget, highest review score first. Scores from only a handful of
+/// reviews go after the rest, and games nobody reviewed go last.
+pub fn rank(games: Vec<Game>, budget: &Budget) -> Vec<Game> {
+    let mut fitting: Vec<Game> = games
+        .into_iter()
+        .filter(|game| budget.category.hours(game).is_some_and(|hours| hours <= budget.hours))
+        .collect();
+    fitting.sort_by_key(|game| {
+        (
+            Reliability::from_count(game.count_review) == Reliability::Weak,
+            Reverse(game.rating()),
+            Reverse(game.count_review),
+        )
+    });
+    fitting
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(name: &str, main_hours: u32, review_score: u32, count_review: u32) -> Game {
+        Game {
+            game_name: name.to_string(),
+            comp_main: main_hours * 3600,
+            review_score,
+            count_review,
+            ..Default::default()
+        }
+    }
+
+    fn budget() -> Budget {
+        Budget {
+            hours: 10.0,
+            category: TimeCategory::Main,
+            platform: String::new(),
+            genre: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_search_options() {
+        let options = search_options(&Budget {
+            hours: 1.5,
+            category: TimeCategory::MainExtras,
+            platform: "Nintendo Switch".to_string(),
+            genre: "Puzzle".to_string(),
+        });
+        assert_eq!(options.range_category, "mainp");
+        assert_eq!(options.range_time.min, None);
+        assert_eq!(options.range_time.max, Some(5400));
+        assert_eq!(options.sort_category, "rating");
+        assert_eq!(options.platform, "Nintendo Switch");
+        assert_eq!(options.gameplay.genre, "Puzzle");
+
+        let options = search_options(&budget());
+        assert!(options.platform.is_empty() && options.gameplay.genre.is_empty());
+    }
+
+    #[test]
+    fn test_describe() {
+        let time_formatter = TimeFormatter::default();
+        let time = time_formatter.format(10.0);
+        assert_eq!(describe(&budget(), &time_formatter), format!("Main Story in {} or less", time));
+        assert_eq!(
+            describe(
+                &Budget {
+                    genre: "Puzzle".to_string(),
+                    ..budget()
+                },
+                &time_formatter
+            ),
+            format!("Main Story in {} or less · Puzzle", time)
+        );
+    }
+
+    #[test]
+    fn test_rank() {
+        let games = vec![
+            game("Good", 8, 80, 300),
+            game("Too Long", 30, 95, 300),
+            game("Untimed", 0, 95, 300),
+            game("Unreviewed", 5, 0, 0),
+            game("Best", 10, 90, 40),
+            game("Barely Reviewed", 2, 100, 2),
+            game("Also Good", 4, 80, 500),
+        ];
+        let ranked = rank(games, &budget());
+        assert_eq!(
+            ranked.iter().map(|game| game.game_name.as_str()).collect::<Vec<_>>(),
+            vec!["Best", "Also Good", "Good", "Barely Reviewed", "Unreviewed"]
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::api::{Game, GameSearchOptions, RangeYear};
+use crate::discover::{self, Budget};
+use crate::i18n::gettext;
+use crate::json_file::write_json_atomically;
+use crate::settings::TimeCategory;
+
+// Format of home.json; a file in another format is dropped and the feed fetched again
+const FEED_FORMAT_VERSION: u32 = 1;
+/// Games shown per section
+pub const SECTION_SIZE: usize = 12;
+/// Games kept in the recently viewed section
+pub const RECENTLY_VIEWED_SIZE: usize = 12;
+/// Main story length of the games in the short games section
+const SHORT_GAME_HOURS: f64 = 10.0;
+/// How long fetched sections are shown before they're fetched again
+pub const REFRESH_AFTER: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// The sections of the start screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SectionKind {
+    Popular,
+    NewThisYear,
+    ShortAndGreat,
+    RecentlyViewed,
+}
+
+impl SectionKind {
+    /// The sections fetched from HowLongToBeat, in the order they're shown
+    pub const FETCHED: [Self; 3] = [Self::Popular, Self::NewThisYear, Self::ShortAndGreat];
+
+    pub fn title(self) -> String {
+        match self {
+            Self::Popular => gettext("Popular Right Now"),
+            Self::NewThisYear => gettext("New This Year"),
+            Self::ShortAndGreat => gettext("Short and Highly Rated"),
+            Self::RecentlyViewed => gettext("Recently Viewed"),
+        }
+    }
+
+    /// What to search for to fill the section in `year`. `None` for sections kept locally.
+    pub fn search_options(self, year: i32) -> Option<GameSearchOptions> {
+        match self {
+            Self::Popular => Some(GameSearchOptions::default()),
+            Self::NewThisYear => Some(GameSearchOptions {
+                range_year: RangeYear {
+                    min: year.to_string(),
+                    max: year.to_string(),
+                },
+                ..Default::default()
+            }),
+            Self::ShortAndGreat => Some(discover::search_options(&short_game_budget())),
+            Self::RecentlyViewed => None,
+        }
+    }
+
+    /// Trims search results down to what the section shows
+    pub fn pick(self, games: Vec<Game>) -> Vec<Game> {
+        let mut games = match self {
+            // The range filter is loose, and scores from a few reviews aren't worth much
+            Self::ShortAndGreat => discover::rank(games, &short_game_budget()),
+            _ => games,
+        };
+        games.truncate(SECTION_SIZE);
+        games
+    }
+}
+
+fn short_game_budget() -> Budget {
+    Budget {
+        hours: SHORT_GAME_HOURS,
+        category: TimeCategory::Main,
+        platform: String::new(),
+        genre: String::new(),
+    }
+}
+
+/// A fetched section as cached on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Section {
+    pub kind: SectionKind,
+    pub games: Vec<Game>,
+    /// Unix timestamp of when the games were fetched
+    pub fetched: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct FeedFile {
+    version: u32,
+    sections: Vec<Section>,
+    recently_viewed: Vec<Game>,
+}
+
+/// The start screen's sections, cached in the user cache directory so they show up at once,
+/// and offline
+pub struct HomeFeed {
+    path: PathBuf,
+    sections: Vec<Section>,
+    recently_viewed: Vec<Game>,
+}
+
+impl HomeFeed {
+    /// Default location: `$XDG_CACHE_HOME/howlongtobeat/home.json`
+    pub fn default_path() -> PathBuf {
+        glib::user_cache_dir().join("howlongtobeat").join("home.json")
+    }
+
+    /// Loads the feed from `path`. A missing, unreadable or outdated cache starts out empty;
+    /// the sections are fetched again anyway.
+    pub fn load_from(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let file = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str::<FeedFile>(&contents).unwrap_or_else(|e| {
+                eprintln!("Ignoring unreadable home feed cache {}: {}", path.display(), e);
+                FeedFile::default()
+            }),
+            Err(_) => FeedFile::default(),
+        };
+        if file.version != FEED_FORMAT_VERSION {
+            return Self {
+                path,
+                sections: Vec::new(),
+                recently_viewed: Vec::new(),
+            };
+        }
+        Self {
+            path,
+            sections: file.sections,
+            recently_viewed: file.recently_viewed,
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = FeedFile {
+            version: FEED_FORMAT_VERSION,
+            sections: self.sections.clone(),
+            recently_viewed: self.recently_viewed.clone(),
+        };
+        write_json_atomically(&self.path, &file)
+    }
+
+    pub fn section(&self, kind: SectionKind) -> Option<&Section> {
+        self.sections.iter().find(|section| section.kind == kind)
+    }
+
+    /// Replaces a section with freshly fetched games
+    pub fn set_section(&mut self, kind: SectionKind, games: Vec<Game>, now: u64) {
+        self.sections.retain(|section| section.kind != kind);
+        self.sections.push(Section {
+            kind,
+            games,
+            fetched: now,
+        });
+    }
+
+    /// Whether a section is missing or older than [`REFRESH_AFTER`]
+    pub fn needs_refresh(&self, kind: SectionKind, now: u64) -> bool {
+        self.section(kind)
+            .is_none_or(|section| now.saturating_sub(section.fetched) >= REFRESH_AFTER.as_secs())
+    }
+
+    /// Puts `game` at the front of the recently viewed games
+    pub fn record_viewed(&mut self, game: &Game) {
+        if game.game_id == 0 {
+            return;
+        }
+        self.recently_viewed.retain(|viewed| viewed.game_id != game.game_id);
+        self.recently_viewed.insert(0, game.clone());
+        self.recently_viewed.truncate(RECENTLY_VIEWED_SIZE);
+    }
+
+    pub fn recently_viewed(&self) -> &[Game] {
+        &self.recently_viewed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(game_id: u64) -> Game {
+        Game {
+            game_id,
+            game_name: format!("Game {}", game_id),
+            ..Default::default()
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("hltb-home-test-{}-{}", name, std::process::id()))
+            .join("home.json")
+    }
+
+    #[test]
+    fn test_search_options() {
+        let new = SectionKind::NewThisYear.search_options(2026).unwrap();
+        assert_eq!((new.range_year.min.as_str(), new.range_year.max.as_str()), ("2026", "2026"));
+
+        let short = SectionKind::ShortAndGreat.search_options(2026).unwrap();
+        assert_eq!(short.sort_category, "rating");
+        assert_eq!(short.range_category, "main");
+        assert_eq!(short.range_time.max, Some(36000));
+
+        assert_eq!(SectionKind::Popular.search_options(2026).unwrap().sort_category, "popular");
+        assert!(SectionKind::RecentlyViewed.search_options(2026).is_none());
+    }
+
+    #[test]
+    fn test_pick() {
+        let games: Vec<Game> = (1..=SECTION_SIZE as u64 + 3).map(game).collect();
+        assert_eq!(SectionKind::Popular.pick(games).len(), SECTION_SIZE);
+
+        // Games that turn out longer than the range are left out
+        let long = Game {
+            comp_main: 20 * 3600,
+            ..game(1)
+        };
+        let short = Game {
+            comp_main: 5 * 3600,
+            ..game(2)
+        };
+        let picked = SectionKind::ShortAndGreat.pick(vec![long, short]);
+        assert_eq!(picked.iter().map(|game| game.game_id).collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_refresh() {
+        let mut feed = HomeFeed::load_from(temp_path("refresh"));
+        assert!(feed.needs_refresh(SectionKind::Popular, 1000));
+
+        feed.set_section(SectionKind::Popular, vec![game(1)], 1000);
+        assert!(!feed.needs_refresh(SectionKind::Popular, 1000 + REFRESH_AFTER.as_secs() - 1));
+        assert!(feed.needs_refresh(SectionKind::Popular, 1000 + REFRESH_AFTER.as_secs()));
+        assert!(feed.needs_refresh(SectionKind::NewThisYear, 1000));
+
+        // Setting a section again replaces it
+        feed.set_section(SectionKind::Popular, vec![game(2)], 2000);
+        assert_eq!(feed.section(SectionKind::Popular).unwrap().games[0].game_id, 2);
+    }
+
+    #[test]
+    fn test_recently_viewed() {
+        let mut feed = HomeFeed::load_from(temp_path("viewed"));
+        for game_id in 0..RECENTLY_VIEWED_SIZE as u64 + 2 {
+            feed.record_viewed(&game(game_id));
+        }
+        feed.record_viewed(&game(5));
+
+        let ids: Vec<u64> = feed.recently_viewed().iter().map(|game| game.game_id).collect();
+        assert_eq!(ids.len(), RECENTLY_VIEWED_SIZE);
+        assert_eq!(ids[0], 5, "Viewing a game again moves it to the front");
+        assert_eq!(ids.iter().filter(|&&id| id == 5).count(), 1);
+        assert!(!ids.contains(&0), "Game 0 has no id and is never recorded");
+        assert!(!ids.contains(&1), "The oldest views are dropped");
+    }
+
+    #[test]
+    fn test_save_and_reload() {
+        let path = temp_path("roundtrip");
+        let mut feed = HomeFeed::load_from(&path);
+        feed.set_section(SectionKind::NewThisYear, vec![game(7)], 1234);
+        feed.record_viewed(&game(8));
+        feed.save().unwrap();
+
+        let reloaded = HomeFeed::load_from(&path);
+        let section = reloaded.section(SectionKind::NewThisYear).unwrap();
+        assert_eq!((section.games[0].game_id, section.fetched), (7, 1234));
+        assert_eq!(reloaded.recently_viewed()[0].game_id, 8);
+
+        // A cache from another format version is dropped rather than misread
+        std::fs::write(&path, r#"{"version": 99, "sections": [{"kind": "popular", "games": [], "fetched": 1}]}"#)
+            .unwrap();
+        assert!(HomeFeed::load_from(&path).section(SectionKind::Popular).is_none());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(HomeFeed::load_from(&path).recently_viewed().is_empty());
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,66 @@
+pub use gettextrs::{gettext, ngettext};
+use gettextrs::LocaleCategory;
+
+/// gettext domain, the same as the application id
+pub const GETTEXT_PACKAGE: &str = "com.github.justadreamerfl.HowLongToBeat";
+
+/// Where meson installs the compiled catalogs, passed in at build time
+const LOCALEDIR: &str = match option_env!("LOCALEDIR") {
+    Some(dir) => dir,
+    None => "/usr/local/share/locale",
+};
+
+/// Loads the translations for the user's locale. Strings stay in English if there are none.
+pub fn init() {
+    gettextrs::setlocale(LocaleCategory::LcAll, "");
+    if let Err(e) = gettextrs::bindtextdomain(GETTEXT_PACKAGE, LOCALEDIR) {
+        eprintln!("Failed to bind the translations in {}: {}", LOCALEDIR, e);
+        return;
+    }
+    if let Err(e) = gettextrs::bind_textdomain_codeset(GETTEXT_PACKAGE, "UTF-8") {
+        eprintln!("Failed to set the translation codeset: {}", e);
+    }
+    if let Err(e) = gettextrs::textdomain(GETTEXT_PACKAGE) {
+        eprintln!("Failed to switch to the {} translations: {}", GETTEXT_PACKAGE, e);
+    }
+}
+
+/// Translates `msgid` and fills in its `{name}` placeholders.
+///
+/// Named placeholders let translators move the values around, which `format!` can't offer
+/// for strings that are only known at runtime.
+pub fn gettext_f(msgid: &str, args: &[(&str, &str)]) -> String {
+    fill(gettext(msgid), args)
+}
+
+/// Like [`gettext_f`], picking the singular or plural form for `n`
+pub fn ngettext_f(singular: &str, plural: &str, n: usize, args: &[(&str, &str)]) -> String {
+    fill(ngettext(singular, plural, n.min(u32::MAX as usize) as u32), args)
+}
+
+fn fill(mut text: String, args: &[(&str, &str)]) -> String {
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        // Without a bound catalog the English text comes back unchanged
+        assert_eq!(
+            gettext_f("Did you mean “{query}”?", &[("query", "zelda")]),
+            "Did you mean “zelda”?"
+        );
+        assert_eq!(
+            gettext_f("{count} of {count} for {name}", &[("count", "2"), ("name", "{count}")]),
+            "2 of 2 for {count}"
+        );
+        assert_eq!(ngettext_f("{count} game", "{count} games", 1, &[("count", "1")]), "1 game");
+        assert_eq!(ngettext_f("{count} game", "{count} games", 3, &[("count", "3")]), "3 games");
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::ImportedGame;
+
+// Library caches Heroic keeps per store: (file name, top-level array key, store label)
+const STORE_CACHES: &[(&str, &str, &str)] = &[
+    ("legendary_library.json", "library", "Epic Games Store"),
+    ("gog_library.json", "games", "GOG"),
+    ("nile_library.json", "library", "Amazon Games"),
+];
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct HeroicGame {
+    app_name: String,
+    title: String,
+    is_installed: bool,
+    // Heroic marks DLCs and other non-game items in the library cache
+    is_dlc: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaytimeRecord {
+    // Minutes
+    #[serde(default)]
+    total_played: f64,
+}
+
+/// Finds Heroic's configuration directory in the usual places (native and Flatpak)
+pub fn find_config_dir() -> Option<PathBuf> {
+    let home = glib::home_dir();
+    [
+        glib::user_config_dir().join("heroic"),
+        home.join(".var")
+            .join("app")
+            .join("com.heroicgameslauncher.hgl")
+            .join("config")
+            .join("heroic"),
+    ]
+    .into_iter()
+    .find(|path| path.join("store_cache").is_dir())
+}
+
+/// Reads the Epic, GOG and Amazon library caches from a Heroic config directory
+pub fn read_games(config_dir: &Path) -> Result<Vec<ImportedGame>> {
+    let playtimes = read_playtimes(&config_dir.join("store").join("timestamp.json"));
+    let mut games = Vec::new();
+
+    for (file_name, key, store) in STORE_CACHES {
+        let path = config_dir.join("store_cache").join(file_name);
+        if !path.exists() {
+            continue;
+        }
+
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut cache: serde_json::Value = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let entries: Vec<HeroicGame> = serde_json::from_value(cache[*key].take())
+            .with_context(|| format!("Unexpected library layout in {}", path.display()))?;
+
+        games.extend(
+            entries
+                .into_iter()
+                .filter(|game| !game.is_dlc && !game.title.trim().is_empty())
+                .map(|game| ImportedGame {
+                    playtime_minutes: playtimes.get(&game.app_name).copied().unwrap_or(0),
+                    title: game.title,
+                    platform: store.to_string(),
+                    installed: game.is_installed,
+                    year: None,
+                    status: None,
+                    source: "heroic",
+                }),
+        );
+    }
+
+    Ok(games)
+}
+
+/// Playtime in minutes per app name. Missing or broken files just mean no playtime.
+fn read_playtimes(path: &Path) -> HashMap<String, u32> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| {
+            serde_json::from_str::<HashMap<String, PlaytimeRecord>>(&contents).ok()
+        })
+        .map(|records| {
+            records
+                .into_iter()
+                .map(|(app_name, record)| (app_name, record.total_played.round() as u32))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("heroic")
+    }
+
+    #[test]
+    fn test_read_games_fixture() {
+        let games = read_games(&fixture_dir()).unwrap();
+        let titles: Vec<&str> = games.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, vec!["Control", "Alan Wake", "Cyberpunk 2077", "Tomb Raider"]);
+
+        let control = &games[0];
+        assert_eq!(control.platform, "Epic Games Store");
+        assert!(control.installed);
+        assert_eq!(control.playtime_minutes, 845);
+
+        let cyberpunk = &games[2];
+        assert_eq!(cyberpunk.platform, "GOG");
+        assert!(!cyberpunk.installed);
+        assert_eq!(cyberpunk.playtime_minutes, 0);
+
+        assert_eq!(games[3].platform, "Amazon Games");
+    }
+
+    #[test]
+    fn test_read_games_empty_config_dir() {
+        let games = read_games(Path::new("/nonexistent/heroic")).unwrap();
+        assert!(games.is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use super::ImportedGame;
+use crate::library::{LibraryEntry, PlayStatus};
+
+/// Column layout of the game list export on HowLongToBeat.com
+pub const COLUMNS: &[&str] = &[
+    "Title",
+    "Platform",
+    "Storefront",
+    "Playing",
+    "Backlog",
+    "Replay",
+    "Custom",
+    "Custom 2",
+    "Custom 3",
+    "Completed",
+    "Retired",
+    "Main Story",
+    "Main + Extras",
+    "Completionist",
+    "Progress",
+    "Review",
+    "Review Notes",
+    "Added",
+];
+
+// When a row is on several lists, the first one here wins
+const STATUS_COLUMNS: &[(&str, PlayStatus)] = &[
+    ("Completed", PlayStatus::Completed),
+    ("Retired", PlayStatus::Retired),
+    ("Playing", PlayStatus::Playing),
+    ("Replay", PlayStatus::Replay),
+    ("Backlog", PlayStatus::Backlog),
+];
+
+/// Reads an HLTB game list export. Columns are matched by header name, so reordered or
+/// missing columns are fine as long as there is a title.
+pub fn read_csv(reader: impl Read) -> Result<Vec<ImportedGame>> {
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+
+    let headers: HashMap<String, usize> = csv_reader
+        .headers()
+        .context("Failed to read CSV header")?
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (name.to_lowercase(), index))
+        .collect();
+
+    if !headers.contains_key("title") {
+        return Err(anyhow::anyhow!(
+            "This doesn't look like a HowLongToBeat export: there is no \"Title\" column"
+        ));
+    }
+
+    let mut games = Vec::new();
+    for (line, record) in csv_reader.records().enumerate() {
+        let record = record.with_context(|| format!("Failed to read CSV row {}", line + 2))?;
+        let field = |name: &str| -> &str {
+            headers
+                .get(&name.to_lowercase())
+                .and_then(|index| record.get(*index))
+                .unwrap_or("")
+        };
+
+        let title = field("Title");
+        if title.is_empty() {
+            continue;
+        }
+
+        let status = STATUS_COLUMNS
+            .iter()
+            .find(|(column, _)| is_marked(field(column)))
+            .map(|(_, status)| *status);
+
+        // Prefer the tracked progress; otherwise use the longest completion time the user logged
+        let playtime_seconds = parse_duration(field("Progress")).unwrap_or_else(|| {
+            ["Main Story", "Main + Extras", "Completionist"]
+                .iter()
+                .filter_map(|column| parse_duration(field(column)))
+                .max()
+                .unwrap_or(0)
+        });
+
+        let platform = match field("Platform") {
+            "" => field("Storefront"),
+            platform => platform,
+        };
+
+        games.push(ImportedGame {
+            title: title.to_string(),
+            platform: platform.to_string(),
+            installed: false,
+            year: None,
+            playtime_minutes: playtime_seconds / 60,
+            status,
+            source: "hltb-csv",
+        });
+    }
+
+    Ok(games)
+}
+
+/// Writes library entries in the same column layout as the HLTB export
+pub fn write_csv(writer: impl Write, entries: &[LibraryEntry]) -> Result<()> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    csv_writer.write_record(COLUMNS)?;
+
+    for entry in entries {
+        let record: Vec<String> = COLUMNS
+            .iter()
+            .map(|column| match *column {
+                "Title" => entry.game.game_name.clone(),
+                "Platform" => entry.platform.clone(),
+                "Storefront" => storefront(&entry.source).to_string(),
+                "Progress" if entry.playtime_minutes > 0 => {
+                    format_duration(entry.playtime_minutes * 60)
+                }
+                "Added" if entry.added > 0 => glib::DateTime::from_unix_utc(entry.added as i64)
+                    .ok()
+                    .and_then(|date| date.format("%Y-%m-%d").ok())
+                    .map(|date| date.to_string())
+                    .unwrap_or_default(),
+                column => STATUS_COLUMNS
+                    .iter()
+                    .find(|(name, status)| *name == column && *status == entry.status)
+                    .map(|_| "X".to_string())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        csv_writer.write_record(&record)?;
+    }
+
+    csv_writer.flush()?;
+    Ok(())
+}
+
+fn is_marked(value: &str) -> bool {
+    !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("no")
+}
+
+fn storefront(source: &str) -> &'static str {
+    match source {
+        "steam" => "Steam",
+        "heroic" => "Heroic",
+        "lutris" => "Lutris",
+        _ => "",
+    }
+}
+
+/// Parses "hh:mm:ss" or "hh:mm" (hours can exceed 24) into seconds. Durations too long to
+/// count in seconds are treated as missing.
+pub fn parse_duration(value: &str) -> Option<u32> {
+    let parts: Vec<u64> = value
+        .split(':')
+        .map(|part| part.trim().parse().ok())
+        .collect::<Option<_>>()?;
+
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [hours, minutes, seconds] => (*hours, *minutes, *seconds),
+        [hours, minutes] => (*hours, *minutes, 0),
+        _ => return None,
+    };
+    let seconds = hours
+        .checked_mul(3600)?
+        .checked_add(minutes.checked_mul(60)?)?
+        .checked_add(seconds)?;
+    u32::try_from(seconds).ok().filter(|seconds| *seconds > 0)
+}
+
+/// Formats seconds as "hh:mm:ss"
+pub fn format_duration(seconds: u32) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Game;
+    use std::path::Path;
+
+    fn fixture() -> std::fs::File {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("hltb")
+            .join("export.csv");
+        std::fs::File::open(path).unwrap()
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("12:30:00"), Some(45000));
+        assert_eq!(parse_duration("120:05"), Some(432300));
+        assert_eq!(parse_duration("--"), None);
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("00:00:00"), None);
+        assert_eq!(parse_duration("9999999:00:00"), None, "Too long to count in seconds");
+        assert_eq!(parse_duration("99999999999999999:00"), None);
+        assert_eq!(parse_duration("1193046:28:15"), Some(u32::MAX));
+        assert_eq!(format_duration(432305), "120:05:05");
+    }
+
+    #[test]
+    fn test_read_csv_fixture() {
+        let games = read_csv(fixture()).unwrap();
+        let titles: Vec<&str> = games.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, vec!["Hades", "Outer Wilds", "Persona 5 Royal", "Disco Elysium"]);
+
+        assert_eq!(games[0].status, Some(PlayStatus::Completed), "Completed wins over Replay");
+        assert_eq!(games[0].playtime_minutes, 2730, "Uses the Progress column");
+        assert_eq!(games[0].platform, "PC");
+
+        assert_eq!(games[1].status, Some(PlayStatus::Backlog));
+        assert_eq!(games[1].platform, "Steam", "Falls back to the storefront");
+
+        assert_eq!(games[2].status, Some(PlayStatus::Retired));
+        assert_eq!(games[2].playtime_minutes, 6000, "Longest logged completion time");
+
+        assert_eq!(games[3].status, None);
+    }
+
+    #[test]
+    fn test_read_csv_rejects_other_files() {
+        assert!(read_csv("name,score\nCeleste,90\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_write_csv_roundtrip() {
+        let entry = LibraryEntry {
+            game: Game {
+                game_id: 1,
+                game_name: "Portal 2".to_string(),
+                ..Default::default()
+            },
+            status: PlayStatus::Playing,
+            playtime_minutes: 754,
+            platform: "PC".to_string(),
+            source: "steam".to_string(),
+            added: 1700000000,
+        };
+
+        let mut output = Vec::new();
+        write_csv(&mut output, &[entry]).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.starts_with("Title,Platform,Storefront,Playing,Backlog"));
+        assert!(text.contains("Portal 2,PC,Steam,X,"));
+        assert!(text.contains("12:34:00"));
+        assert!(text.contains("2023-11-14"));
+
+        let games = read_csv(text.as_bytes()).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].title, "Portal 2");
+        assert_eq!(games[0].status, Some(PlayStatus::Playing));
+        assert_eq!(games[0].playtime_minutes, 754);
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OpenFlags};
+use std::path::{Path, PathBuf};
+
+use super::ImportedGame;
+
+/// Finds Lutris' game database in the usual places (native and Flatpak)
+pub fn find_database() -> Option<PathBuf> {
+    let home = glib::home_dir();
+    [
+        glib::user_data_dir().join("lutris").join("pga.db"),
+        home.join(".var")
+            .join("app")
+            .join("net.lutris.Lutris")
+            .join("data")
+            .join("lutris")
+            .join("pga.db"),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+/// Reads every game from a Lutris `pga.db`
+pub fn read_games(db_path: &Path) -> Result<Vec<ImportedGame>> {
+    // Read-only so we never interfere with a running Lutris
+    let connection = Connection::open_with_flags(
+        db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .with_context(|| format!("Failed to open {}", db_path.display()))?;
+
+    // Databases from older Lutris versions don't have a playtime column
+    let has_playtime = connection
+        .prepare("SELECT 1 FROM pragma_table_info('games') WHERE name = 'playtime'")?
+        .exists([])?;
+    let query = if has_playtime {
+        "SELECT name, platform, runner, installed, year, playtime FROM games ORDER BY name"
+    } else {
+        "SELECT name, platform, runner, installed, year, 0.0 FROM games ORDER BY name"
+    };
+
+    let mut statement = connection.prepare(query)?;
+    let rows = statement.query_map([], |row| {
+        let platform: Option<String> = row.get(1)?;
+        let runner: Option<String> = row.get(2)?;
+        let installed: Option<i64> = row.get(3)?;
+        let year: Option<i64> = row.get(4)?;
+        // Lutris stores playtime as fractional hours
+        let playtime_hours: Option<f64> = row.get(5)?;
+
+        Ok(ImportedGame {
+            title: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+            platform: platform.or(runner).unwrap_or_default(),
+            installed: installed.unwrap_or(0) != 0,
+            year: year.and_then(|y| u32::try_from(y).ok()).filter(|y| *y > 0),
+            playtime_minutes: (playtime_hours.unwrap_or(0.0) * 60.0).round() as u32,
+            status: None,
+            source: "lutris",
+        })
+    })?;
+
+    let mut games = Vec::new();
+    for game in rows {
+        let game = game?;
+        if !game.title.trim().is_empty() {
+            games.push(game);
+        }
+    }
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("lutris")
+            .join(name)
+    }
+
+    #[test]
+    fn test_read_games_fixture() {
+        let games = read_games(&fixture("pga.db")).unwrap();
+        let titles: Vec<&str> = games.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, vec!["Celeste", "Disco Elysium", "The Witcher 3: Wild Hunt"]);
+
+        let witcher = &games[2];
+        assert!(witcher.installed);
+        assert_eq!(witcher.platform, "Windows");
+        assert_eq!(witcher.playtime_minutes, 5430, "90.5 hours in minutes");
+        assert_eq!(witcher.year, Some(2015));
+
+        let celeste = &games[0];
+        assert!(!celeste.installed);
+        assert_eq!(celeste.platform, "linux", "Falls back to the runner without a platform");
+        assert_eq!(celeste.year, None);
+    }
+
+    #[test]
+    fn test_read_games_without_playtime_column() {
+        let games = read_games(&fixture("pga_legacy.db")).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].title, "Hollow Knight");
+        assert_eq!(games[0].playtime_minutes, 0);
+    }
+
+    #[test]
+    fn test_read_games_missing_database() {
+        assert!(read_games(&fixture("missing.db")).is_err());
+    }
+}
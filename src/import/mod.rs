@@ -0,0 +1,393 @@
+pub mod heroic;
+pub mod hltb_csv;
+pub mod lutris;
+pub mod steam;
+
+use anyhow::Result;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::api::{FailureKind, Game, HltbClient};
+use crate::library::{LibraryEntry, PlayStatus};
+use crate::matcher::{self, MatchQuery};
+
+// Pause between HLTB lookups so a large library import doesn't hammer the site
+const LOOKUP_DELAY: Duration = Duration::from_millis(250);
+
+/// A game read from a launcher's local library (Lutris, Heroic) or an exported list
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedGame {
+    pub title: String,
+    /// Platform or store the source reports, e.g. "Linux" or "GOG"
+    pub platform: String,
+    pub installed: bool,
+    pub year: Option<u32>,
+    pub playtime_minutes: u32,
+    /// Play status recorded by the source, if it tracks one
+    pub status: Option<PlayStatus>,
+    /// Where the game came from, stored as the library entry source
+    pub source: &'static str,
+}
+
+/// A launcher game paired with its best HLTB candidate, waiting for the user's review
+#[derive(Debug, Clone)]
+pub struct StagedMatch {
+    pub imported_game: ImportedGame,
+    pub candidate: Option<Game>,
+    /// Match confidence between 0.0 and 1.0
+    pub score: f64,
+    /// Whether the match is selected for import
+    pub include: bool,
+    /// All ranked candidates, for picking a different match by hand
+    pub candidates: Vec<(Game, f64)>,
+    /// Why the lookup failed, like when HLTB couldn't be reached. A failed game isn't unmatched;
+    /// looking it up again may find it.
+    pub failed: Option<String>,
+}
+
+impl ImportedGame {
+    pub fn match_query(&self) -> MatchQuery {
+        MatchQuery {
+            title: self.title.clone(),
+            year: self.year,
+            platform: Some(self.platform.clone()).filter(|p| !p.is_empty()),
+        }
+    }
+}
+
+impl StagedMatch {
+    /// Stages the best of `results`, HLTB's answer for the game's title
+    fn new(imported_game: ImportedGame, results: Result<Vec<Game>>) -> Self {
+        let (candidates, failed) = match results {
+            Ok(results) => (matcher::rank(results, &imported_game.match_query()), None),
+            Err(e) => {
+                eprintln!("Lookup failed for '{}': {:#}", imported_game.title, e);
+                (Vec::new(), Some(FailureKind::of(&e).title()))
+            }
+        };
+
+        // Ambiguous matches are left unselected until the user picks one
+        let (candidate, score) = match candidates.first() {
+            Some((game, score)) => (Some(game.clone()), *score),
+            None => (None, 0.0),
+        };
+        Self {
+            imported_game,
+            include: candidate.is_some() && !matcher::is_ambiguous(&candidates),
+            candidate,
+            score,
+            candidates,
+            failed,
+        }
+    }
+
+    pub fn to_library_entry(&self) -> Option<LibraryEntry> {
+        let game = self.candidate.clone()?;
+        let mut entry = LibraryEntry::new(game, self.imported_game.source);
+        entry.platform = self.imported_game.platform.clone();
+        entry.playtime_minutes = self.imported_game.playtime_minutes;
+        entry.status = match self.imported_game.status {
+            Some(status) => status,
+            None if self.imported_game.playtime_minutes > 0 => PlayStatus::Playing,
+            None => PlayStatus::Backlog,
+        };
+        Some(entry)
+    }
+}
+
+/// Picks the HLTB result for a Steam app, preferring an exact `profile_steam` match
+pub fn pick_steam_match(results: Vec<Game>, app_id: u32, name: &str) -> Option<Game> {
+    let wanted = matcher::normalize_title(name);
+    let mut fallback = None;
+
+    for game in results {
+        if game.profile_steam == app_id {
+            return Some(game);
+        }
+        if fallback.is_none() && matcher::normalize_title(&game.game_name) == wanted {
+            fallback = Some(game);
+        }
+    }
+
+    fallback
+}
+
+/// Looks up a single Steam game on HowLongToBeat
+pub async fn resolve_steam_game(
+    client: &HltbClient,
+    http: &reqwest::Client,
+    game: &steam::SteamGame,
+) -> Result<Option<Game>> {
+    let name = match &game.name {
+        Some(name) => name.clone(),
+        None => steam::fetch_store_name(http, game.app_id).await?,
+    };
+
+    let results = client.search(&name).await?;
+    Ok(pick_steam_match(results, game.app_id, &name))
+}
+
+/// Outcome of a bulk import
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub entries: Vec<LibraryEntry>,
+    /// Titles (or app ids) that couldn't be matched on HLTB
+    pub unmatched: Vec<String>,
+    /// Titles (or app ids) whose lookup failed, like when HLTB couldn't be reached, with the error
+    pub failed: Vec<(String, anyhow::Error)>,
+}
+
+/// Resolves every Steam game on HLTB, one at a time.
+/// `progress` is called with (done, total) after each lookup.
+pub async fn import_steam_games(
+    client: &HltbClient,
+    games: &[steam::SteamGame],
+    progress: impl FnMut(usize, usize),
+) -> ImportSummary {
+    let http = reqwest::Client::new();
+    collect_steam_matches(
+        games,
+        |steam_game| {
+            let http = http.clone();
+            async move {
+                let result = resolve_steam_game(client, &http, &steam_game).await;
+                tokio::time::sleep(LOOKUP_DELAY).await;
+                result
+            }
+        },
+        progress,
+    )
+    .await
+}
+
+/// Builds the import summary from `lookup`'s answer for every Steam game
+async fn collect_steam_matches<F, Fut>(
+    games: &[steam::SteamGame],
+    mut lookup: F,
+    mut progress: impl FnMut(usize, usize),
+) -> ImportSummary
+where
+    F: FnMut(steam::SteamGame) -> Fut,
+    Fut: Future<Output = Result<Option<Game>>>,
+{
+    let mut summary = ImportSummary::default();
+
+    for (index, steam_game) in games.iter().enumerate() {
+        let title = || {
+            steam_game
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Steam app {}", steam_game.app_id))
+        };
+        match lookup(steam_game.clone()).await {
+            Ok(Some(game)) => {
+                let mut entry = LibraryEntry::new(game, "steam");
+                entry.platform = "PC".to_string();
+                entry.playtime_minutes = steam_game.playtime_minutes;
+                entry.status = if steam_game.playtime_minutes > 0 {
+                    PlayStatus::Playing
+                } else {
+                    PlayStatus::Backlog
+                };
+                summary.entries.push(entry);
+            }
+            Ok(None) => summary.unmatched.push(title()),
+            Err(e) => {
+                eprintln!("Lookup failed for '{}': {:#}", title(), e);
+                summary.failed.push((title(), e));
+            }
+        }
+
+        progress(index + 1, games.len());
+    }
+
+    summary
+}
+
+/// Searches HLTB for every imported game, one at a time, and stages the best match for review.
+/// `progress` is called with (done, total) after each lookup.
+pub async fn stage_imported_games(
+    client: &HltbClient,
+    games: Vec<ImportedGame>,
+    progress: impl FnMut(usize, usize),
+) -> Vec<StagedMatch> {
+    stage_matches(
+        games,
+        |title| async move {
+            let results = client.search(&title).await;
+            tokio::time::sleep(LOOKUP_DELAY).await;
+            results
+        },
+        progress,
+    )
+    .await
+}
+
+/// Stages every imported game from `lookup`'s search results for its title
+async fn stage_matches<F, Fut>(
+    games: Vec<ImportedGame>,
+    mut lookup: F,
+    mut progress: impl FnMut(usize, usize),
+) -> Vec<StagedMatch>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<Vec<Game>>>,
+{
+    let total = games.len();
+    let mut staged = Vec::with_capacity(total);
+
+    for (index, imported_game) in games.into_iter().enumerate() {
+        let results = lookup(imported_game.title.clone()).await;
+        staged.push(StagedMatch::new(imported_game, results));
+        progress(index + 1, total);
+    }
+
+    staged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(game_id: u64, name: &str, profile_steam: u32) -> Game {
+        Game {
+            game_id,
+            game_name: name.to_string(),
+            profile_steam,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pick_steam_match_prefers_app_id() {
+        let results = vec![
+            game(1, "Portal 2", 0),
+            game(2, "Portal 2: Community Edition", 0),
+            game(3, "Portal 2", 620),
+        ];
+        assert_eq!(pick_steam_match(results, 620, "Portal 2").unwrap().game_id, 3);
+    }
+
+    #[test]
+    fn test_pick_steam_match_falls_back_to_exact_title() {
+        let results = vec![game(1, "Portal", 400), game(2, "Portal 2", 0)];
+        assert_eq!(pick_steam_match(results, 620, "PORTAL 2").unwrap().game_id, 2);
+    }
+
+    #[test]
+    fn test_pick_steam_match_no_match() {
+        let results = vec![game(1, "Portal", 400)];
+        assert!(pick_steam_match(results, 620, "Portal 2").is_none());
+    }
+
+    #[test]
+    fn test_failed_lookups_are_not_unmatched() {
+        let steam_game = |app_id, name: Option<&str>| steam::SteamGame {
+            app_id,
+            name: name.map(str::to_string),
+            installed: true,
+            playtime_minutes: 0,
+        };
+        let games = vec![
+            steam_game(620, Some("Portal 2")),
+            steam_game(400, Some("Not On HLTB")),
+            steam_game(70, None),
+        ];
+        let mut progress = Vec::new();
+        let summary = glib::MainContext::new().block_on(collect_steam_matches(
+            &games,
+            |steam_game| async move {
+                match steam_game.app_id {
+                    620 => Ok(Some(game(3, "Portal 2", 620))),
+                    400 => Ok(None),
+                    _ => Err(anyhow::anyhow!("HowLongToBeat can't be reached")),
+                }
+            },
+            |done, total| progress.push((done, total)),
+        ));
+
+        assert_eq!(summary.entries.len(), 1);
+        assert_eq!(summary.entries[0].status, PlayStatus::Backlog);
+        assert_eq!(summary.unmatched, vec!["Not On HLTB"]);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].0, "Steam app 70");
+        assert_eq!(summary.failed[0].1.to_string(), "HowLongToBeat can't be reached");
+        assert_eq!(progress, vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn test_failed_lookups_are_staged_as_failed() {
+        let imported_game = |title: &str| ImportedGame {
+            title: title.to_string(),
+            platform: "Linux".to_string(),
+            installed: true,
+            year: None,
+            playtime_minutes: 0,
+            status: None,
+            source: "lutris",
+        };
+        let games = vec![imported_game("Celeste"), imported_game("Not On HLTB"), imported_game("Hades")];
+        let mut progress = Vec::new();
+        let staged = glib::MainContext::new().block_on(stage_matches(
+            games,
+            |title| async move {
+                match title.as_str() {
+                    "Celeste" => Ok(vec![game(1, "Celeste", 0)]),
+                    "Not On HLTB" => Ok(Vec::new()),
+                    _ => Err(anyhow::anyhow!("HowLongToBeat can't be reached")),
+                }
+            },
+            |done, total| progress.push((done, total)),
+        ));
+
+        assert!(staged[0].include && staged[0].failed.is_none());
+        assert_eq!(staged[0].candidate.as_ref().unwrap().game_id, 1);
+        assert!(!staged[1].include && staged[1].candidate.is_none());
+        assert!(staged[1].failed.is_none(), "No match isn't a failure");
+        assert!(!staged[2].include && staged[2].candidate.is_none());
+        assert!(staged[2].failed.is_some());
+        assert_eq!(progress, vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn test_match_query_uses_year_and_platform() {
+        let imported_game = ImportedGame {
+            title: "Tomb Raider".to_string(),
+            platform: String::new(),
+            installed: false,
+            year: Some(2013),
+            playtime_minutes: 0,
+            status: None,
+            source: "lutris",
+        };
+        let query = imported_game.match_query();
+        assert_eq!(query.year, Some(2013));
+        assert_eq!(query.platform, None, "An empty platform isn't a constraint");
+    }
+
+    #[test]
+    fn test_staged_match_to_library_entry() {
+        let staged = StagedMatch {
+            imported_game: ImportedGame {
+                title: "Control".to_string(),
+                platform: "Epic Games Store".to_string(),
+                installed: true,
+                year: None,
+                playtime_minutes: 845,
+                status: None,
+                source: "heroic",
+            },
+            candidate: Some(game(7, "Control", 0)),
+            score: 1.0,
+            include: true,
+            candidates: Vec::new(),
+            failed: None,
+        };
+
+        let entry = staged.to_library_entry().unwrap();
+        assert_eq!(entry.game.game_id, 7);
+        assert_eq!(entry.source, "heroic");
+        assert_eq!(entry.platform, "Epic Games Store");
+        assert_eq!(entry.status, PlayStatus::Playing);
+    }
+}
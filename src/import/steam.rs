@@ -0,0 +1,461 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// Steam tools and runtimes that show up in the library but aren't games
+const NON_GAME_APP_IDS: &[u32] = &[
+    228980,  // Steamworks Common Redistributables
+    1070560, // Steam Linux Runtime 1.0 (scout)
+    1391110, // Steam Linux Runtime 2.0 (soldier)
+    1628350, // Steam Linux Runtime 3.0 (sniper)
+    1493710, // Proton Experimental
+    2180100, // Proton Hotfix
+];
+const NON_GAME_NAME_PREFIXES: &[&str] = &["Proton ", "Steam Linux Runtime", "Steamworks "];
+
+/// A game found in the local Steam installation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SteamGame {
+    pub app_id: u32,
+    /// Name from the app manifest. Only installed games have one locally.
+    pub name: Option<String>,
+    pub installed: bool,
+    /// Total playtime in minutes, as recorded in `localconfig.vdf`
+    pub playtime_minutes: u32,
+}
+
+/// A node in a Valve KeyValues (VDF/ACF) document
+#[derive(Debug, Clone, PartialEq)]
+pub enum VdfValue {
+    Str(String),
+    Map(Vec<(String, VdfValue)>),
+}
+
+impl VdfValue {
+    /// Looks up a child by key. Steam isn't consistent about key casing, so this ignores case.
+    pub fn get(&self, key: &str) -> Option<&VdfValue> {
+        match self {
+            VdfValue::Map(children) => children
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            VdfValue::Str(_) => None,
+        }
+    }
+
+    /// Follows a chain of keys, e.g. `["Software", "Valve", "Steam", "apps"]`
+    pub fn get_path(&self, path: &[&str]) -> Option<&VdfValue> {
+        path.iter().try_fold(self, |node, key| node.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::Str(s) => Some(s),
+            VdfValue::Map(_) => None,
+        }
+    }
+
+    pub fn entries(&self) -> &[(String, VdfValue)] {
+        match self {
+            VdfValue::Map(children) => children,
+            VdfValue::Str(_) => &[],
+        }
+    }
+}
+
+/// Parses a text VDF document into a map of its top-level keys
+pub fn parse_vdf(input: &str) -> Result<VdfValue> {
+    let mut tokens = tokenize(input)?.into_iter().peekable();
+    let root = parse_map(&mut tokens, false)?;
+    Ok(VdfValue::Map(root))
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '{' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            '}' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '/' => {
+                // Line comment
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '[' => {
+                // Platform conditionals like [$WIN32] - we don't evaluate them
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(other) => value.push(other),
+                            None => break,
+                        },
+                        other => value.push(other),
+                    }
+                }
+                if !closed {
+                    return Err(anyhow::anyhow!("Unterminated string in VDF document"));
+                }
+                tokens.push(Token::Str(value));
+            }
+            _ => {
+                // Unquoted token, ends at whitespace or a brace
+                let mut value = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Str(value));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_map(
+    tokens: &mut std::iter::Peekable<std::vec::IntoIter<Token>>,
+    nested: bool,
+) -> Result<Vec<(String, VdfValue)>> {
+    let mut entries = Vec::new();
+
+    loop {
+        let key = match tokens.next() {
+            Some(Token::Str(key)) => key,
+            Some(Token::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            Some(Token::Close) => return Err(anyhow::anyhow!("Unexpected '}}' in VDF document")),
+            Some(Token::Open) => return Err(anyhow::anyhow!("Expected a key before '{{' in VDF document")),
+            None => return Err(anyhow::anyhow!("Unexpected end of VDF document")),
+        };
+
+        let value = match tokens.next() {
+            Some(Token::Str(value)) => VdfValue::Str(value),
+            Some(Token::Open) => VdfValue::Map(parse_map(tokens, true)?),
+            _ => return Err(anyhow::anyhow!("Missing value for key '{}' in VDF document", key)),
+        };
+
+        entries.push((key, value));
+    }
+}
+
+fn read_vdf(path: &Path) -> Result<VdfValue> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_vdf(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Finds the Steam installation in the usual places (native and Flatpak)
+pub fn find_steam_root() -> Option<PathBuf> {
+    let home = glib::home_dir();
+    [
+        home.join(".steam").join("steam"),
+        home.join(".local").join("share").join("Steam"),
+        home.join(".var")
+            .join("app")
+            .join("com.valvesoftware.Steam")
+            .join(".local")
+            .join("share")
+            .join("Steam"),
+    ]
+    .into_iter()
+    .find(|path| path.join("steamapps").is_dir())
+}
+
+/// Library folder paths and the app ids installed in each, from `libraryfolders.vdf`
+pub fn parse_library_folders(doc: &VdfValue) -> Vec<(PathBuf, Vec<u32>)> {
+    let Some(folders) = doc.get("libraryfolders") else {
+        return Vec::new();
+    };
+
+    folders
+        .entries()
+        .iter()
+        .filter(|(key, _)| key.parse::<u32>().is_ok())
+        .filter_map(|(_, folder)| match folder {
+            // Old format: "1" "/path/to/library"
+            VdfValue::Str(path) => Some((PathBuf::from(path), Vec::new())),
+            // New format: "1" { "path" "/path/to/library" "apps" { "620" "12345" } }
+            VdfValue::Map(_) => {
+                let path = folder.get("path")?.as_str()?;
+                let apps = folder
+                    .get("apps")
+                    .map(|apps| {
+                        apps.entries()
+                            .iter()
+                            .filter_map(|(id, _)| id.parse().ok())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Some((PathBuf::from(path), apps))
+            }
+        })
+        .collect()
+}
+
+/// App id and name from an `appmanifest_<id>.acf` document
+pub fn parse_app_manifest(doc: &VdfValue) -> Option<(u32, String)> {
+    let state = doc.get("AppState")?;
+    let app_id = state.get("appid")?.as_str()?.parse().ok()?;
+    let name = state.get("name")?.as_str()?.to_string();
+    Some((app_id, name))
+}
+
+/// Playtime in minutes per app id, from a user's `localconfig.vdf`
+pub fn parse_local_config_playtimes(doc: &VdfValue) -> BTreeMap<u32, u32> {
+    let Some(apps) = doc.get_path(&["UserLocalConfigStore", "Software", "Valve", "Steam", "apps"])
+    else {
+        return BTreeMap::new();
+    };
+
+    apps.entries()
+        .iter()
+        .filter_map(|(id, app)| {
+            let app_id = id.parse().ok()?;
+            let playtime = app
+                .get("Playtime")
+                .and_then(|p| p.as_str())
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(0);
+            Some((app_id, playtime))
+        })
+        .collect()
+}
+
+/// Scans a Steam installation for owned and installed games.
+///
+/// Installed games come from the app manifests in every library folder; owned games that
+/// aren't installed are only known through the playtime records in `localconfig.vdf`, so
+/// they come back without a name.
+pub fn scan_library(steam_root: &Path) -> Result<Vec<SteamGame>> {
+    let mut games: BTreeMap<u32, SteamGame> = BTreeMap::new();
+    let mut library_paths = vec![steam_root.to_path_buf()];
+
+    // Newer clients keep libraryfolders.vdf in config/, older ones in steamapps/
+    for candidate in [
+        steam_root.join("steamapps").join("libraryfolders.vdf"),
+        steam_root.join("config").join("libraryfolders.vdf"),
+    ] {
+        if !candidate.exists() {
+            continue;
+        }
+        for (path, app_ids) in parse_library_folders(&read_vdf(&candidate)?) {
+            for app_id in app_ids {
+                games.entry(app_id).or_insert_with(|| SteamGame {
+                    app_id,
+                    name: None,
+                    installed: true,
+                    playtime_minutes: 0,
+                });
+            }
+            if !library_paths.contains(&path) {
+                library_paths.push(path);
+            }
+        }
+    }
+
+    for library in &library_paths {
+        let Ok(dir) = std::fs::read_dir(library.join("steamapps")) else {
+            continue;
+        };
+        for file in dir.flatten() {
+            let file_name = file.file_name();
+            let file_name = file_name.to_string_lossy();
+            if !(file_name.starts_with("appmanifest_") && file_name.ends_with(".acf")) {
+                continue;
+            }
+            // A single broken manifest shouldn't abort the whole import
+            let Some((app_id, name)) = read_vdf(&file.path()).ok().as_ref().and_then(parse_app_manifest)
+            else {
+                continue;
+            };
+            let game = games.entry(app_id).or_insert_with(|| SteamGame {
+                app_id,
+                name: None,
+                installed: true,
+                playtime_minutes: 0,
+            });
+            game.name = Some(name);
+            game.installed = true;
+        }
+    }
+
+    // Every Steam account that logged in on this machine has its own userdata folder
+    if let Ok(users) = std::fs::read_dir(steam_root.join("userdata")) {
+        for user in users.flatten() {
+            let config = user.path().join("config").join("localconfig.vdf");
+            if !config.exists() {
+                continue;
+            }
+            // One user's broken config shouldn't abort the import either
+            let doc = match read_vdf(&config) {
+                Ok(doc) => doc,
+                Err(e) => {
+                    eprintln!("Skipping {}: {:#}", config.display(), e);
+                    continue;
+                }
+            };
+            for (app_id, playtime) in parse_local_config_playtimes(&doc) {
+                let game = games.entry(app_id).or_insert_with(|| SteamGame {
+                    app_id,
+                    name: None,
+                    installed: false,
+                    playtime_minutes: 0,
+                });
+                game.playtime_minutes = game.playtime_minutes.max(playtime);
+            }
+        }
+    }
+
+    Ok(games
+        .into_values()
+        .filter(|game| !is_non_game(game))
+        .collect())
+}
+
+fn is_non_game(game: &SteamGame) -> bool {
+    NON_GAME_APP_IDS.contains(&game.app_id)
+        || game
+            .name
+            .as_deref()
+            .is_some_and(|name| NON_GAME_NAME_PREFIXES.iter().any(|p| name.starts_with(p)))
+}
+
+/// Looks up the store name of an app that isn't installed locally
+pub async fn fetch_store_name(client: &reqwest::Client, app_id: u32) -> Result<String> {
+    let url = format!(
+        "https://store.steampowered.com/api/appdetails?appids={}&filters=basic",
+        app_id
+    );
+    let response: serde_json::Value = client.get(&url).send().await?.json().await?;
+
+    response[app_id.to_string()]["data"]["name"]
+        .as_str()
+        .map(|name| name.to_string())
+        .ok_or_else(|| anyhow::anyhow!("Steam store has no details for app {}", app_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("steam")
+    }
+
+    #[test]
+    fn test_parse_vdf_nested_and_escaped() {
+        let doc = parse_vdf(
+            r#"
+            // comment line
+            "Root"
+            {
+                "key"   "value with \"quotes\""
+                "Nested" { "Inner" "1" }
+                unquoted token
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(doc.get_path(&["root", "KEY"]).and_then(|v| v.as_str()), Some("value with \"quotes\""));
+        assert_eq!(doc.get_path(&["Root", "nested", "inner"]).and_then(|v| v.as_str()), Some("1"));
+        assert_eq!(doc.get_path(&["Root", "unquoted"]).and_then(|v| v.as_str()), Some("token"));
+    }
+
+    #[test]
+    fn test_parse_vdf_rejects_unbalanced_braces() {
+        assert!(parse_vdf(r#""Root" { "key" "value""#).is_err());
+        assert!(parse_vdf(r#""key" "value" }"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_library_folders_both_formats() {
+        let new_format = read_vdf(&fixture_root().join("steamapps").join("libraryfolders.vdf")).unwrap();
+        let folders = parse_library_folders(&new_format);
+        assert_eq!(folders.len(), 2);
+        assert_eq!(folders[1].0, PathBuf::from("/mnt/games/SteamLibrary"));
+        assert_eq!(folders[1].1, vec![1145360]);
+
+        let old_format = parse_vdf(
+            r#""LibraryFolders" { "TimeNextStatsReport" "1700000000" "1" "/mnt/old" }"#,
+        )
+        .unwrap();
+        let folders = parse_library_folders(&old_format);
+        assert_eq!(folders, vec![(PathBuf::from("/mnt/old"), Vec::new())]);
+    }
+
+    #[test]
+    fn test_parse_app_manifest() {
+        let doc = read_vdf(&fixture_root().join("steamapps").join("appmanifest_620.acf")).unwrap();
+        assert_eq!(parse_app_manifest(&doc), Some((620, "Portal 2".to_string())));
+    }
+
+    #[test]
+    fn test_scan_library_fixture() {
+        // User 11111111's localconfig.vdf is cut off; the other users are still read
+        let games = scan_library(&fixture_root()).unwrap();
+
+        let portal = games.iter().find(|g| g.app_id == 620).expect("Portal 2 is installed");
+        assert_eq!(portal.name.as_deref(), Some("Portal 2"));
+        assert!(portal.installed);
+        assert_eq!(portal.playtime_minutes, 754, "Should take the larger playtime across users");
+
+        // Listed in libraryfolders.vdf but its library isn't mounted, so there's no manifest
+        let hades = games.iter().find(|g| g.app_id == 1145360).expect("Hades is installed");
+        assert_eq!(hades.name, None);
+        assert!(hades.installed);
+
+        // Only known from localconfig.vdf
+        let celeste = games.iter().find(|g| g.app_id == 504230).expect("Celeste is owned");
+        assert!(!celeste.installed);
+        assert_eq!(celeste.playtime_minutes, 1260);
+
+        assert!(
+            games.iter().all(|g| g.app_id != 1493710 && g.app_id != 228980),
+            "Proton and redistributables should be filtered out"
+        );
+    }
+}
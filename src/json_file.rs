@@ -0,0 +1,35 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+/// Writes `value` as JSON to `path`, creating the parent directory if needed. The JSON goes to
+/// a `.json.tmp` file first and replaces `path` in one rename, so a crash or a full disk can't
+/// leave a truncated file behind.
+pub fn write_json_atomically<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, serde_json::to_string(value)?)
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_json_atomically() {
+        let dir = std::env::temp_dir().join(format!("hltb-json-file-test-{}", std::process::id()));
+        let path = dir.join("nested").join("file.json");
+
+        write_json_atomically(&path, &vec![1, 2]).unwrap();
+        write_json_atomically(&path, &vec![3]).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[3]");
+        assert!(!path.with_extension("json.tmp").exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,323 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::Game;
+use crate::i18n::gettext;
+use crate::json_file::write_json_atomically;
+
+// Bump this when the on-disk layout changes in a non backwards compatible way
+const LIBRARY_FORMAT_VERSION: u32 = 1;
+
+/// Play status of a library entry, mirroring the lists used on HowLongToBeat.com
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayStatus {
+    Playing,
+    #[default]
+    Backlog,
+    Replay,
+    Completed,
+    Retired,
+}
+
+impl PlayStatus {
+    pub fn label(&self) -> String {
+        match self {
+            PlayStatus::Playing => gettext("Playing"),
+            PlayStatus::Backlog => gettext("Backlog"),
+            PlayStatus::Replay => gettext("Replay"),
+            PlayStatus::Completed => gettext("Completed"),
+            PlayStatus::Retired => gettext("Retired"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LibraryEntry {
+    pub game: Game,
+    pub status: PlayStatus,
+    /// Time actually played, in minutes
+    pub playtime_minutes: u32,
+    /// Platform the user owns the game on (may differ from `game.profile_platform`)
+    pub platform: String,
+    /// Where the entry came from, e.g. "steam" or "manual"
+    pub source: String,
+    /// Unix timestamp of when the entry was added
+    pub added: u64,
+}
+
+impl LibraryEntry {
+    pub fn new(game: Game, source: &str) -> Self {
+        Self {
+            game,
+            source: source.to_string(),
+            added: unix_now(),
+            ..Default::default()
+        }
+    }
+
+    /// Get the played time in hours
+    pub fn playtime_hours(&self) -> f64 {
+        self.playtime_minutes as f64 / 60.0
+    }
+}
+
+/// What adding a game that's already in the library does to its play status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusMerge {
+    /// Keep the status the user has; for sources that only guess one, like launchers
+    Keep,
+    /// Take the incoming status, for sources that track it, like a HowLongToBeat export
+    Replace,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LibraryFile {
+    version: u32,
+    entries: Vec<LibraryEntry>,
+}
+
+/// The user's local game library, stored as JSON in the user data directory
+pub struct Library {
+    /// Where the library is saved; `None` keeps it in memory only
+    path: Option<PathBuf>,
+    entries: Vec<LibraryEntry>,
+}
+
+impl Library {
+    /// Default location: `$XDG_DATA_HOME/howlongtobeat/library.json`
+    pub fn default_path() -> PathBuf {
+        glib::user_data_dir()
+            .join("howlongtobeat")
+            .join("library.json")
+    }
+
+    /// Loads the library from `path`. A file that can't be read is left alone, since it may
+    /// hold games the user cares about, and the library goes to a `.recovered.json` file beside
+    /// it instead. If that can't be read either, the library is only kept in memory.
+    pub fn load_or_recover(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let error = match Self::load_from(&path) {
+            Ok(library) => return library,
+            Err(e) => e,
+        };
+        eprintln!("Failed to load library: {:#}", error);
+
+        let recovered = path.with_extension("recovered.json");
+        match Self::load_from(&recovered) {
+            Ok(library) => library,
+            Err(e) => {
+                eprintln!("Failed to load the recovered library, not saving the library: {:#}", e);
+                Self {
+                    path: None,
+                    entries: Vec::new(),
+                }
+            }
+        }
+    }
+
+    /// Loads the library from `path`, starting empty if the file doesn't exist yet
+    pub fn load_from(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if !path.exists() {
+            return Ok(Self {
+                path: Some(path),
+                entries: Vec::new(),
+            });
+        }
+
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read library file {}", path.display()))?;
+        let file: LibraryFile = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse library file {}", path.display()))?;
+
+        if file.version > LIBRARY_FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Library file {} was written by a newer version of the app (format {})",
+                path.display(),
+                file.version
+            ));
+        }
+
+        Ok(Self {
+            path: Some(path),
+            entries: file.entries,
+        })
+    }
+
+    /// Writes the library back to disk, creating the parent directory if needed
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = LibraryFile {
+            version: LIBRARY_FORMAT_VERSION,
+            entries: self.entries.clone(),
+        };
+        write_json_atomically(path, &file)
+    }
+
+    pub fn entries(&self) -> &[LibraryEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds an entry, or merges it into the existing entry for the same game, taking its status
+    /// as `status_merge` says. Returns true if a new entry was added.
+    pub fn add_or_update(&mut self, entry: LibraryEntry, status_merge: StatusMerge) -> bool {
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.game.game_id == entry.game.game_id)
+        {
+            Some(existing) => {
+                // Keep the larger playtime so re-importing never loses hours
+                existing.playtime_minutes = existing.playtime_minutes.max(entry.playtime_minutes);
+                existing.game = entry.game;
+                if existing.platform.is_empty() {
+                    existing.platform = entry.platform;
+                }
+                if status_merge == StatusMerge::Replace {
+                    existing.status = entry.status;
+                }
+                false
+            }
+            None => {
+                self.entries.push(entry);
+                true
+            }
+        }
+    }
+
+    /// Adds several entries at once. Returns how many of them were new.
+    pub fn add_all(&mut self, entries: impl IntoIterator<Item = LibraryEntry>, status_merge: StatusMerge) -> usize {
+        entries
+            .into_iter()
+            .filter(|entry| entry.game.game_id != 0)
+            .map(|entry| self.add_or_update(entry, status_merge))
+            .filter(|added| *added)
+            .count()
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(game_id: u64, playtime_minutes: u32) -> LibraryEntry {
+        LibraryEntry {
+            game: Game {
+                game_id,
+                game_name: format!("Game {}", game_id),
+                ..Default::default()
+            },
+            playtime_minutes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_add_or_update_deduplicates_by_game_id() {
+        let mut library = Library::load_from("/nonexistent/library.json").unwrap();
+        assert!(library.is_empty());
+
+        assert!(library.add_or_update(entry(1, 30), StatusMerge::Keep));
+        assert!(!library.add_or_update(entry(1, 10), StatusMerge::Keep));
+        assert_eq!(library.entries().len(), 1);
+        assert_eq!(library.entries()[0].playtime_minutes, 30, "Should keep the larger playtime");
+    }
+
+    #[test]
+    fn test_reimport_with_changed_status() {
+        let mut library = Library::load_from("/nonexistent/library.json").unwrap();
+        let with_status = |status| LibraryEntry {
+            status,
+            ..entry(1, 0)
+        };
+        library.add_all(vec![with_status(PlayStatus::Backlog)], StatusMerge::Keep);
+
+        // A launcher's guess doesn't undo what the user set
+        library.add_all(vec![with_status(PlayStatus::Playing)], StatusMerge::Keep);
+        assert_eq!(library.entries()[0].status, PlayStatus::Backlog);
+
+        // An HLTB export knows better
+        let added = library.add_all(vec![with_status(PlayStatus::Completed)], StatusMerge::Replace);
+        assert_eq!(added, 0);
+        assert_eq!(library.entries()[0].status, PlayStatus::Completed);
+    }
+
+    #[test]
+    fn test_add_all_skips_unresolved_games() {
+        let mut library = Library::load_from("/nonexistent/library.json").unwrap();
+        let added = library.add_all(vec![entry(1, 0), entry(0, 0), entry(2, 0), entry(1, 5)], StatusMerge::Keep);
+        assert_eq!(added, 2);
+        let ids: Vec<u64> = library.entries().iter().map(|e| e.game.game_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_save_and_reload_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("hltb-library-test-{}", std::process::id()));
+        let path = dir.join("library.json");
+
+        let mut library = Library::load_from(&path).unwrap();
+        let mut played = entry(42, 90);
+        played.status = PlayStatus::Completed;
+        played.source = "steam".to_string();
+        library.add_or_update(played, StatusMerge::Keep);
+        library.save().unwrap();
+
+        let reloaded = Library::load_from(&path).unwrap();
+        assert_eq!(reloaded.entries().len(), 1);
+        assert_eq!(reloaded.entries()[0].game.game_id, 42);
+        assert_eq!(reloaded.entries()[0].status, PlayStatus::Completed);
+        assert_eq!(reloaded.entries()[0].playtime_hours(), 1.5);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unreadable_library_is_left_alone() {
+        let dir = std::env::temp_dir().join(format!("hltb-library-recover-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("library.json");
+        let recovered = dir.join("library.recovered.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        // Imports go beside the broken file
+        let mut library = Library::load_or_recover(&path);
+        library.add_or_update(entry(1, 0), StatusMerge::Keep);
+        library.save().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not json");
+        assert_eq!(Library::load_from(&recovered).unwrap().entries().len(), 1);
+
+        // With both unreadable, the library lives in memory and touches neither
+        std::fs::write(&recovered, "not json either").unwrap();
+        let mut library = Library::load_or_recover(&path);
+        assert!(library.is_empty());
+        library.add_or_update(entry(2, 0), StatusMerge::Keep);
+        library.save().unwrap();
+        assert_eq!(std::fs::read_to_string(&recovered).unwrap(), "not json either");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_play_status_serialization() {
+        let json = serde_json::to_string(&PlayStatus::Completed).unwrap();
+        assert_eq!(json, "\"completed\"");
+    }
+}
@@ -0,0 +1,146 @@
+/// URI scheme registered by the desktop file
+const URI_SCHEME: &str = "hltb";
+
+const HLTB_HOSTS: &[&str] = &["howlongtobeat.com", "www.howlongtobeat.com"];
+const STEAM_HOSTS: &[&str] = &["store.steampowered.com", "steamcommunity.com"];
+
+/// Something the app can be pointed at from outside
+#[derive(Debug, Clone, PartialEq)]
+pub enum Link {
+    /// A game by its HowLongToBeat id
+    Game(u64),
+    /// A search for this text
+    Search(String),
+    /// A game by its Steam app id
+    SteamApp(u32),
+}
+
+/// Parses `hltb://game/<id>`, `hltb://search/<query>` and HowLongToBeat game page links.
+/// Links to the website work with or without the `https://` part, as they're often pasted.
+pub fn parse(text: &str) -> Option<Link> {
+    let text = text.trim();
+
+    if let Some(rest) = strip_scheme(text, URI_SCHEME) {
+        let (kind, value) = rest.split_once('/')?;
+        return match kind {
+            "game" => parse_id(value).map(Link::Game),
+            "search" => {
+                let query = unescape(value.split(['?', '#']).next().unwrap_or_default());
+                let query = query.trim();
+                (!query.is_empty()).then(|| Link::Search(query.to_string()))
+            }
+            _ => None,
+        };
+    }
+
+    let (host, path) = split_web_link(text)?;
+    if !HLTB_HOSTS.contains(&host.as_str()) {
+        return None;
+    }
+    path.strip_prefix("game/").and_then(parse_id).map(Link::Game)
+}
+
+/// Parses what people paste into the search field: anything [`parse`] accepts,
+/// Steam store links and bare HowLongToBeat game ids
+pub fn parse_pasted(text: &str) -> Option<Link> {
+    let text = text.trim();
+    if let Some(link) = parse(text) {
+        return Some(link);
+    }
+    if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) {
+        return parse_id(text).map(Link::Game);
+    }
+
+    if let Some(rest) = strip_scheme(text, "steam") {
+        let id = rest.strip_prefix("store/").or_else(|| rest.strip_prefix("run/"))?;
+        return parse_id(id).and_then(|id| u32::try_from(id).ok()).map(Link::SteamApp);
+    }
+    let (host, path) = split_web_link(text)?;
+    if !STEAM_HOSTS.contains(&host.as_str()) {
+        return None;
+    }
+    path.strip_prefix("app/")
+        .and_then(parse_id)
+        .and_then(|id| u32::try_from(id).ok())
+        .map(Link::SteamApp)
+}
+
+/// Splits a web link, with or without its `http(s)://` part, into a lowercase host and the path
+fn split_web_link(text: &str) -> Option<(String, &str)> {
+    let rest = strip_scheme(text, "https")
+        .or_else(|| strip_scheme(text, "http"))
+        .unwrap_or(text);
+    let (host, path) = rest.split_once('/')?;
+    Some((host.to_lowercase(), path))
+}
+
+fn strip_scheme<'a>(text: &'a str, scheme: &str) -> Option<&'a str> {
+    let (found, rest) = text.split_once("://")?;
+    found.eq_ignore_ascii_case(scheme).then_some(rest)
+}
+
+/// Reads the id at the start of a path, ignoring anything after it like `/reviews` or `?q=`
+fn parse_id(path: &str) -> Option<u64> {
+    let id = path.split(['/', '?', '#']).next()?;
+    id.parse().ok().filter(|id| *id > 0)
+}
+
+fn unescape(text: &str) -> String {
+    // Bots write spaces as `+`; a literal plus arrives as `%2B`
+    let text = text.replace('+', " ");
+    glib::Uri::unescape_string(&text, None::<&str>)
+        .map(|text| text.to_string())
+        .unwrap_or(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_uris() {
+        assert_eq!(parse("hltb://game/10270"), Some(Link::Game(10270)));
+        assert_eq!(parse("HLTB://game/10270/"), Some(Link::Game(10270)));
+        assert_eq!(parse("hltb://search/hollow%20knight"), Some(Link::Search("hollow knight".to_string())));
+        assert_eq!(parse("hltb://search/celeste+2018"), Some(Link::Search("celeste 2018".to_string())));
+        assert_eq!(parse("hltb://search/c%2B%2B"), Some(Link::Search("c++".to_string())));
+
+        assert_eq!(parse("hltb://game/abc"), None);
+        assert_eq!(parse("hltb://game/0"), None);
+        assert_eq!(parse("hltb://search/"), None);
+        assert_eq!(parse("hltb://library"), None);
+    }
+
+    #[test]
+    fn test_website_links() {
+        assert_eq!(parse("https://howlongtobeat.com/game/10270"), Some(Link::Game(10270)));
+        assert_eq!(parse("http://www.howlongtobeat.com/game/10270"), Some(Link::Game(10270)));
+        assert_eq!(parse("  howlongtobeat.com/game/10270/reviews "), Some(Link::Game(10270)));
+        assert_eq!(parse("https://HowLongToBeat.com/game/10270?tab=dlc#top"), Some(Link::Game(10270)));
+
+        assert_eq!(parse("https://howlongtobeat.com/user/someone"), None);
+        assert_eq!(parse("https://example.com/game/10270"), None);
+        assert_eq!(parse("the witcher 3"), None);
+        assert_eq!(parse("10270"), None);
+    }
+
+    #[test]
+    fn test_pasted_links() {
+        assert_eq!(parse_pasted("https://howlongtobeat.com/game/10270"), Some(Link::Game(10270)));
+        assert_eq!(parse_pasted(" 10270 "), Some(Link::Game(10270)));
+        assert_eq!(
+            parse_pasted("https://store.steampowered.com/app/292030/The_Witcher_3_Wild_Hunt/"),
+            Some(Link::SteamApp(292030))
+        );
+        assert_eq!(parse_pasted("store.steampowered.com/app/292030"), Some(Link::SteamApp(292030)));
+        assert_eq!(parse_pasted("https://steamcommunity.com/app/292030/reviews/"), Some(Link::SteamApp(292030)));
+        assert_eq!(parse_pasted("steam://store/292030"), Some(Link::SteamApp(292030)));
+
+        assert_eq!(parse_pasted("0"), None);
+        assert_eq!(parse_pasted("99999999999999999999"), None);
+        assert_eq!(parse_pasted("https://store.steampowered.com/app/99999999999"), None);
+        assert_eq!(parse_pasted("https://store.steampowered.com/bundle/1234"), None);
+        assert_eq!(parse_pasted("witcher 3"), None);
+        assert_eq!(parse_pasted(""), None);
+    }
+}
@@ -0,0 +1,378 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::api::Game;
+use crate::matcher::{numerals_starting_with, title_words};
+
+/// How well a word of the query matched a word of a game, best last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Match {
+    Typo,
+    Prefix,
+    Exact,
+}
+
+impl Match {
+    /// Points for a game whose name, or only its alias, has the word
+    fn score(self, alias: bool) -> u32 {
+        let score = match self {
+            Match::Typo => 2,
+            Match::Prefix => 4,
+            Match::Exact => 6,
+        };
+        if alias {
+            score - 1
+        } else {
+            score
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Posting {
+    doc: u32,
+    /// Whether the word is only in the game's alias
+    alias: bool,
+}
+
+/// A search over every game the app has come across: the library, cached results and games
+/// looked up for imports. Finds games by the start of the last word typed, by whole words with
+/// a typo or two, and by their aliases, without going to HowLongToBeat.
+///
+/// Games are kept by id; adding one again refreshes its data. Words of a name a game no longer
+/// has keep pointing at it, which only matters for the rare game that gets renamed.
+#[derive(Debug, Default)]
+pub struct LocalIndex {
+    games: Vec<Game>,
+    docs: HashMap<u64, u32>,
+    terms: BTreeMap<String, Vec<Posting>>,
+}
+
+impl LocalIndex {
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    /// Adds a game, or refreshes the one with the same id. Games without an id are skipped.
+    pub fn insert(&mut self, game: Game) {
+        if game.game_id == 0 {
+            return;
+        }
+        match self.docs.get(&game.game_id).copied() {
+            Some(doc) => {
+                let known = &self.games[doc as usize];
+                let renamed = known.game_name != game.game_name || known.game_alias != game.game_alias;
+                self.games[doc as usize] = game;
+                if renamed {
+                    self.index(doc, true);
+                }
+            }
+            None => {
+                let doc = self.games.len() as u32;
+                self.docs.insert(game.game_id, doc);
+                self.games.push(game);
+                self.index(doc, false);
+            }
+        }
+    }
+
+    pub fn extend(&mut self, games: impl IntoIterator<Item = Game>) {
+        for game in games {
+            self.insert(game);
+        }
+    }
+
+    /// Every game in the index, in the order they were added
+    pub fn games(&self) -> &[Game] {
+        &self.games
+    }
+
+    /// Lists the game under the words of its name and alias. Only a game indexed before, now
+    /// renamed, can already be listed under some of them.
+    fn index(&mut self, doc: u32, indexed_before: bool) {
+        let game = &self.games[doc as usize];
+        let mut words: Vec<(String, bool)> = Vec::new();
+        for word in title_words(&game.game_name) {
+            if !words.iter().any(|(known, _)| *known == word) {
+                words.push((word, false));
+            }
+        }
+        for word in title_words(&game.game_alias) {
+            if !words.iter().any(|(known, _)| *known == word) {
+                words.push((word, true));
+            }
+        }
+
+        for (word, alias) in words {
+            let postings = self.terms.entry(word).or_default();
+            if indexed_before && postings.iter().any(|posting| posting.doc == doc) {
+                continue;
+            }
+            postings.push(Posting { doc, alias });
+        }
+    }
+
+    /// Up to `limit` games matching every word of `query`, best first. The last word may be
+    /// unfinished; other words match whole, or with a typo when nothing matches exactly.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<Game> {
+        let tokens = title_words(query);
+        let Some(last) = tokens.len().checked_sub(1) else {
+            return Vec::new();
+        };
+        let still_typing = !query.ends_with(char::is_whitespace);
+        // Normalizing turns a started "vi…" into "6", so look for the word as typed, and for the
+        // numerals it starts, as well
+        let typed_last = query
+            .split(|c: char| !c.is_alphanumeric())
+            .rfind(|word| !word.is_empty())
+            .map(str::to_lowercase);
+
+        let mut scores: Option<HashMap<u32, u32>> = None;
+        for (i, token) in tokens.iter().enumerate() {
+            let mut matches: HashMap<u32, u32> = HashMap::new();
+            self.collect(token, Match::Exact, &mut matches);
+            if i == last && still_typing {
+                self.collect_prefixed(token, &mut matches);
+                if let Some(typed) = typed_last.as_deref().filter(|typed| typed != token) {
+                    self.collect_prefixed(typed, &mut matches);
+                }
+                for digits in typed_last.as_deref().into_iter().flat_map(numerals_starting_with) {
+                    self.collect(digits, Match::Prefix, &mut matches);
+                }
+            }
+            if matches.is_empty() {
+                self.collect_typos(token, &mut matches);
+            }
+
+            let combined = match scores {
+                None => matches,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(doc, score)| matches.get(&doc).map(|more| (doc, score + more)))
+                    .collect(),
+            };
+            if combined.is_empty() {
+                return Vec::new();
+            }
+            scores = Some(combined);
+        }
+
+        let query_title = tokens.join(" ");
+        let mut ranked: Vec<(u32, u32)> = scores.unwrap_or_default().into_iter().collect();
+        ranked.sort_by_cached_key(|&(doc, score)| {
+            let game = &self.games[doc as usize];
+            (
+                Reverse(score),
+                // A game named exactly what was typed beats longer names
+                title_words(&game.game_name).join(" ") != query_title,
+                Reverse(game.count_comp),
+                game.game_name.len(),
+                doc,
+            )
+        });
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(doc, _)| self.games[doc as usize].clone())
+            .collect()
+    }
+
+    fn collect(&self, term: &str, kind: Match, matches: &mut HashMap<u32, u32>) {
+        for posting in self.terms.get(term).into_iter().flatten() {
+            let score = matches.entry(posting.doc).or_default();
+            *score = (*score).max(kind.score(posting.alias));
+        }
+    }
+
+    fn collect_prefixed(&self, prefix: &str, matches: &mut HashMap<u32, u32>) {
+        let terms = self
+            .terms
+            .range::<str, _>((std::ops::Bound::Included(prefix), std::ops::Bound::Unbounded))
+            .take_while(|(term, _)| term.starts_with(prefix));
+        for (term, _) in terms {
+            if term != prefix {
+                self.collect(term, Match::Prefix, matches);
+            }
+        }
+    }
+
+    fn collect_typos(&self, token: &str, matches: &mut HashMap<u32, u32>) {
+        let allowed = allowed_typos(token);
+        if allowed == 0 {
+            return;
+        }
+        let length = token.chars().count();
+        for term in self.terms.keys() {
+            if term.chars().count().abs_diff(length) <= allowed && within_distance(token, term, allowed) {
+                self.collect(term, Match::Typo, matches);
+            }
+        }
+    }
+}
+
+/// Typos forgiven in a word: none in short words, where they'd match too much
+fn allowed_typos(word: &str) -> usize {
+    match word.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Whether `a` turns into `b` with at most `max` inserted, removed or changed letters
+fn within_distance(a: &str, b: &str, max: usize) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        // Every path through this row already costs too much
+        if current.iter().all(|&distance| distance > max) {
+            return false;
+        }
+        previous = current;
+    }
+    previous[b.len()] <= max
+}
+
+/// The local hits as shown while searching, each refreshed from the remote results if they
+/// have it, followed by the remote results that weren't local hits
+pub fn merge(local: Vec<Game>, remote: Vec<Game>) -> Vec<Game> {
+    let mut remote_by_id: HashMap<u64, Game> = HashMap::new();
+    let mut rest = Vec::new();
+    let local_ids: Vec<u64> = local.iter().map(|game| game.game_id).collect();
+    for game in remote {
+        if local_ids.contains(&game.game_id) {
+            remote_by_id.insert(game.game_id, game);
+        } else {
+            rest.push(game);
+        }
+    }
+    local
+        .into_iter()
+        .map(|game| remote_by_id.remove(&game.game_id).unwrap_or(game))
+        .chain(rest)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(game_id: u64, name: &str, alias: &str, count_comp: u32) -> Game {
+        Game {
+            game_id,
+            game_name: name.to_string(),
+            game_alias: alias.to_string(),
+            count_comp,
+            ..Default::default()
+        }
+    }
+
+    fn index() -> LocalIndex {
+        let mut index = LocalIndex::default();
+        index.extend([
+            game(1, "The Witcher 3: Wild Hunt", "Wiedzmin 3", 900),
+            game(2, "The Witcher", "", 300),
+            game(3, "Final Fantasy VII", "FF7", 800),
+            game(4, "Final Fantasy VII Remake", "FFVII Remake", 500),
+            game(5, "Hollow Knight", "", 700),
+            game(6, "Knights of the Old Republic", "KOTOR", 400),
+            game(7, "Pokémon Yellow", "", 200),
+            game(0, "No Id", "", 0),
+        ]);
+        index
+    }
+
+    fn ids(games: &[Game]) -> Vec<u64> {
+        games.iter().map(|game| game.game_id).collect()
+    }
+
+    #[test]
+    fn test_prefix() {
+        let index = index();
+        assert_eq!(index.len(), 7, "Games without an id aren't indexed");
+        assert_eq!(ids(&index.search("witc", 10)), vec![1, 2]);
+        assert_eq!(ids(&index.search("the witcher", 10)), vec![2, 1], "An exact name goes first");
+        assert_eq!(ids(&index.search("hollow kn", 10)), vec![5]);
+        // Only the last word is taken as unfinished
+        assert!(index.search("witc 3", 10).is_empty());
+        assert!(index.search("hollow kn ", 10).is_empty());
+        assert!(index.search("", 10).is_empty());
+    }
+
+    #[test]
+    fn test_numerals_and_aliases() {
+        let index = index();
+        assert_eq!(ids(&index.search("final fantasy 7", 10)), vec![3, 4]);
+        assert_eq!(ids(&index.search("final fantasy vi", 10)), vec![3, 4], "A started numeral still matches");
+        assert_eq!(ids(&index.search("kotor", 10)), vec![6]);
+        assert_eq!(ids(&index.search("ff7", 10)), vec![3]);
+        assert_eq!(ids(&index.search("wiedzmin", 10)), vec![1]);
+        assert_eq!(ids(&index.search("pokemon", 10)), vec![7], "Accents don't matter");
+        // The name counts for more than the alias
+        assert_eq!(ids(&index.search("remake", 10)), vec![4]);
+    }
+
+    #[test]
+    fn test_typos() {
+        let index = index();
+        assert_eq!(ids(&index.search("hollow knigt ", 10)), vec![5]);
+        assert_eq!(ids(&index.search("finl fantasy", 10)), vec![3, 4]);
+        // Short words have to be right
+        assert!(index.search("kot ", 10).is_empty());
+        assert!(within_distance("witcher", "witcher", 0));
+        assert!(within_distance("witcher", "wticher", 2));
+        assert!(!within_distance("witcher", "hunt", 2));
+    }
+
+    #[test]
+    fn test_refresh() {
+        let mut index = index();
+        index.insert(game(5, "Hollow Knight", "", 1200));
+        assert_eq!(index.len(), 7);
+        assert_eq!(index.search("hollow", 10)[0].count_comp, 1200);
+
+        index.insert(game(5, "Hollow Knight", "HK", 1200));
+        assert_eq!(ids(&index.search("hk", 10)), vec![5]);
+        assert_eq!(ids(&index.search("hollow", 10)), vec![5], "Refreshing doesn't list a game twice");
+    }
+
+    #[test]
+    fn test_merge() {
+        let local = vec![game(2, "The Witcher", "", 300), game(1, "The Witcher 3", "", 900)];
+        let remote = vec![game(1, "The Witcher 3", "", 950), game(7, "The Witcher 2", "", 600)];
+        let merged = merge(local, remote);
+        assert_eq!(ids(&merged), vec![2, 1, 7]);
+        assert_eq!(merged[1].count_comp, 950, "Local hits are refreshed from the remote results");
+    }
+
+    #[test]
+    fn test_many_games() {
+        let syllables = ["ka", "lo", "mi", "ren", "to", "vas", "qui", "dor", "sel", "zan", "bri", "tho"];
+        let word = |n: usize| -> String { (0..3).map(|i| syllables[(n / 12usize.pow(i)) % 12]).collect() };
+        let mut index = LocalIndex::default();
+        index.extend((1..=30_000u64).map(|id| {
+            let n = id as usize;
+            game(id, &format!("{} {} {}", word(n), word(n * 7 + 3), word(n * 13 + 5)), "", (n % 500) as u32)
+        }));
+        assert_eq!(index.len(), 30_000);
+
+        let start = std::time::Instant::now();
+        for n in 0..100 {
+            let name = index.games()[n * 250].game_name.clone();
+            let typed = &name[..name.len() - 2];
+            assert!(ids(&index.search(typed, 20)).contains(&(n as u64 * 250 + 1)), "{} finds {}", typed, name);
+            index.search(&format!("{}x", word(n)), 20);
+        }
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(10),
+            "200 searches over 30000 games took {:?}",
+            start.elapsed()
+        );
+    }
+}
@@ -1,35 +1,252 @@
 mod api;
+mod batch;
+mod chart;
+mod compare;
+mod discover;
+mod home_feed;
+mod i18n;
+mod import;
+mod json_file;
+mod library;
+mod links;
+mod local_index;
+mod matcher;
+mod offline_cache;
+mod query;
+mod randomizer;
+mod refine;
+mod reliability;
+mod search_history;
+mod search_provider;
+mod settings;
+mod time_format;
 mod ui;
 
 use adw::prelude::*;
-use gtk::glib;
+use gtk::{gio, glib};
 use libadwaita as adw;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use i18n::gettext;
+use links::Link;
+use offline_cache::ResultCache;
+use search_provider::{Activation, SearchProvider};
+use settings::Settings;
 
 const APP_ID: &str = "com.github.justadreamerfl.HowLongToBeat";
 
+/// State shared by the application's signal handlers
+struct AppState {
+    settings: Settings,
+    // The single main window, created on first activation and reused afterwards
+    window: RefCell<Option<Rc<ui::AppWindow>>>,
+}
+
 fn main() -> glib::ExitCode {
+    i18n::init();
+
     // Initialize tokio runtime for async HTTP requests
     // This is required for reqwest to work properly
     // The runtime and guard must stay alive for the entire application lifetime,
     // so we declare them here and let them live until main() returns (after app.run() exits)
     let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-    
+
     // Enter the runtime context so that tokio async operations work
     // The guard ensures the runtime context remains active
     let _guard = rt.enter();
-    
+
     // Create application
-    let app = adw::Application::builder().application_id(APP_ID).build();
+    // Command lines and opened links are forwarded to the running instance
+    let app = adw::Application::builder()
+        .application_id(APP_ID)
+        .flags(gio::ApplicationFlags::HANDLES_COMMAND_LINE | gio::ApplicationFlags::HANDLES_OPEN)
+        .build();
+    let state = Rc::new(AppState {
+        settings: Settings::load(),
+        window: RefCell::new(None),
+    });
+
+    app.add_main_option(
+        "search",
+        glib::Char::from(b's'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        &gettext("Search for a game"),
+        Some(&gettext("QUERY")),
+    );
+    app.add_main_option(
+        "library",
+        glib::Char::from(b'l'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        &gettext("Show the game library"),
+        None,
+    );
+    app.add_main_option(
+        "surprise",
+        glib::Char::from(b'r'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        &gettext("Roll a random game, matching the --search filters if given"),
+        None,
+    );
+
+    // Trim the caches, and export the GNOME Shell search provider as soon as we're on the bus
+    let state_clone = state.clone();
+    app.connect_startup(move |app| {
+        prune_caches(&state_clone.settings);
+        register_search_provider(app, state_clone.clone());
+    });
+
+    // `app.surprise` takes the filters to roll from, and is exported on D-Bus along with the
+    // application: `gapplication action APP_ID surprise "'platform:switch main:<10h'"`
+    let surprise_action = gio::SimpleAction::new("surprise", Some(glib::VariantTy::STRING));
+    let app_clone = app.clone();
+    let state_clone = state.clone();
+    surprise_action.connect_activate(move |_, parameter| {
+        let query = parameter.and_then(|parameter| parameter.get::<String>()).unwrap_or_default();
+        let window = main_window(&app_clone, &state_clone);
+        window.present();
+        window.surprise(&query);
+    });
+    app.add_action(&surprise_action);
 
     // Connect activate signal
-    app.connect_activate(build_ui);
+    let state_clone = state.clone();
+    app.connect_activate(move |app| main_window(app, &state_clone).present());
+
+    // `howlongtobeat --search QUERY`, `--library` or links as arguments
+    let state_clone = state.clone();
+    app.connect_command_line(move |app, command_line| {
+        handle_command_line(app, &state_clone, command_line).into()
+    });
+
+    // Links opened through the `hltb` URI scheme handler
+    let state_clone = state.clone();
+    app.connect_open(move |app, files, _hint| {
+        for file in files {
+            match links::parse(&file.uri()) {
+                Some(link) => open_link(app, &state_clone, link),
+                None => eprintln!("Don't know how to open {}", file.uri()),
+            }
+        }
+    });
 
     // Run the application (blocks until the app exits)
     // Both rt and _guard remain in scope during this entire time
     app.run()
 }
 
-fn build_ui(app: &adw::Application) {
-    let window = ui::AppWindow::new(app);
+/// Returns the main window, building it if it doesn't exist yet
+fn main_window(app: &adw::Application, state: &AppState) -> Rc<ui::AppWindow> {
+    state
+        .window
+        .borrow_mut()
+        .get_or_insert_with(|| Rc::new(ui::AppWindow::new(app, state.settings.clone())))
+        .clone()
+}
+
+fn handle_command_line(
+    app: &adw::Application,
+    state: &AppState,
+    command_line: &gio::ApplicationCommandLine,
+) -> glib::ExitCode {
+    let options = command_line.options_dict();
+
+    // The first argument is the program name. Links are opened; the other words are searched
+    // for together, so `howlongtobeat the witcher` works without quotes.
+    let mut opened = Vec::new();
+    let mut words = Vec::new();
+    for argument in command_line.arguments().iter().skip(1) {
+        let argument = argument.to_string_lossy();
+        match links::parse(&argument) {
+            Some(link) => opened.push(link),
+            None if argument.contains("://") => eprintln!("Not a HowLongToBeat link, skipping: {}", argument),
+            None => words.push(argument.to_string()),
+        }
+    }
+    let query = words.join(" ");
+    if !query.trim().is_empty() {
+        opened.push(Link::Search(query.trim().to_string()));
+    }
+
+    let search = options.lookup::<String>("search").ok().flatten();
+    if options.contains("surprise") {
+        // Roll from the search filters instead of searching
+        let window = main_window(app, state);
+        window.present();
+        window.surprise(search.as_deref().unwrap_or_default());
+    } else if let Some(query) = search {
+        opened.push(Link::Search(query));
+    }
+
+    if options.contains("library") {
+        let window = main_window(app, state);
+        window.show_library();
+        window.present();
+    } else if opened.is_empty() && !options.contains("surprise") {
+        app.activate();
+    }
+    for link in opened {
+        open_link(app, state, link);
+    }
+    glib::ExitCode::SUCCESS
+}
+
+fn open_link(app: &adw::Application, state: &AppState, link: Link) {
+    let window = main_window(app, state);
+    match link {
+        Link::Game(game_id) => window.show_game_id(game_id),
+        Link::Search(query) => window.search(&query),
+        // The search field resolves Steam links itself
+        Link::SteamApp(app_id) => window.search(&format!("https://store.steampowered.com/app/{}", app_id)),
+    }
     window.present();
 }
+
+/// Keeps the covers and the offline results within the limits set in the preferences, on a
+/// worker thread so a full cache doesn't hold up the window
+fn prune_caches(settings: &Settings) {
+    let max_bytes = u64::from(settings.cache_max_size_mb()) * 1024 * 1024;
+    let max_age = Duration::from_secs(u64::from(settings.cache_max_age_days()) * 24 * 60 * 60);
+    let pruning = gio::spawn_blocking(move || {
+        offline_cache::prune_cache_dir(&SearchProvider::default_cover_dir(), max_bytes, max_age);
+        ResultCache::new(ResultCache::default_dir()).prune(max_bytes, max_age);
+    });
+    glib::spawn_future_local(async move {
+        if pruning.await.is_err() {
+            eprintln!("Failed to prune the caches");
+        }
+    });
+}
+
+fn register_search_provider(app: &adw::Application, state: Rc<AppState>) {
+    let Some(connection) = app.dbus_connection() else {
+        return;
+    };
+
+    let settings = state.settings.clone();
+    let cover_dir = SearchProvider::default_cover_dir();
+    let result_cache = ResultCache::new(ResultCache::default_dir());
+
+    let app_clone = app.clone();
+    let provider = SearchProvider::new(Arc::new(api::HltbClient::new().with_cache(result_cache)), move |activation| {
+        let window = main_window(&app_clone, &state);
+        match activation {
+            Activation::Game(_, Some(game)) => window.show_game(&game),
+            Activation::Game(game_id, None) => window.show_game_id(game_id),
+            Activation::Search(terms) => window.search(&terms),
+        }
+        window.present();
+    })
+    .with_settings(settings)
+    .with_covers(cover_dir)
+    .with_application(app);
+
+    if let Err(e) = Rc::new(provider).register(&connection) {
+        eprintln!("Failed to register the search provider: {}", e);
+    }
+}
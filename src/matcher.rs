@@ -0,0 +1,519 @@
+use crate::api::Game;
+
+/// Matches at or above this score are trusted without asking the user
+pub const CONFIDENT_SCORE: f64 = 0.75;
+// A runner-up this close to the best match makes the pick ambiguous
+const AMBIGUITY_MARGIN: f64 = 0.05;
+// How many ranked candidates are kept for the manual pick dialog
+const MAX_CANDIDATES: usize = 8;
+// How much a match that only holds with "V" or "X" read as a numeral is worth next to an exact one
+const LETTER_NUMERAL_FACTOR: f64 = 0.9;
+
+// Words that only describe a release of a game, not a different game
+const EDITION_WORDS: &[&str] = &[
+    "game", "of", "the", "year", "goty", "definitive", "complete", "deluxe", "ultimate", "gold",
+    "enhanced", "directors", "cut", "anniversary", "special", "collectors", "premium", "standard",
+    "digital", "legendary", "extended", "platinum",
+];
+const ROMAN_NUMERALS: &[(&str, &str)] = &[
+    ("ii", "2"), ("iii", "3"), ("iv", "4"), ("v", "5"), ("vi", "6"), ("vii", "7"), ("viii", "8"),
+    ("ix", "9"), ("x", "10"), ("xi", "11"), ("xii", "12"), ("xiii", "13"), ("xiv", "14"),
+    ("xv", "15"), ("xvi", "16"),
+];
+// Numerals that are as often just letters: "Mega Man X", "Resident Evil Code: Veronica X"
+const LETTER_NUMERALS: &[&str] = &["v", "x"];
+
+/// What is known about the title being matched
+#[derive(Debug, Clone, Default)]
+pub struct MatchQuery {
+    pub title: String,
+    pub year: Option<u32>,
+    /// Platform or store the title came from, e.g. "Steam" or "PlayStation 4"
+    pub platform: Option<String>,
+}
+
+impl MatchQuery {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A title split into comparable words
+#[derive(Debug, PartialEq)]
+struct NormalizedTitle {
+    /// All words without edition suffixes
+    full: String,
+    /// Words before the first subtitle separator
+    main: String,
+    numbers: Vec<String>,
+}
+
+/// The words of `title` with its roman numerals as digits. `letter_numerals` says whether "V"
+/// and "X" are read as numerals too.
+fn words(title: &str, letter_numerals: bool) -> Vec<String> {
+    let title = title.to_lowercase().replace('&', " and ");
+    let cleaned: String = title
+        .chars()
+        .map(|c| match c {
+            // Apostrophes join words ("Director's" -> "directors")
+            '\'' | '’' | '™' | '®' | '©' => '\0',
+            c if c.is_alphanumeric() => fold_accent(c),
+            _ => ' ',
+        })
+        .filter(|c| *c != '\0')
+        .collect();
+
+    cleaned
+        .split_whitespace()
+        .map(|word| {
+            ROMAN_NUMERALS
+                .iter()
+                .filter(|(roman, _)| letter_numerals || !LETTER_NUMERALS.contains(roman))
+                .find(|(roman, _)| *roman == word)
+                .map(|(_, digits)| digits.to_string())
+                .unwrap_or_else(|| word.to_string())
+        })
+        .collect()
+}
+
+fn fold_accent(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+        'è' | 'é' | 'ê' | 'ë' => 'e',
+        'ì' | 'í' | 'î' | 'ï' => 'i',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => 'o',
+        'ù' | 'ú' | 'û' | 'ü' => 'u',
+        'ñ' => 'n',
+        'ç' => 'c',
+        c => c,
+    }
+}
+
+/// Drops a trailing "... Edition" phrase and "GOTY"-style markers
+fn strip_editions(mut words: Vec<String>) -> Vec<String> {
+    if let Some(pos) = words.iter().rposition(|w| w == "edition" || w == "version") {
+        // Only strip when everything between the qualifier and "edition" is an edition word
+        let start = words[..pos]
+            .iter()
+            .rposition(|w| !EDITION_WORDS.contains(&w.as_str()))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        if start > 0 && start < pos {
+            words.drain(start..=pos);
+        }
+    }
+
+    let game_of_the_year = ["game", "of", "the", "year"];
+    if let Some(pos) = words.windows(4).position(|w| w == game_of_the_year) {
+        if pos > 0 {
+            words.drain(pos..pos + 4);
+        }
+    }
+    words.retain(|w| w != "goty");
+
+    // "The" at the start is often dropped by stores
+    if words.len() > 1 && words[0] == "the" {
+        words.remove(0);
+    }
+    words
+}
+
+fn normalize(title: &str, letter_numerals: bool) -> NormalizedTitle {
+    let full = strip_editions(words(title, letter_numerals));
+
+    // The main title is whatever precedes a colon or a spaced dash
+    let main_part = title
+        .split([':', '–', '—'])
+        .next()
+        .unwrap_or(title)
+        .split(" - ")
+        .next()
+        .unwrap_or(title);
+    let main = strip_editions(words(main_part, letter_numerals));
+
+    let numbers = full
+        .iter()
+        .filter(|w| w.chars().all(|c| c.is_ascii_digit()))
+        // Years in titles ("FIFA 2004") are numbers too, but lone "1"s are rarely written out
+        .filter(|w| *w != "1")
+        .cloned()
+        .collect();
+
+    NormalizedTitle {
+        full: full.join(" "),
+        main: main.join(" "),
+        numbers,
+    }
+}
+
+/// The words of a title as matching compares them: lowercase, without accents or punctuation,
+/// with roman numerals as digits
+pub fn title_words(title: &str) -> Vec<String> {
+    words(title, true)
+}
+
+/// Roman numerals `typed` is the start of, as the digits [`title_words`] turns them into
+pub fn numerals_starting_with(typed: &str) -> impl Iterator<Item = &'static str> + '_ {
+    ROMAN_NUMERALS
+        .iter()
+        .filter(move |(roman, _)| roman.len() > typed.len() && roman.starts_with(typed))
+        .map(|(_, digits)| *digits)
+}
+
+/// Normalizes a title for exact comparison: case, punctuation, roman numerals and editions.
+/// "V" and "X" stay letters, so "Mega Man X" isn't "Mega Man 10".
+pub fn normalize_title(title: &str) -> String {
+    normalize(title, false).full.replace(' ', "")
+}
+
+/// Looser variants of a search query, most specific first, for when the query finds nothing:
+/// without punctuation, without edition suffixes, with roman numerals and digits swapped,
+/// and with trailing words dropped one at a time.
+pub fn relax_query(query: &str) -> Vec<String> {
+    let stripped: Vec<String> = query
+        .chars()
+        .filter(|c| !matches!(c, '\'' | '’' | '™' | '®' | '©'))
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+
+    let swap_numerals = |to_digits: bool| -> String {
+        stripped
+            .iter()
+            .map(|word| {
+                let lower = word.to_lowercase();
+                ROMAN_NUMERALS
+                    .iter()
+                    .find(|(roman, digits)| if to_digits { *roman == lower } else { *digits == lower })
+                    .map(|(roman, digits)| if to_digits { digits.to_string() } else { roman.to_uppercase() })
+                    .unwrap_or_else(|| word.clone())
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut candidates = vec![
+        stripped.join(" "),
+        strip_editions(words(query, true)).join(" "),
+        swap_numerals(true),
+        swap_numerals(false),
+    ];
+    // A single leftover word like "The" or "Final" matches far too much
+    let min_words = if stripped.len() > 2 { 2 } else { 1 };
+    candidates.extend((min_words..stripped.len()).rev().map(|n| stripped[..n].join(" ")));
+
+    let mut seen = vec![query.trim().to_lowercase()];
+    candidates
+        .into_iter()
+        .filter(|variant| !variant.is_empty())
+        .filter(|variant| {
+            let key = variant.to_lowercase();
+            if seen.contains(&key) {
+                false
+            } else {
+                seen.push(key);
+                true
+            }
+        })
+        .collect()
+}
+
+/// Dice coefficient over character bigrams, ignoring spaces
+fn dice(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().filter(|c| !c.is_whitespace()).collect();
+    let b: Vec<char> = b.chars().filter(|c| !c.is_whitespace()).collect();
+    if a == b {
+        return if a.is_empty() { 0.0 } else { 1.0 };
+    }
+    if a.len() < 2 || b.len() < 2 {
+        return 0.0;
+    }
+
+    let a_bigrams: Vec<(char, char)> = a.windows(2).map(|w| (w[0], w[1])).collect();
+    let mut b_bigrams: Vec<(char, char)> = b.windows(2).map(|w| (w[0], w[1])).collect();
+    let total = a_bigrams.len() + b_bigrams.len();
+    let mut shared = 0;
+    for bigram in a_bigrams {
+        if let Some(pos) = b_bigrams.iter().position(|b| *b == bigram) {
+            b_bigrams.swap_remove(pos);
+            shared += 1;
+        }
+    }
+
+    (2 * shared) as f64 / total as f64
+}
+
+/// Similarity of two titles between 0.0 and 1.0. A lone "V" or "X" may be a numeral, but a
+/// match that needs it to be one ("Civilization V" and "Civilization 5") is never exact.
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let as_letters = similarity(&normalize(a, false), &normalize(b, false));
+    let as_numerals = similarity(&normalize(a, true), &normalize(b, true));
+    as_letters.max(as_numerals * LETTER_NUMERAL_FACTOR)
+}
+
+fn similarity(a: &NormalizedTitle, b: &NormalizedTitle) -> f64 {
+    if a.full.is_empty() || b.full.is_empty() {
+        return 0.0;
+    }
+
+    let mut score = dice(&a.full, &b.full);
+    // One side may leave out the subtitle ("Hades" vs "Hades: Battle out of Hell")
+    if a.main == b.full || a.full == b.main {
+        score = score.max(0.9);
+    }
+
+    // Different numbers mean a different entry in a series, however similar the rest is
+    if a.numbers != b.numbers {
+        score *= if a.numbers.is_empty() || b.numbers.is_empty() { 0.8 } else { 0.6 };
+    }
+    score
+}
+
+/// Scores a candidate against the query: the title similarity adjusted by year and platform.
+/// Unclamped so that year and platform still separate two exact title matches.
+fn raw_score(game: &Game, query: &MatchQuery) -> f64 {
+    let mut score = title_similarity(&game.game_name, &query.title);
+    for alias in game.game_alias.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+        score = score.max(title_similarity(alias, &query.title));
+    }
+
+    if let (Some(year), true) = (query.year, game.release_world > 0) {
+        score += match (game.release_world as i64 - year as i64).abs() {
+            0 => 0.05,
+            1 => 0.02,
+            _ => -0.1,
+        };
+    }
+
+    if let Some(platform) = query.platform.as_deref() {
+        if platform_matches(&game.profile_platform, platform) {
+            score += 0.03;
+        }
+    }
+
+    score
+}
+
+fn platform_matches(game_platforms: &str, platform: &str) -> bool {
+    let platform = platform.to_lowercase();
+    // Stores and launchers all mean PC on HLTB
+    let platform = match platform.as_str() {
+        "steam" | "gog" | "epic games store" | "amazon games" | "linux" | "windows" | "lutris"
+        | "heroic" => "pc",
+        other => other,
+    };
+
+    game_platforms
+        .split(',')
+        .map(|p| p.trim().to_lowercase())
+        .any(|p| p == platform)
+}
+
+/// Ranks candidates from best to worst. Ties go to the more popular game.
+pub fn rank(results: Vec<Game>, query: &MatchQuery) -> Vec<(Game, f64)> {
+    let mut ranked: Vec<(Game, f64)> = results
+        .into_iter()
+        .map(|game| {
+            let score = raw_score(&game, query);
+            (game, score)
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        b.1.total_cmp(&a.1)
+            .then(b.0.comp_all_count.cmp(&a.0.comp_all_count))
+    });
+    ranked.truncate(MAX_CANDIDATES);
+    ranked
+        .into_iter()
+        .map(|(game, score)| (game, score.clamp(0.0, 1.0)))
+        .collect()
+}
+
+/// Whether the ranked candidates need the user to pick one by hand
+pub fn is_ambiguous(ranked: &[(Game, f64)]) -> bool {
+    match ranked {
+        [] => false,
+        [(_, best)] => *best < CONFIDENT_SCORE,
+        [(_, best), (_, second), ..] => {
+            *best < CONFIDENT_SCORE || best - second < AMBIGUITY_MARGIN
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(game_id: u64, name: &str, year: u64, platform: &str) -> Game {
+        Game {
+            game_id,
+            game_name: name.to_string(),
+            release_world: year,
+            profile_platform: platform.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn best(results: Vec<Game>, query: &MatchQuery) -> u64 {
+        rank(results, query)[0].0.game_id
+    }
+
+    #[test]
+    fn test_normalize_title() {
+        assert_eq!(normalize_title("The Witcher 3: Wild Hunt"), "witcher3wildhunt");
+        assert_eq!(normalize_title("Final Fantasy VII"), "finalfantasy7");
+        assert_eq!(normalize_title("Ratchet & Clank"), "ratchetandclank");
+        assert_eq!(normalize_title("DOOM®"), "doom");
+        assert_eq!(normalize_title("Baldur's Gate II: Enhanced Edition"), "baldursgate2");
+        assert_eq!(normalize_title("Batman: Arkham City - Game of the Year Edition"), "batmanarkhamcity");
+        assert_eq!(normalize_title("Fallout 3 GOTY"), "fallout3");
+        assert_eq!(normalize_title("Divinity: Original Sin 2 - Definitive Edition"), "divinityoriginalsin2");
+        assert_eq!(normalize_title("Director's Cut"), "directorscut", "Nothing left to strip it from");
+        assert_eq!(normalize_title("The Last of Us"), "lastofus");
+    }
+
+    #[test]
+    fn test_title_similarity() {
+        assert_eq!(title_similarity("The Witcher 3: Wild Hunt", "the witcher 3 wild hunt"), 1.0);
+        assert!(title_similarity("Witcher 3 Wild Hunt", "The Witcher 3: Wild Hunt") > 0.75);
+        assert!(title_similarity("Celeste", "Hollow Knight") < 0.2);
+        assert_eq!(title_similarity("", "Celeste"), 0.0);
+    }
+
+    #[test]
+    fn test_sequel_numbers_disambiguate() {
+        assert!(title_similarity("The Witcher 2", "The Witcher 3") < 0.6);
+        assert!(title_similarity("Portal", "Portal 2") < CONFIDENT_SCORE);
+        assert_eq!(title_similarity("Final Fantasy 7", "Final Fantasy VII"), 1.0);
+        let civilization = title_similarity("Civilization V", "Sid Meier's Civilization V");
+        assert_eq!(title_similarity("Civilization 5", "Sid Meier's Civilization V"), civilization * LETTER_NUMERAL_FACTOR);
+    }
+
+    #[test]
+    fn test_letter_numerals_are_weaker_than_exact_matches() {
+        assert_ne!(normalize_title("Mega Man X"), normalize_title("Mega Man 10"));
+        assert!(title_similarity("Mega Man X", "Mega Man 10") < 1.0);
+        assert_eq!(title_similarity("Civilization V", "Civilization 5"), LETTER_NUMERAL_FACTOR);
+        assert!(title_similarity("Resident Evil Code: Veronica X", "Resident Evil Code: Veronica") > 0.9);
+
+        // Each title still finds itself when both are among the results
+        let results = || vec![game(1, "Mega Man 10", 2010, ""), game(2, "Mega Man X", 1993, "")];
+        let ranked = rank(results(), &MatchQuery::new("Mega Man X"));
+        assert_eq!(ranked[0].0.game_id, 2);
+        assert!(!is_ambiguous(&ranked));
+        let ranked = rank(results(), &MatchQuery::new("Mega Man 10"));
+        assert_eq!(ranked[0].0.game_id, 1);
+        assert!(!is_ambiguous(&ranked));
+    }
+
+    #[test]
+    fn test_subtitles() {
+        assert!(title_similarity("Hades", "Hades: Battle Out of Hell") >= 0.9);
+        assert!(title_similarity("Nier Automata", "NieR:Automata") > 0.9);
+        assert!(title_similarity("Star Wars Jedi: Fallen Order", "Star Wars Jedi: Survivor") < CONFIDENT_SCORE);
+    }
+
+    #[test]
+    fn test_tricky_title_corpus() {
+        let cases = [
+            ("Grand Theft Auto V", "Grand Theft Auto IV", "Grand Theft Auto V"),
+            ("DOOM Eternal", "DOOM", "DOOM Eternal"),
+            ("Resident Evil 2", "Resident Evil", "Resident Evil 2"),
+            ("The Elder Scrolls V: Skyrim Special Edition", "The Elder Scrolls IV: Oblivion", "The Elder Scrolls V: Skyrim"),
+            ("Persona 5 Royal", "Persona 4 Golden", "Persona 5"),
+            ("Pokemon Red", "Pokémon Blue", "Pokémon Red"),
+            ("Deus Ex: GOTY Edition", "Deus Ex: Human Revolution", "Deus Ex"),
+        ];
+
+        for (query, wrong, right) in cases {
+            let results = vec![game(1, wrong, 0, ""), game(2, right, 0, "")];
+            assert_eq!(best(results, &MatchQuery::new(query)), 2, "{} should match {}", query, right);
+        }
+    }
+
+    #[test]
+    fn test_aliases() {
+        let mut alias_game = game(2, "Grand Theft Auto V", 0, "");
+        alias_game.game_alias = "GTA 5, GTA V".to_string();
+        let results = vec![game(1, "Grand Theft Auto IV", 0, ""), alias_game];
+
+        let ranked = rank(results, &MatchQuery::new("GTA 5"));
+        assert_eq!(ranked[0].0.game_id, 2);
+        assert_eq!(ranked[0].1, 1.0);
+    }
+
+    #[test]
+    fn test_year_breaks_remake_ties() {
+        let results = vec![
+            game(1, "Tomb Raider", 1996, "PC, PlayStation"),
+            game(2, "Tomb Raider", 2013, "PC, PlayStation 3"),
+        ];
+        let query = MatchQuery {
+            year: Some(2013),
+            ..MatchQuery::new("Tomb Raider")
+        };
+        let ranked = rank(results, &query);
+        assert_eq!(ranked[0].0.game_id, 2);
+        assert!(!is_ambiguous(&ranked));
+
+        // Without a year the remake and the original can't be told apart
+        let results = vec![
+            game(1, "Tomb Raider", 1996, "PC, PlayStation"),
+            game(2, "Tomb Raider", 2013, "PC, PlayStation 3"),
+        ];
+        assert!(is_ambiguous(&rank(results, &MatchQuery::new("Tomb Raider"))));
+    }
+
+    #[test]
+    fn test_platform_breaks_ties() {
+        let results = vec![
+            game(1, "Spider-Man", 2002, "GameCube, PlayStation 2"),
+            game(2, "Spider-Man", 2002, "PC, Xbox"),
+        ];
+        let query = MatchQuery {
+            platform: Some("Steam".to_string()),
+            ..MatchQuery::new("Spider-Man")
+        };
+        assert_eq!(best(results, &query), 2);
+    }
+
+    #[test]
+    fn test_popularity_breaks_exact_ties() {
+        let mut popular = game(2, "Prey", 0, "");
+        popular.comp_all_count = 5000;
+        let results = vec![game(1, "Prey", 0, ""), popular];
+        assert_eq!(best(results, &MatchQuery::new("Prey")), 2);
+    }
+
+    #[test]
+    fn test_relax_query() {
+        let variants = relax_query("The Witcher III: Wild Hunt - GOTY Edition");
+        assert_eq!(variants[0], "The Witcher III Wild Hunt GOTY Edition");
+        assert!(variants.contains(&"witcher 3 wild hunt".to_string()));
+        assert!(variants.contains(&"The Witcher 3 Wild Hunt GOTY Edition".to_string()));
+        assert!(variants.contains(&"The Witcher III Wild Hunt GOTY".to_string()));
+        assert_eq!(variants.last().unwrap(), "The Witcher");
+
+        let variants = relax_query("Final Fantasy 7");
+        assert_eq!(variants, vec!["Final Fantasy VII", "Final Fantasy"]);
+    }
+
+    #[test]
+    fn test_relax_query_nothing_to_relax() {
+        assert!(relax_query("Celeste").is_empty());
+        assert!(relax_query("  ").is_empty());
+    }
+
+    #[test]
+    fn test_is_ambiguous() {
+        assert!(!is_ambiguous(&[]));
+        assert!(is_ambiguous(&[(game(1, "Celeste", 0, ""), 0.5)]));
+        assert!(!is_ambiguous(&[(game(1, "Celeste", 0, ""), 1.0), (game(2, "Celeste Classic", 0, ""), 0.7)]));
+    }
+}
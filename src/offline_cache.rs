@@ -0,0 +1,336 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::api::{Game, SearchRequest, SearchResponse};
+use crate::i18n::{gettext, ngettext_f};
+use crate::json_file::write_json_atomically;
+
+// Layout of an entry file; entries in other layouts are misses until they're pruned
+const CACHE_FORMAT_VERSION: u32 = 1;
+
+/// One cached response, in a file of its own
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    version: u32,
+    /// What was asked for, to tell apart requests whose file names collide
+    key: String,
+    /// Unix timestamp of when the response was fetched
+    fetched: u64,
+    data: T,
+}
+
+/// A response served from the cache
+#[derive(Debug, Clone)]
+pub struct CachedResponse<T> {
+    pub data: T,
+    /// Unix timestamp of when it was fetched
+    pub fetched: u64,
+}
+
+/// Search result pages and game details as last fetched, for when HowLongToBeat can't be
+/// reached. Each response is a JSON file named after a hash of its request.
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Default location: `$XDG_CACHE_HOME/howlongtobeat/results`
+    pub fn default_dir() -> PathBuf {
+        glib::user_cache_dir().join("howlongtobeat").join("results")
+    }
+
+    pub fn store_search(&self, request: &SearchRequest, response: &SearchResponse, now: u64) -> Result<()> {
+        self.store(&search_key(request)?, response, now)
+    }
+
+    /// The response last fetched for `request`, if there is one
+    pub fn search(&self, request: &SearchRequest) -> Option<CachedResponse<SearchResponse>> {
+        self.load(&search_key(request).ok()?)
+    }
+
+    pub fn store_game(&self, game: &Game, now: u64) -> Result<()> {
+        self.store(&game_key(game.game_id), game, now)
+    }
+
+    /// The details last fetched for a game, if there are any
+    pub fn game(&self, game_id: u64) -> Option<CachedResponse<Game>> {
+        self.load(&game_key(game_id))
+    }
+
+    /// Every game in the cache, from search results and game details alike. Entries that can't
+    /// be read are skipped; a game found more than once is listed more than once.
+    pub fn games(&self) -> Vec<Game> {
+        let Ok(files) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut games = Vec::new();
+        for file in files.flatten() {
+            let Ok(contents) = std::fs::read_to_string(file.path()) else {
+                continue;
+            };
+            let Ok(entry) = serde_json::from_str::<Entry<serde_json::Value>>(&contents) else {
+                continue;
+            };
+            if entry.version != CACHE_FORMAT_VERSION {
+                continue;
+            }
+            if entry.key.starts_with("search:") {
+                if let Ok(response) = serde_json::from_value::<SearchResponse>(entry.data) {
+                    games.extend(response.data);
+                }
+            } else if entry.key.starts_with("game:") {
+                if let Ok(game) = serde_json::from_value::<Game>(entry.data) {
+                    games.push(game);
+                }
+            }
+        }
+        games
+    }
+
+    /// Removes entries older than `max_age`, then the oldest until the rest fit in `max_bytes`
+    pub fn prune(&self, max_bytes: u64, max_age: Duration) {
+        prune_cache_dir(&self.dir, max_bytes, max_age);
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let hash = glib::compute_checksum_for_string(glib::ChecksumType::Sha1, key)
+            .expect("SHA-1 is always supported");
+        self.dir.join(format!("{}.json", hash))
+    }
+
+    fn store<T: Serialize>(&self, key: &str, data: &T, now: u64) -> Result<()> {
+        let entry = Entry {
+            version: CACHE_FORMAT_VERSION,
+            key: key.to_string(),
+            fetched: now,
+            data,
+        };
+        write_json_atomically(&self.path(key), &entry)
+    }
+
+    /// Reads an entry back. Missing, unreadable, outdated and colliding entries are all misses.
+    fn load<T: DeserializeOwned>(&self, key: &str) -> Option<CachedResponse<T>> {
+        let contents = std::fs::read_to_string(self.path(key)).ok()?;
+        let entry: Entry<T> = serde_json::from_str(&contents).ok()?;
+        (entry.version == CACHE_FORMAT_VERSION && entry.key == key).then_some(CachedResponse {
+            data: entry.data,
+            fetched: entry.fetched,
+        })
+    }
+}
+
+/// Requests asking for the same thing serialize the same way
+fn search_key(request: &SearchRequest) -> Result<String> {
+    Ok(format!("search:{}", serde_json::to_string(request)?))
+}
+
+fn game_key(game_id: u64) -> String {
+    format!("game:{}", game_id)
+}
+
+/// Removes files in a cache directory, like the covers, older than `max_age`, then the least
+/// recently written ones until the rest fit in `max_bytes`
+pub fn prune_cache_dir(dir: &Path, max_bytes: u64, max_age: Duration) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    let now = SystemTime::now();
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let modified = metadata.modified().unwrap_or(now);
+        if now.duration_since(modified).unwrap_or_default() > max_age {
+            std::fs::remove_file(entry.path()).ok();
+        } else {
+            files.push((modified, metadata.len(), entry.path()));
+        }
+    }
+
+    // Newest first, so the oldest are dropped once the budget runs out
+    files.sort_by_key(|file| std::cmp::Reverse(file.0));
+    let mut total = 0;
+    for (_, size, path) in files {
+        total += size;
+        if total > max_bytes {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
+
+/// How long ago cached data was fetched, like "3 hours ago"
+pub fn describe_age(fetched: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(fetched);
+    let (minutes, hours, days) = (seconds / 60, seconds / 3600, seconds / 86400);
+    if minutes < 1 {
+        gettext("just now")
+    } else if hours < 1 {
+        ngettext_f("{count} minute ago", "{count} minutes ago", minutes as usize, &[("count", &minutes.to_string())])
+    } else if days < 1 {
+        ngettext_f("{count} hour ago", "{count} hours ago", hours as usize, &[("count", &hours.to_string())])
+    } else {
+        ngettext_f("{count} day ago", "{count} days ago", days as usize, &[("count", &days.to_string())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::GameSearchOptions;
+
+    fn temp_cache(name: &str) -> ResultCache {
+        ResultCache::new(std::env::temp_dir().join(format!("hltb-results-test-{}-{}", name, std::process::id())))
+    }
+
+    fn response(names: &[&str]) -> SearchResponse {
+        SearchResponse {
+            data: names
+                .iter()
+                .map(|name| Game {
+                    game_name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_search_round_trip() {
+        let cache = temp_cache("search");
+        let zelda = SearchRequest::new("zelda", GameSearchOptions::default());
+        let sorted = SearchRequest::new(
+            "zelda",
+            GameSearchOptions {
+                sort_category: "rating".to_string(),
+                ..Default::default()
+            },
+        );
+        assert!(cache.search(&zelda).is_none());
+
+        cache.store_search(&zelda, &response(&["Breath of the Wild"]), 100).unwrap();
+        let hit = cache.search(&zelda).unwrap();
+        assert_eq!(hit.fetched, 100);
+        assert_eq!(hit.data.data[0].game_name, "Breath of the Wild");
+        // The same words with other options are a different request
+        assert!(cache.search(&sorted).is_none());
+
+        // Storing again replaces the entry
+        cache.store_search(&zelda, &response(&["Tears of the Kingdom"]), 200).unwrap();
+        assert_eq!(cache.search(&zelda).unwrap().data.data[0].game_name, "Tears of the Kingdom");
+
+        std::fs::remove_dir_all(&cache.dir).ok();
+    }
+
+    #[test]
+    fn test_game_round_trip() {
+        let cache = temp_cache("game");
+        let game = Game {
+            game_id: 10270,
+            game_name: "The Witcher 3: Wild Hunt".to_string(),
+            ..Default::default()
+        };
+        cache.store_game(&game, 300).unwrap();
+        let hit = cache.game(10270).unwrap();
+        assert_eq!((hit.data.game_name.as_str(), hit.fetched), ("The Witcher 3: Wild Hunt", 300));
+        assert!(cache.game(1).is_none());
+
+        std::fs::remove_dir_all(&cache.dir).ok();
+    }
+
+    #[test]
+    fn test_games() {
+        let cache = temp_cache("games");
+        assert!(cache.games().is_empty(), "A cache that was never written has no games");
+
+        let request = SearchRequest::new("souls", GameSearchOptions::default());
+        cache.store_search(&request, &response(&["Dark Souls", "Demon's Souls"]), 100).unwrap();
+        cache
+            .store_game(
+                &Game {
+                    game_id: 5,
+                    game_name: "Celeste".to_string(),
+                    ..Default::default()
+                },
+                100,
+            )
+            .unwrap();
+        std::fs::write(cache.dir.join("broken.json"), "not json").unwrap();
+
+        let mut names: Vec<String> = cache.games().into_iter().map(|game| game.game_name).collect();
+        names.sort();
+        assert_eq!(names, vec!["Celeste", "Dark Souls", "Demon's Souls"]);
+
+        std::fs::remove_dir_all(&cache.dir).ok();
+    }
+
+    #[test]
+    fn test_other_formats_are_misses() {
+        let cache = temp_cache("formats");
+        let key = game_key(5);
+        std::fs::create_dir_all(&cache.dir).unwrap();
+
+        std::fs::write(cache.path(&key), r#"{"version": 99, "key": "game:5", "fetched": 1, "data": {}}"#).unwrap();
+        assert!(cache.game(5).is_none(), "Entries from another format version are ignored");
+
+        std::fs::write(cache.path(&key), r#"{"version": 1, "key": "game:6", "fetched": 1, "data": {}}"#).unwrap();
+        assert!(cache.game(5).is_none(), "An entry for another request is ignored");
+
+        std::fs::write(cache.path(&key), "not json").unwrap();
+        assert!(cache.game(5).is_none());
+
+        std::fs::remove_dir_all(&cache.dir).ok();
+    }
+
+    #[test]
+    fn test_prune_cache_dir() {
+        let dir = std::env::temp_dir().join(format!("hltb-covers-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let now = SystemTime::now();
+        for (name, age_days) in [("new.jpg", 0), ("older.jpg", 2), ("oldest.jpg", 5), ("expired.jpg", 40)] {
+            let path = dir.join(name);
+            std::fs::write(&path, [0u8; 100]).unwrap();
+            let modified = now - Duration::from_secs(age_days * 24 * 60 * 60);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+
+        prune_cache_dir(&dir, 250, Duration::from_secs(30 * 24 * 60 * 60));
+
+        let mut left: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec!["new.jpg", "older.jpg"]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_describe_age() {
+        assert_eq!(describe_age(1000, 1030), "just now");
+        assert_eq!(describe_age(1000, 1000 + 60), "1 minute ago");
+        assert_eq!(describe_age(1000, 1000 + 45 * 60), "45 minutes ago");
+        assert_eq!(describe_age(1000, 1000 + 3 * 3600 + 59), "3 hours ago");
+        assert_eq!(describe_age(1000, 1000 + 2 * 86400), "2 days ago");
+        assert_eq!(describe_age(1000, 0), "just now", "Clocks set back don't give negative ages");
+    }
+}
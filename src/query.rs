@@ -0,0 +1,431 @@
+use std::fmt;
+
+use crate::api::{GameSearchOptions, RangeTime, RangeYear};
+use crate::i18n::gettext_f;
+
+// Short platform names accepted by `platform:`, mapped to HLTB's platform names
+const PLATFORM_ALIASES: &[(&str, &str)] = &[
+    ("pc", "PC"),
+    ("linux", "Linux"),
+    ("mac", "Mac"),
+    ("mobile", "Mobile"),
+    ("ps1", "PlayStation"),
+    ("psx", "PlayStation"),
+    ("ps2", "PlayStation 2"),
+    ("ps3", "PlayStation 3"),
+    ("ps4", "PlayStation 4"),
+    ("ps5", "PlayStation 5"),
+    ("psp", "PlayStation Portable"),
+    ("vita", "PlayStation Vita"),
+    ("xbox", "Xbox"),
+    ("x360", "Xbox 360"),
+    ("xone", "Xbox One"),
+    ("xb1", "Xbox One"),
+    ("xsx", "Xbox Series X/S"),
+    ("switch", "Nintendo Switch"),
+    ("wii", "Wii"),
+    ("wiiu", "Wii U"),
+    ("gc", "Nintendo GameCube"),
+    ("n64", "Nintendo 64"),
+    ("snes", "Super Nintendo"),
+    ("nes", "NES"),
+    ("3ds", "Nintendo 3DS"),
+    ("ds", "Nintendo DS"),
+    ("gba", "Game Boy Advance"),
+];
+
+// Values accepted by `sort:`, mapped to HLTB's sort categories
+const SORT_ALIASES: &[(&str, &str)] = &[
+    ("popular", "popular"),
+    ("rating", "rating"),
+    ("score", "rating"),
+    ("name", "name"),
+    ("title", "name"),
+    ("release", "release"),
+    ("year", "release"),
+    ("main", "main"),
+    ("length", "main"),
+    ("extras", "mainp"),
+    ("comp", "comp"),
+    ("completionist", "comp"),
+];
+
+// Filter keys for time ranges, mapped to HLTB's range categories
+const TIME_KEYS: &[(&str, &str)] = &[
+    ("main", "main"),
+    ("extras", "mainp"),
+    ("plus", "mainp"),
+    ("comp", "comp"),
+    ("completionist", "comp"),
+    ("all", "all"),
+];
+
+/// A search entry text split into search terms and HLTB search options
+#[derive(Debug, Clone, Default)]
+pub struct ParsedQuery {
+    pub terms: Vec<String>,
+    pub options: GameSearchOptions,
+    /// The filter tokens as typed, less their quotes, to rebuild the query around different terms
+    pub filters: Vec<String>,
+}
+
+impl ParsedQuery {
+    /// The search terms joined back into a plain query
+    pub fn text(&self) -> String {
+        self.terms.join(" ")
+    }
+
+    /// The full query text with `terms` in place of the original search terms
+    pub fn with_terms(&self, terms: &str) -> String {
+        std::iter::once(terms.to_string())
+            .chain(self.filters.iter().map(|filter| quote_value(filter)))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Puts the quotes `tokenize` dropped back around a filter value with spaces in it
+fn quote_value(filter: &str) -> String {
+    match filter.split_once(':') {
+        Some((key, value)) if value.contains(char::is_whitespace) => {
+            format!("{}:\"{}\"", key, value)
+        }
+        _ => filter.to_string(),
+    }
+}
+
+/// A filter that couldn't be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    /// The offending filter as typed
+    pub token: String,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = gettext_f(
+            "{filter}: {problem}",
+            &[("filter", &self.token), ("problem", &self.message)],
+        );
+        f.write_str(&text)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Parses search entry text like `persona platform:ps4 year:2015..2020 main:<60h sort:rating`.
+/// Words that aren't a known `key:value` filter are kept as search terms.
+pub fn parse(text: &str) -> Result<ParsedQuery, QueryError> {
+    let mut query = ParsedQuery::default();
+
+    for token in tokenize(text) {
+        let Some((key, value)) = token.split_once(':') else {
+            query.terms.push(token);
+            continue;
+        };
+        let key = key.to_lowercase();
+        let error = |message: String| QueryError {
+            token: token.clone(),
+            message,
+        };
+
+        if key == "platform" {
+            if value.is_empty() {
+                return Err(error(gettext_f(
+                    "expected a platform, e.g. {example}",
+                    &[("example", "platform:ps4")],
+                )));
+            }
+            query.options.platform = PLATFORM_ALIASES
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(value))
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_else(|| value.to_string());
+        } else if key == "year" {
+            query.options.range_year = parse_year_range(value).ok_or_else(|| {
+                error(gettext_f(
+                    "expected a year or a range, e.g. {example}",
+                    &[("example", "year:2015..2020")],
+                ))
+            })?;
+        } else if key == "sort" {
+            query.options.sort_category = SORT_ALIASES
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(value))
+                .map(|(_, category)| category.to_string())
+                .ok_or_else(|| {
+                    error(gettext_f(
+                        "expected one of {values}",
+                        &[(
+                            "values",
+                            "popular, rating, name, release, main, extras, completionist",
+                        )],
+                    ))
+                })?;
+        } else if let Some((_, category)) = TIME_KEYS.iter().find(|(name, _)| *name == key) {
+            query.options.range_category = category.to_string();
+            query.options.range_time = parse_time_range(value).ok_or_else(|| {
+                error(gettext_f(
+                    "expected a time like {examples}",
+                    &[("examples", "<60h, >10h, 10..20h")],
+                ))
+            })?;
+        } else {
+            // Not a filter, just a title with a colon in it ("Nier:Automata")
+            query.terms.push(token);
+            continue;
+        }
+
+        query.filters.push(token);
+    }
+
+    Ok(query)
+}
+
+/// The platforms `platform:` knows aliases for, alphabetically, to pick from in menus
+pub fn platform_names() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = PLATFORM_ALIASES.iter().map(|(_, name)| *name).collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// Splits on whitespace, keeping double-quoted values together (`platform:"Nintendo Switch"`)
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Splits `a..b`, `a..`, `..b` or `a` into optional bounds
+fn split_range(value: &str) -> Option<(Option<&str>, Option<&str>)> {
+    match value.split_once("..") {
+        Some((min, max)) if min.is_empty() && max.is_empty() => None,
+        Some((min, max)) => Some((bound(min), bound(max))),
+        None if value.is_empty() => None,
+        None => Some((Some(value), Some(value))),
+    }
+}
+
+fn parse_year_range(value: &str) -> Option<RangeYear> {
+    let (min, max) = split_range(value)?;
+    let year = |s: Option<&str>| -> Option<Option<u32>> {
+        match s {
+            None => Some(None),
+            Some(s) => s
+                .parse::<u32>()
+                .ok()
+                .filter(|y| (1950..=2100).contains(y))
+                .map(Some),
+        }
+    };
+    let (min, max) = (year(min)?, year(max)?);
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return None;
+        }
+    }
+
+    Some(RangeYear {
+        min: min.map(|y| y.to_string()).unwrap_or_default(),
+        max: max.map(|y| y.to_string()).unwrap_or_default(),
+    })
+}
+
+/// Parses `<60h`, `>10h`, `10..20h` or `90m` into a range in seconds
+fn parse_time_range(value: &str) -> Option<RangeTime> {
+    if let Some(max) = value.strip_prefix('<') {
+        return Some(RangeTime {
+            min: None,
+            max: Some(parse_duration(max)?),
+        });
+    }
+    if let Some(min) = value.strip_prefix('>') {
+        return Some(RangeTime {
+            min: Some(parse_duration(min)?),
+            max: None,
+        });
+    }
+
+    let (min, max) = split_range(value)?;
+    // A unit on the upper bound applies to both ends ("10..20h")
+    let unit = max
+        .and_then(|m| m.chars().last())
+        .filter(|c| c.is_alphabetic());
+    let with_unit = |s: &str| -> Option<u32> {
+        match (s.chars().last(), unit) {
+            (Some(c), Some(unit)) if c.is_ascii_digit() => {
+                parse_duration(&format!("{}{}", s, unit))
+            }
+            _ => parse_duration(s),
+        }
+    };
+
+    let min = min.map(with_unit).map_or(Some(None), |m| m.map(Some))?;
+    let max = max.map(with_unit).map_or(Some(None), |m| m.map(Some))?;
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return None;
+        }
+    }
+    Some(RangeTime { min, max })
+}
+
+/// Parses `60h`, `1.5h`, `90m` or a bare number of hours into seconds
+fn parse_duration(value: &str) -> Option<u32> {
+    let value = value.trim().to_lowercase();
+    let (number, seconds_per_unit) = if let Some(hours) = value.strip_suffix('h') {
+        (hours, 3600.0)
+    } else if let Some(minutes) = value.strip_suffix('m') {
+        (minutes, 60.0)
+    } else {
+        (value.as_str(), 3600.0)
+    };
+
+    let number: f64 = number.parse().ok()?;
+    (number.is_finite() && number >= 0.0).then(|| (number * seconds_per_unit).round() as u32)
+}
+
+fn bound(s: &str) -> Option<&str> {
+    (!s.is_empty()).then_some(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_query() {
+        let query = parse("persona platform:ps4 year:2015..2020 main:<60h sort:rating").unwrap();
+        assert_eq!(query.terms, vec!["persona"]);
+        assert_eq!(query.options.platform, "PlayStation 4");
+        assert_eq!(query.options.range_year.min, "2015");
+        assert_eq!(query.options.range_year.max, "2020");
+        assert_eq!(query.options.range_category, "main");
+        assert_eq!(query.options.range_time.min, None);
+        assert_eq!(query.options.range_time.max, Some(60 * 3600));
+        assert_eq!(query.options.sort_category, "rating");
+        assert_eq!(
+            query.with_terms("persona 5"),
+            "persona 5 platform:ps4 year:2015..2020 main:<60h sort:rating"
+        );
+    }
+
+    #[test]
+    fn test_plain_query_keeps_defaults() {
+        let query = parse("  The Witcher 3  ").unwrap();
+        assert_eq!(query.text(), "The Witcher 3");
+        assert_eq!(query.options.sort_category, "popular");
+        assert_eq!(query.options.platform, "");
+        assert!(query.filters.is_empty());
+    }
+
+    #[test]
+    fn test_colons_in_titles_are_terms() {
+        let query = parse("Nier:Automata re:zero").unwrap();
+        assert_eq!(query.terms, vec!["Nier:Automata", "re:zero"]);
+    }
+
+    #[test]
+    fn test_quoted_platform() {
+        let query = parse("zelda platform:\"Nintendo Switch\"").unwrap();
+        assert_eq!(query.terms, vec!["zelda"]);
+        assert_eq!(query.options.platform, "Nintendo Switch");
+    }
+
+    #[test]
+    fn test_quoted_filters_survive_new_terms() {
+        let original = parse("zelda platform:\"Nintendo Switch\" main:<60h").unwrap();
+        let text = original.with_terms("zelda");
+        assert_eq!(text, "zelda platform:\"Nintendo Switch\" main:<60h");
+
+        let rebuilt = parse(&text).unwrap();
+        assert_eq!(rebuilt.terms, original.terms);
+        assert_eq!(rebuilt.filters, original.filters);
+        assert_eq!(rebuilt.options.platform, original.options.platform);
+        assert_eq!(
+            rebuilt.options.range_time.max,
+            original.options.range_time.max
+        );
+    }
+
+    #[test]
+    fn test_year_ranges() {
+        let year = |value: &str| parse_year_range(value).map(|r| (r.min, r.max));
+        assert_eq!(year("2015"), Some(("2015".to_string(), "2015".to_string())));
+        assert_eq!(year("2015.."), Some(("2015".to_string(), String::new())));
+        assert_eq!(year("..2000"), Some((String::new(), "2000".to_string())));
+        assert_eq!(year("2020..2015"), None);
+        assert_eq!(year(".."), None);
+        assert_eq!(year("soon"), None);
+        assert_eq!(year("15"), None);
+    }
+
+    #[test]
+    fn test_time_ranges() {
+        let time = |value: &str| parse_time_range(value).map(|r| (r.min, r.max));
+        assert_eq!(time(">10h"), Some((Some(36000), None)));
+        assert_eq!(time("<90m"), Some((None, Some(5400))));
+        assert_eq!(time("10..20h"), Some((Some(36000), Some(72000))));
+        assert_eq!(time("1.5h..3h"), Some((Some(5400), Some(10800))));
+        assert_eq!(time("20..10"), None);
+        assert_eq!(time("<lots"), None);
+    }
+
+    #[test]
+    fn test_time_keys_set_range_category() {
+        assert_eq!(parse("comp:<100h").unwrap().options.range_category, "comp");
+        assert_eq!(parse("extras:>5h").unwrap().options.range_category, "mainp");
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = parse("persona year:soon").unwrap_err();
+        assert_eq!(error.token, "year:soon");
+        assert!(error.to_string().starts_with("year:soon: expected a year"));
+
+        assert_eq!(parse("sort:fun").unwrap_err().token, "sort:fun");
+        assert_eq!(parse("main:forever").unwrap_err().token, "main:forever");
+        assert_eq!(parse("platform:").unwrap_err().token, "platform:");
+    }
+
+    #[test]
+    fn test_unknown_platform_is_passed_through() {
+        assert_eq!(
+            parse("platform:Dreamcast").unwrap().options.platform,
+            "Dreamcast"
+        );
+        assert_eq!(
+            parse("platform:PS5").unwrap().options.platform,
+            "PlayStation 5"
+        );
+    }
+
+    #[test]
+    fn test_platform_names() {
+        let names = platform_names();
+        assert!(
+            names.windows(2).all(|pair| pair[0] < pair[1]),
+            "Sorted without duplicates"
+        );
+        assert!(names.contains(&"PlayStation") && names.contains(&"Nintendo Switch"));
+    }
+}
@@ -0,0 +1,120 @@
+use crate::api::{Game, GameSearchOptions};
+use crate::query::ParsedQuery;
+
+/// How many rolls are remembered
+pub const HISTORY_SIZE: usize = 20;
+/// How often to roll again when the randomizer comes up with a game rolled before
+pub const MAX_REROLLS: usize = 3;
+
+/// What a roll has to match: the filters of whatever produced the results on screen
+#[derive(Debug, Clone, Default)]
+pub struct RollFilters {
+    pub terms: String,
+    pub options: GameSearchOptions,
+    /// The filters as the user gave them, empty for any game
+    pub description: String,
+}
+
+impl RollFilters {
+    /// The filters of a search typed as `text`
+    pub fn from_query(parsed: &ParsedQuery, text: &str) -> Self {
+        Self {
+            terms: parsed.text(),
+            options: parsed.options.clone(),
+            description: text.trim().to_string(),
+        }
+    }
+}
+
+/// The games rolled so far, newest first
+#[derive(Debug, Clone, Default)]
+pub struct RollHistory {
+    rolls: Vec<Game>,
+}
+
+impl RollHistory {
+    /// Remembers `game` as the latest roll. A game rolled again moves to the front.
+    pub fn push(&mut self, game: Game) {
+        self.rolls.retain(|rolled| rolled.game_id != game.game_id);
+        self.rolls.insert(0, game);
+        self.rolls.truncate(HISTORY_SIZE);
+    }
+
+    pub fn latest(&self) -> Option<&Game> {
+        self.rolls.first()
+    }
+
+    /// The rolls before the latest one, newest first
+    pub fn earlier(&self) -> &[Game] {
+        self.rolls.get(1..).unwrap_or_default()
+    }
+
+    pub fn has_rolled(&self, game_id: u64) -> bool {
+        self.rolls.iter().any(|game| game.game_id == game_id)
+    }
+
+    pub fn clear(&mut self) {
+        self.rolls.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(game_id: u64) -> Game {
+        Game {
+            game_id,
+            game_name: format!("Game {}", game_id),
+            ..Default::default()
+        }
+    }
+
+    fn ids(games: &[Game]) -> Vec<u64> {
+        games.iter().map(|game| game.game_id).collect()
+    }
+
+    #[test]
+    fn test_filters_from_query() {
+        let text = " zelda platform:switch ";
+        let filters = RollFilters::from_query(&crate::query::parse(text).unwrap(), text);
+        assert_eq!(filters.terms, "zelda");
+        assert_eq!(filters.options.platform, "Nintendo Switch");
+        assert_eq!(filters.description, "zelda platform:switch");
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = RollHistory::default();
+        assert!(history.latest().is_none());
+        assert!(history.earlier().is_empty());
+
+        history.push(game(1));
+        assert_eq!(history.latest().map(|game| game.game_id), Some(1));
+        assert!(history.earlier().is_empty());
+
+        history.push(game(2));
+        history.push(game(3));
+        assert_eq!(ids(history.earlier()), vec![2, 1]);
+
+        // Rolling a game again moves it to the front instead of listing it twice
+        history.push(game(1));
+        assert_eq!(history.latest().map(|game| game.game_id), Some(1));
+        assert_eq!(ids(history.earlier()), vec![3, 2]);
+        assert!(history.has_rolled(2) && !history.has_rolled(4));
+
+        history.clear();
+        assert!(history.latest().is_none());
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut history = RollHistory::default();
+        for game_id in 0..HISTORY_SIZE as u64 + 5 {
+            history.push(game(game_id));
+        }
+        assert_eq!(history.earlier().len(), HISTORY_SIZE - 1);
+        assert!(!history.has_rolled(4), "The oldest rolls are forgotten");
+        assert!(history.has_rolled(5));
+    }
+}
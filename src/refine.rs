@@ -0,0 +1,250 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BTreeSet;
+
+use crate::api::Game;
+use crate::i18n::gettext;
+use crate::reliability::{self, Reliability};
+use crate::settings::TimeCategory;
+
+/// Orders for the loaded results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// The order HowLongToBeat returned them in
+    #[default]
+    Relevance,
+    Main,
+    MainExtras,
+    Completionist,
+    ReviewScore,
+    Popularity,
+    ReleaseDate,
+    RatingCount,
+}
+
+impl SortKey {
+    pub const ALL: [Self; 8] = [
+        Self::Relevance,
+        Self::Main,
+        Self::MainExtras,
+        Self::Completionist,
+        Self::ReviewScore,
+        Self::Popularity,
+        Self::ReleaseDate,
+        Self::RatingCount,
+    ];
+
+    pub fn label(self) -> String {
+        match self {
+            Self::Relevance => gettext("Best Match"),
+            Self::Main => gettext("Shortest Main Story"),
+            Self::MainExtras => gettext("Shortest Main + Extras"),
+            Self::Completionist => gettext("Shortest Completionist"),
+            Self::ReviewScore => gettext("Highest Rated"),
+            Self::Popularity => gettext("Most Popular"),
+            Self::ReleaseDate => gettext("Newest"),
+            Self::RatingCount => gettext("Most Ratings"),
+        }
+    }
+
+    /// Whether the value sorted on is too thinly rated to go by, which sends the game below
+    /// the ones that can be trusted. Only times and review scores rest on ratings; popularity,
+    /// release dates and rating counts sort as they are, or "Newest" would bury new releases.
+    fn is_doubtful(self, game: &Game) -> bool {
+        let reliability = match self {
+            Self::Relevance | Self::Popularity | Self::ReleaseDate | Self::RatingCount => return false,
+            Self::Main => reliability::grade(game, TimeCategory::Main),
+            Self::MainExtras => reliability::grade(game, TimeCategory::MainExtras),
+            Self::Completionist => reliability::grade(game, TimeCategory::Completionist),
+            Self::ReviewScore => Some(Reliability::from_count(game.count_review)),
+        };
+        // Games without a time sort after the weakly rated ones
+        reliability.is_none_or(|reliability| reliability == Reliability::Weak)
+    }
+
+    fn compare(self, a: &Game, b: &Game) -> Ordering {
+        match self {
+            Self::Relevance => Ordering::Equal,
+            Self::Main => by_time(TimeCategory::Main, a, b),
+            Self::MainExtras => by_time(TimeCategory::MainExtras, a, b),
+            Self::Completionist => by_time(TimeCategory::Completionist, a, b),
+            Self::ReviewScore => Reverse(a.rating()).cmp(&Reverse(b.rating())),
+            Self::Popularity => b.profile_popular.cmp(&a.profile_popular),
+            Self::ReleaseDate => b.release_world.cmp(&a.release_world),
+            Self::RatingCount => b.count_comp.cmp(&a.count_comp),
+        }
+    }
+}
+
+/// Shortest first, games without a time last
+fn by_time(category: TimeCategory, a: &Game, b: &Game) -> Ordering {
+    match (category.hours(a), category.hours(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Which of the loaded results to show
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filters {
+    /// Hide games nobody submitted a time for
+    pub hide_untimed: bool,
+    /// Hide games whose time in the shown category rests on only a few ratings
+    pub hide_low_sample: bool,
+    /// Only show games on one of these platforms; all of them when empty
+    pub platforms: BTreeSet<String>,
+}
+
+impl Filters {
+    fn accepts(&self, game: &Game, category: TimeCategory) -> bool {
+        if self.hide_untimed && TimeCategory::ALL.iter().all(|category| category.hours(game).is_none()) {
+            return false;
+        }
+        if self.hide_low_sample && reliability::grade(game, category) == Some(Reliability::Weak) {
+            return false;
+        }
+        self.platforms.is_empty() || game_platforms(game).any(|platform| self.platforms.contains(platform))
+    }
+}
+
+/// The platforms a game is listed on
+pub fn game_platforms(game: &Game) -> impl Iterator<Item = &str> {
+    game.profile_platform
+        .split(',')
+        .map(str::trim)
+        .filter(|platform| !platform.is_empty())
+}
+
+/// Every platform among the results, alphabetically, to offer as filters
+pub fn platforms(games: &[Game]) -> Vec<String> {
+    games
+        .iter()
+        .flat_map(game_platforms)
+        .map(str::to_string)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// The results to show, filtered and sorted. `category` is the time shown on the results,
+/// which the low sample filter looks at. Thinly rated games go after the rest, and games that
+/// compare equal keep their original order.
+pub fn refine(games: &[Game], sort: SortKey, filters: &Filters, category: TimeCategory) -> Vec<Game> {
+    let mut refined: Vec<Game> = games
+        .iter()
+        .filter(|game| filters.accepts(game, category))
+        .cloned()
+        .collect();
+    refined.sort_by(|a, b| {
+        sort.is_doubtful(a)
+            .cmp(&sort.is_doubtful(b))
+            .then_with(|| sort.compare(a, b))
+    });
+    refined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(name: &str, main_hours: u32, main_count: u32, platforms: &str) -> Game {
+        Game {
+            game_name: name.to_string(),
+            comp_main: main_hours * 3600,
+            comp_main_count: main_count,
+            count_comp: main_count,
+            profile_platform: platforms.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn names(games: &[Game]) -> Vec<&str> {
+        games.iter().map(|game| game.game_name.as_str()).collect()
+    }
+
+    fn games() -> Vec<Game> {
+        vec![
+            Game {
+                review_score: 70,
+                count_review: 10,
+                profile_popular: 50,
+                release_world: 2015,
+                ..game("Long", 40, 300, "PC, PlayStation 4")
+            },
+            Game {
+                profile_popular: 500,
+                release_world: 2023,
+                ..game("Untimed", 0, 0, "Nintendo Switch")
+            },
+            Game {
+                review_score: 90,
+                count_review: 10,
+                profile_popular: 10,
+                ..game("Short", 5, 2, "PC")
+            },
+        ]
+    }
+
+    #[test]
+    fn test_sort() {
+        let games = games();
+        let sorted = |sort| names(&refine(&games, sort, &Filters::default(), TimeCategory::Main)).join(", ");
+
+        assert_eq!(sorted(SortKey::Relevance), "Long, Untimed, Short");
+        // Short's time rests on two ratings, so it goes after the trusted one, and games
+        // without a time go last
+        assert_eq!(sorted(SortKey::Main), "Long, Short, Untimed");
+        assert_eq!(sorted(SortKey::Completionist), "Long, Untimed, Short");
+        assert_eq!(sorted(SortKey::ReviewScore), "Short, Long, Untimed");
+        // Sorts that don't rest on ratings ignore how well rated the games are
+        assert_eq!(sorted(SortKey::Popularity), "Untimed, Long, Short");
+        assert_eq!(sorted(SortKey::ReleaseDate), "Untimed, Long, Short");
+        assert_eq!(sorted(SortKey::RatingCount), "Long, Short, Untimed");
+    }
+
+    #[test]
+    fn test_filters() {
+        let games = games();
+        let filtered = |filters: &Filters| {
+            names(&refine(&games, SortKey::Relevance, filters, TimeCategory::Main)).join(", ")
+        };
+
+        assert_eq!(
+            filtered(&Filters {
+                hide_untimed: true,
+                ..Default::default()
+            }),
+            "Long, Short"
+        );
+        // Only games that have a time can have too few ratings for it
+        assert_eq!(
+            filtered(&Filters {
+                hide_low_sample: true,
+                ..Default::default()
+            }),
+            "Long, Untimed"
+        );
+        assert_eq!(
+            filtered(&Filters {
+                platforms: BTreeSet::from(["PC".to_string()]),
+                ..Default::default()
+            }),
+            "Long, Short"
+        );
+        assert_eq!(
+            filtered(&Filters {
+                hide_untimed: true,
+                platforms: BTreeSet::from(["Nintendo Switch".to_string()]),
+                ..Default::default()
+            }),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_platforms() {
+        assert_eq!(platforms(&games()), vec!["Nintendo Switch", "PC", "PlayStation 4"]);
+        assert!(platforms(&[game("Unlisted", 1, 1, "")]).is_empty());
+    }
+}
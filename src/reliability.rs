@@ -0,0 +1,78 @@
+use crate::api::Game;
+use crate::i18n::gettext_f;
+use crate::settings::TimeCategory;
+
+/// Ratings needed before a time is more than a guess
+pub const FAIR_RATINGS: u32 = 5;
+/// Ratings needed before a time can be taken at face value
+pub const STRONG_RATINGS: u32 = 50;
+
+/// How far a number submitted by players can be trusted, by how many players submitted it.
+/// Ordered from weakest to strongest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Reliability {
+    Weak,
+    Fair,
+    Strong,
+}
+
+impl Reliability {
+    pub fn from_count(count: u32) -> Self {
+        if count >= STRONG_RATINGS {
+            Self::Strong
+        } else if count >= FAIR_RATINGS {
+            Self::Fair
+        } else {
+            Self::Weak
+        }
+    }
+
+    /// Why a weak grade shouldn't be trusted; the others need no warning
+    pub fn warning(self) -> Option<String> {
+        match self {
+            Self::Weak => Some(gettext_f(
+                "Based on fewer than {count} ratings, so it may be far off",
+                &[("count", &FAIR_RATINGS.to_string())],
+            )),
+            Self::Fair | Self::Strong => None,
+        }
+    }
+}
+
+/// The grade of the game's time in `category`, or `None` if it has no time there
+pub fn grade(game: &Game, category: TimeCategory) -> Option<Reliability> {
+    category.hours(game)?;
+    Some(Reliability::from_count(category.count(game)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_count() {
+        assert_eq!(Reliability::from_count(0), Reliability::Weak);
+        assert_eq!(Reliability::from_count(2), Reliability::Weak);
+        assert_eq!(Reliability::from_count(FAIR_RATINGS), Reliability::Fair);
+        assert_eq!(Reliability::from_count(STRONG_RATINGS - 1), Reliability::Fair);
+        assert_eq!(Reliability::from_count(20000), Reliability::Strong);
+        assert!(Reliability::Weak < Reliability::Fair && Reliability::Fair < Reliability::Strong);
+
+        assert!(Reliability::Weak.warning().is_some());
+        assert_eq!(Reliability::Strong.warning(), None);
+    }
+
+    #[test]
+    fn test_grade() {
+        let game = Game {
+            comp_main: 36000,
+            comp_main_count: 20000,
+            comp_100: 144000,
+            comp_100_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(grade(&game, TimeCategory::Main), Some(Reliability::Strong));
+        assert_eq!(grade(&game, TimeCategory::Completionist), Some(Reliability::Weak));
+        assert_eq!(grade(&game, TimeCategory::MainExtras), None, "No time, nothing to grade");
+    }
+}
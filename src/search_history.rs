@@ -0,0 +1,394 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::api::Game;
+use crate::json_file::write_json_atomically;
+
+// Format of history.json; histories from newer formats are refused rather than overwritten
+const HISTORY_FORMAT_VERSION: u32 = 1;
+/// Results kept with each search, offered as suggestions when typing something similar
+const RESULTS_PER_QUERY: usize = 5;
+
+/// Something the user looked up before
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum HistoryEntry {
+    /// A submitted search, with its first few results
+    Query { text: String, results: Vec<Game>, at: u64 },
+    /// A game opened on its own page
+    Game { game: Box<Game>, at: u64 },
+}
+
+/// What the search field can suggest
+#[derive(Debug, Clone)]
+pub enum Suggestion {
+    /// A search from the history
+    Query(String),
+    /// A game opened before, or one found by an earlier search
+    Game(Box<Game>),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct HistoryFile {
+    version: u32,
+    entries: Vec<HistoryEntry>,
+}
+
+/// Searches and opened games, newest first, deduplicated and capped at `limit` entries
+pub struct SearchHistory {
+    /// Where the history is saved; `None` keeps it in memory only
+    path: Option<PathBuf>,
+    entries: Vec<HistoryEntry>,
+    limit: usize,
+}
+
+impl SearchHistory {
+    /// Default location: `$XDG_DATA_HOME/howlongtobeat/history.json`
+    pub fn default_path() -> PathBuf {
+        glib::user_data_dir().join("howlongtobeat").join("history.json")
+    }
+
+    /// Loads the history from `path`, or starts an empty one if it doesn't exist yet
+    pub fn load_from(path: impl Into<PathBuf>, limit: usize) -> Result<Self> {
+        let path = path.into();
+        if !path.exists() {
+            return Ok(Self::empty(Some(path), limit));
+        }
+
+        let contents =
+            std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let file: HistoryFile =
+            serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?;
+        if file.version > HISTORY_FORMAT_VERSION {
+            bail!(
+                "{} was written by a newer version of the app (format {})",
+                path.display(),
+                file.version
+            );
+        }
+        let mut history = Self::empty(Some(path), limit);
+        history.entries = file.entries;
+        history.entries.truncate(limit);
+        Ok(history)
+    }
+
+    /// Loads the history from `path`. A file that can't be read is renamed to
+    /// `history.broken.json`, replacing an earlier one, and the history starts over in its
+    /// place; if it can't be moved, the history is kept in memory only so the file is left as is.
+    pub fn load_or_recover(path: impl Into<PathBuf>, limit: usize) -> Self {
+        let path = path.into();
+        match Self::load_from(&path, limit) {
+            Ok(history) => history,
+            Err(e) => {
+                eprintln!("Failed to load search history: {:#}", e);
+                let broken = path.with_extension("broken.json");
+                match std::fs::rename(&path, &broken) {
+                    Ok(()) => {
+                        eprintln!("Moved the unreadable search history to {}", broken.display());
+                        Self::empty(Some(path), limit)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to move {} aside, not saving the search history: {}", path.display(), e);
+                        Self::empty(None, limit)
+                    }
+                }
+            }
+        }
+    }
+
+    fn empty(path: Option<PathBuf>, limit: usize) -> Self {
+        Self {
+            path,
+            entries: Vec::new(),
+            limit,
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = HistoryFile {
+            version: HISTORY_FORMAT_VERSION,
+            entries: self.entries.clone(),
+        };
+        write_json_atomically(path, &file)
+    }
+
+    /// Changes how many entries are kept, dropping the oldest ones past it. 0 keeps none.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.entries.truncate(limit);
+    }
+
+    /// Remembers a submitted search. Searching for the same text again, in any case, moves it
+    /// to the front and keeps the results from before until new ones come in.
+    pub fn record_query(&mut self, text: &str, now: u64) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        let key = text.to_lowercase();
+        let mut results = Vec::new();
+        self.entries.retain(|entry| match entry {
+            HistoryEntry::Query { text: recorded, results: recorded_results, .. }
+                if recorded.to_lowercase() == key =>
+            {
+                results = recorded_results.clone();
+                false
+            }
+            _ => true,
+        });
+        self.push(HistoryEntry::Query {
+            text: text.to_string(),
+            results,
+            at: now,
+        });
+    }
+
+    /// Keeps the first results of a search already in the history. Searches that weren't
+    /// submitted, like the ones run while typing, are left out.
+    pub fn remember_results(&mut self, text: &str, games: &[Game]) {
+        let key = text.trim().to_lowercase();
+        for entry in &mut self.entries {
+            if let HistoryEntry::Query { text, results, .. } = entry {
+                if text.to_lowercase() == key {
+                    *results = games.iter().take(RESULTS_PER_QUERY).cloned().collect();
+                }
+            }
+        }
+    }
+
+    /// Remembers an opened game, moving it to the front if it was opened before
+    pub fn record_game(&mut self, game: &Game, now: u64) {
+        if game.game_id == 0 {
+            return;
+        }
+        self.entries.retain(|entry| match entry {
+            HistoryEntry::Game { game: opened, .. } => opened.game_id != game.game_id,
+            HistoryEntry::Query { .. } => true,
+        });
+        self.push(HistoryEntry::Game {
+            game: Box::new(game.clone()),
+            at: now,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn push(&mut self, entry: HistoryEntry) {
+        self.entries.insert(0, entry);
+        self.entries.truncate(self.limit);
+    }
+
+    /// Up to `max` suggestions for `input`, newest first: searches, then opened games, then
+    /// games earlier searches found. Every word typed has to start a word of the suggestion.
+    /// With nothing typed, the latest searches and games are suggested.
+    pub fn suggestions(&self, input: &str, max: usize) -> Vec<Suggestion> {
+        let typed = words(input);
+        let key = input.trim().to_lowercase();
+        let matches = |candidate: &str| {
+            let candidate = words(candidate);
+            typed.iter().all(|typed| candidate.iter().any(|word| word.starts_with(typed.as_str())))
+        };
+
+        let mut queries = Vec::new();
+        let mut opened = Vec::new();
+        let mut found = Vec::new();
+        for entry in &self.entries {
+            match entry {
+                HistoryEntry::Query { text, results, .. } => {
+                    // No point suggesting what's already typed
+                    if text.to_lowercase() != key && matches(text) {
+                        queries.push(Suggestion::Query(text.clone()));
+                    }
+                    found.extend(results.iter().filter(|game| matches(&game.game_name)));
+                }
+                HistoryEntry::Game { game, .. } if matches(&game.game_name) => opened.push(game.as_ref()),
+                HistoryEntry::Game { .. } => {}
+            }
+        }
+
+        let mut game_ids = Vec::new();
+        let games = opened.into_iter().chain(found).filter(|game| {
+            let new = !game_ids.contains(&game.game_id);
+            game_ids.push(game.game_id);
+            new
+        });
+        queries
+            .into_iter()
+            .chain(games.map(|game| Suggestion::Game(Box::new(game.clone()))))
+            .take(max)
+            .collect()
+    }
+}
+
+/// Lowercase words of a title or query, split on anything that isn't a letter or digit
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(game_id: u64, name: &str) -> Game {
+        Game {
+            game_id,
+            game_name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("hltb-history-test-{}-{}", name, std::process::id()))
+            .join("history.json")
+    }
+
+    /// Entries and suggestions in a few words, like "query:zelda" or "game:7"
+    fn ids(entries: &[HistoryEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| match entry {
+                HistoryEntry::Query { text, .. } => format!("query:{}", text),
+                HistoryEntry::Game { game, .. } => format!("game:{}", game.game_id),
+            })
+            .collect()
+    }
+
+    fn suggested(history: &SearchHistory, input: &str, max: usize) -> Vec<String> {
+        history
+            .suggestions(input, max)
+            .into_iter()
+            .map(|suggestion| match suggestion {
+                Suggestion::Query(text) => format!("query:{}", text),
+                Suggestion::Game(game) => format!("game:{}", game.game_id),
+            })
+            .collect()
+    }
+
+    fn history(limit: usize) -> SearchHistory {
+        SearchHistory::load_from(temp_path("unsaved"), limit).unwrap()
+    }
+
+    #[test]
+    fn test_records_are_deduplicated() {
+        let mut history = history(10);
+        history.record_query("Hollow Knight", 1);
+        history.record_game(&game(7, "Celeste"), 2);
+        history.record_query("  hollow knight ", 3);
+        history.record_game(&game(7, "Celeste"), 4);
+        history.record_query("", 5);
+        history.record_game(&game(0, "No Id"), 6);
+
+        assert_eq!(ids(&history.entries), vec!["game:7", "query:hollow knight"]);
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut history = history(3);
+        for (at, text) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            history.record_query(text, at as u64);
+        }
+        assert_eq!(history.entries.len(), 3);
+        assert_eq!(suggested(&history, "", 10), vec!["query:d", "query:c", "query:b"]);
+
+        history.set_limit(1);
+        assert_eq!(history.entries.len(), 1);
+        history.set_limit(0);
+        history.record_query("e", 5);
+        assert!(history.entries.is_empty(), "A limit of 0 keeps no history");
+    }
+
+    #[test]
+    fn test_results_are_kept_with_their_query() {
+        let mut history = history(10);
+        let results: Vec<Game> = (1..=8).map(|id| game(id, &format!("Zelda {}", id))).collect();
+        // Searches nobody submitted aren't remembered
+        history.remember_results("mario", &results);
+        assert!(history.entries.is_empty());
+
+        history.record_query("Zelda", 1);
+        history.remember_results("zelda", &results);
+        // Submitting it again keeps the results until new ones come in
+        history.record_query("zelda", 2);
+        let HistoryEntry::Query { results, .. } = &history.entries[0] else {
+            panic!("Expected a query");
+        };
+        assert_eq!(results.len(), RESULTS_PER_QUERY);
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let mut history = history(10);
+        history.record_query("dark souls", 1);
+        history.remember_results("dark souls", &[game(1, "Dark Souls III"), game(2, "Dark Souls II")]);
+        history.record_game(&game(1, "Dark Souls III"), 2);
+        history.record_game(&game(3, "Darkest Dungeon"), 3);
+        history.record_query("Stardew Valley", 4);
+
+        assert_eq!(suggested(&history, "dark s", 10), vec!["query:dark souls", "game:1", "game:2"]);
+        assert_eq!(suggested(&history, "DARK", 2), vec!["query:dark souls", "game:3"]);
+        // Words match in any order, but only from their start
+        assert_eq!(suggested(&history, "valley star", 10), vec!["query:Stardew Valley"]);
+        assert!(suggested(&history, "ark", 10).is_empty());
+        // The text already typed isn't suggested back
+        assert_eq!(suggested(&history, "Dark Souls", 10), vec!["game:1", "game:2"]);
+
+        history.clear();
+        assert!(history.suggestions("", 10).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_reload() {
+        let path = temp_path("roundtrip");
+        let mut history = SearchHistory::load_from(&path, 10).unwrap();
+        history.record_query("celeste", 1);
+        history.remember_results("celeste", &[game(7, "Celeste")]);
+        history.record_game(&game(9, "Hades"), 2);
+        history.save().unwrap();
+
+        let reloaded = SearchHistory::load_from(&path, 10).unwrap();
+        assert_eq!(ids(&reloaded.entries), vec!["game:9", "query:celeste"]);
+        let HistoryEntry::Query { results, .. } = &reloaded.entries[1] else {
+            panic!("Expected a query");
+        };
+        assert_eq!(results[0].game_name, "Celeste");
+        assert_eq!(SearchHistory::load_from(&path, 1).unwrap().entries.len(), 1);
+
+        std::fs::write(&path, r#"{"version": 99, "entries": []}"#).unwrap();
+        assert!(SearchHistory::load_from(&path, 10).is_err(), "Newer formats aren't overwritten");
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_unreadable_history_is_moved_aside() {
+        let path = temp_path("recover");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let broken = path.with_extension("broken.json");
+
+        for contents in ["not json", r#"{"version": 99, "entries": []}"#] {
+            std::fs::write(&path, contents).unwrap();
+            let mut history = SearchHistory::load_or_recover(&path, 10);
+            assert!(history.entries.is_empty());
+            assert_eq!(std::fs::read_to_string(&broken).unwrap(), contents, "The broken file is kept");
+
+            // The history goes on in its usual place
+            history.record_query("celeste", 1);
+            history.save().unwrap();
+            assert_eq!(SearchHistory::load_from(&path, 10).unwrap().entries.len(), 1);
+        }
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
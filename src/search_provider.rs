@@ -0,0 +1,495 @@
+use anyhow::Result;
+use gio::prelude::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::api::{Game, HltbClient};
+use crate::i18n::gettext_f;
+use crate::settings::{Settings, TimeCategory};
+use crate::time_format::TimeFormatter;
+
+/// Object path of the search provider, as listed in the search provider `.ini` file
+pub const OBJECT_PATH: &str = "/com/github/justadreamerfl/HowLongToBeat/SearchProvider";
+
+// Results shown in the overview; the shell only displays a handful per provider anyway
+const MAX_RESULTS: usize = 5;
+// Shorter queries match too much to be useful
+const MIN_QUERY_LENGTH: usize = 3;
+// Result sets kept for `GetResultMetas` and `ActivateResult`: the shell can still ask about the
+// previous one while the next is being searched
+const KEPT_RESULT_SETS: usize = 2;
+
+const INTERFACE_XML: &str = r#"
+<node>
+  <interface name="org.gnome.Shell.SearchProvider2">
+    <method name="GetInitialResultSet">
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="as" name="results" direction="out"/>
+    </method>
+    <method name="GetSubsearchResultSet">
+      <arg type="as" name="previous_results" direction="in"/>
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="as" name="results" direction="out"/>
+    </method>
+    <method name="GetResultMetas">
+      <arg type="as" name="identifiers" direction="in"/>
+      <arg type="aa{sv}" name="metas" direction="out"/>
+    </method>
+    <method name="ActivateResult">
+      <arg type="s" name="identifier" direction="in"/>
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="u" name="timestamp" direction="in"/>
+    </method>
+    <method name="LaunchSearch">
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="u" name="timestamp" direction="in"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+#[derive(Debug)]
+enum ProviderMethod {
+    GetInitialResultSet(Vec<String>),
+    GetSubsearchResultSet(Vec<String>),
+    GetResultMetas(Vec<String>),
+    ActivateResult(String),
+    LaunchSearch(Vec<String>),
+}
+
+impl DBusMethodCall for ProviderMethod {
+    fn parse_call(
+        _obj_path: &str,
+        _interface: Option<&str>,
+        method: &str,
+        params: glib::Variant,
+    ) -> Result<Self, glib::Error> {
+        let invalid = || {
+            glib::Error::new(
+                gio::IOErrorEnum::InvalidArgument,
+                &format!("Invalid arguments for {}", method),
+            )
+        };
+
+        match method {
+            "GetInitialResultSet" => params
+                .get::<(Vec<String>,)>()
+                .map(|(terms,)| Self::GetInitialResultSet(terms)),
+            "GetSubsearchResultSet" => params
+                .get::<(Vec<String>, Vec<String>)>()
+                .map(|(_, terms)| Self::GetSubsearchResultSet(terms)),
+            "GetResultMetas" => params
+                .get::<(Vec<String>,)>()
+                .map(|(ids,)| Self::GetResultMetas(ids)),
+            "ActivateResult" => params
+                .get::<(String, Vec<String>, u32)>()
+                .map(|(id, _, _)| Self::ActivateResult(id)),
+            "LaunchSearch" => params
+                .get::<(Vec<String>, u32)>()
+                .map(|(terms, _)| Self::LaunchSearch(terms)),
+            _ => {
+                return Err(glib::Error::new(
+                    gio::IOErrorEnum::NotSupported,
+                    &format!("Unknown method {}", method),
+                ))
+            }
+        }
+        .ok_or_else(invalid)
+    }
+}
+
+pub type SearchFuture = Pin<Box<dyn Future<Output = Result<Vec<Game>>>>>;
+
+/// Where the provider gets its results from
+pub trait SearchBackend {
+    fn search(&self, query: String) -> SearchFuture;
+}
+
+impl SearchBackend for Arc<HltbClient> {
+    fn search(&self, query: String) -> SearchFuture {
+        let client = self.clone();
+        Box::pin(async move { HltbClient::search(&client, &query).await })
+    }
+}
+
+/// What the user picked in the overview
+#[derive(Debug, Clone)]
+pub enum Activation {
+    /// A result; the game is included when it's still cached from the search
+    Game(u64, Option<Box<Game>>),
+    /// "Show more results" for these search terms
+    Search(String),
+}
+
+/// Serves HLTB results to the GNOME Shell overview
+pub struct SearchProvider {
+    backend: Box<dyn SearchBackend>,
+    /// Games from the latest searches, newest first, for `GetResultMetas` and `ActivateResult`
+    result_sets: RefCell<VecDeque<Vec<Game>>>,
+    /// Where covers are downloaded to; without one, results get a generic icon
+    cover_dir: Option<PathBuf>,
+    /// Decides which completion time the results show
+    settings: Settings,
+    /// Held while a request is running so a D-Bus activated app doesn't quit mid-search
+    application: Option<gio::Application>,
+    on_activate: Box<dyn Fn(Activation)>,
+}
+
+impl SearchProvider {
+    pub fn new(backend: impl SearchBackend + 'static, on_activate: impl Fn(Activation) + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+            result_sets: RefCell::default(),
+            cover_dir: None,
+            settings: Settings::in_memory(),
+            application: None,
+            on_activate: Box::new(on_activate),
+        }
+    }
+
+    pub fn with_covers(mut self, cover_dir: PathBuf) -> Self {
+        self.cover_dir = Some(cover_dir);
+        self
+    }
+
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_application(mut self, application: &impl IsA<gio::Application>) -> Self {
+        self.application = Some(application.clone().upcast());
+        self
+    }
+
+    /// The default cover cache directory
+    pub fn default_cover_dir() -> PathBuf {
+        glib::user_cache_dir().join("howlongtobeat").join("covers")
+    }
+
+    /// Exports the provider on `connection` at [`OBJECT_PATH`]
+    pub fn register(self: &Rc<Self>, connection: &gio::DBusConnection) -> Result<gio::RegistrationId, glib::Error> {
+        let node = gio::DBusNodeInfo::for_xml(INTERFACE_XML)?;
+        let interface = node
+            .lookup_interface("org.gnome.Shell.SearchProvider2")
+            .expect("The interface is defined in INTERFACE_XML");
+
+        let provider = self.clone();
+        connection
+            .register_object(OBJECT_PATH, &interface)
+            .typed_method_call::<ProviderMethod>()
+            .invoke_and_return_future_local(move |_, _, call| {
+                let provider = provider.clone();
+                async move { provider.handle(call).await }
+            })
+            .build()
+    }
+
+    async fn handle(&self, call: ProviderMethod) -> Result<Option<glib::Variant>, glib::Error> {
+        let _hold = self.application.as_ref().map(|app| app.hold());
+
+        match call {
+            // Subsearches query again: HLTB results for a longer query aren't a subset
+            ProviderMethod::GetInitialResultSet(terms) | ProviderMethod::GetSubsearchResultSet(terms) => {
+                Ok(Some(self.search(&terms).await.to_variant()))
+            }
+            ProviderMethod::GetResultMetas(ids) => Ok(Some(self.result_metas(&ids).await.to_variant())),
+            ProviderMethod::ActivateResult(id) => {
+                let game_id = id.parse().map_err(|_| {
+                    glib::Error::new(gio::IOErrorEnum::InvalidArgument, &format!("Unknown result {}", id))
+                })?;
+                let game = self.result(&id);
+                (self.on_activate)(Activation::Game(game_id, game.map(Box::new)));
+                Ok(None)
+            }
+            ProviderMethod::LaunchSearch(terms) => {
+                (self.on_activate)(Activation::Search(terms.join(" ")));
+                Ok(None)
+            }
+        }
+    }
+
+    async fn search(&self, terms: &[String]) -> Vec<String> {
+        let query = terms.join(" ");
+        if query.chars().count() < MIN_QUERY_LENGTH {
+            return Vec::new();
+        }
+
+        let games = match self.backend.search(query.clone()).await {
+            Ok(games) => games,
+            Err(e) => {
+                eprintln!("Search provider lookup failed for '{}': {}", query, e);
+                return Vec::new();
+            }
+        };
+
+        let games: Vec<Game> = games.into_iter().take(MAX_RESULTS).collect();
+        let ids = games.iter().map(|game| game.game_id.to_string()).collect();
+        let mut result_sets = self.result_sets.borrow_mut();
+        result_sets.push_front(games);
+        result_sets.truncate(KEPT_RESULT_SETS);
+        ids
+    }
+
+    /// A game from one of the latest result sets
+    fn result(&self, id: &str) -> Option<Game> {
+        self.result_sets
+            .borrow()
+            .iter()
+            .flatten()
+            .find(|game| game.game_id.to_string() == id)
+            .cloned()
+    }
+
+    async fn result_metas(&self, ids: &[String]) -> Vec<HashMap<String, glib::Variant>> {
+        let games: Vec<Game> = ids.iter().filter_map(|id| self.result(id)).collect();
+
+        let time_category = self.settings.time_category();
+        let time_formatter = self.settings.time_formatter();
+        let mut metas = Vec::with_capacity(games.len());
+        for game in games {
+            let mut meta = HashMap::new();
+            meta.insert("id".to_string(), game.game_id.to_string().to_variant());
+            meta.insert("name".to_string(), game.game_name.to_variant());
+            meta.insert("description".to_string(), result_description(&game, time_category, &time_formatter).to_variant());
+
+            match self.cover(&game).await {
+                Some(path) => {
+                    let icon = gio::FileIcon::new(&gio::File::for_path(path));
+                    if let Some(serialized) = icon.serialize() {
+                        meta.insert("icon".to_string(), serialized);
+                    }
+                }
+                None => {
+                    meta.insert("gicon".to_string(), "applications-games-symbolic".to_variant());
+                }
+            }
+            metas.push(meta);
+        }
+        metas
+    }
+
+    /// Returns the cached cover of a game, downloading it first if needed
+    async fn cover(&self, game: &Game) -> Option<PathBuf> {
+        let cover_dir = self.cover_dir.as_ref()?;
+        let path = cover_path(cover_dir, game)?;
+        if path.is_file() {
+            return Some(path);
+        }
+
+        let response = reqwest::get(game.image_url()).await.ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        let bytes = response.bytes().await.ok()?;
+        std::fs::create_dir_all(cover_dir).ok()?;
+        std::fs::write(&path, &bytes).ok()?;
+        Some(path)
+    }
+}
+
+/// Where a game's cover is cached. The image name comes from the server, so anything but a
+/// plain file name, which could point outside the cache, gets no cover.
+fn cover_path(cover_dir: &Path, game: &Game) -> Option<PathBuf> {
+    let file_name = Path::new(&game.game_image).file_name()?;
+    (file_name == game.game_image.as_str()).then(|| cover_dir.join(file_name))
+}
+
+/// The line under the game name in the overview
+fn result_description(game: &Game, time_category: TimeCategory, time_formatter: &TimeFormatter) -> String {
+    let mut parts = Vec::new();
+    if let Some(hours) = time_category.hours(game) {
+        parts.push(gettext_f(
+            "{category} {time}",
+            &[("category", &time_category.label()), ("time", &time_formatter.format(hours))],
+        ));
+    }
+    if game.release_world > 0 {
+        parts.push(game.release_world.to_string());
+    }
+    parts.join(" · ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    struct StubBackend;
+
+    impl SearchBackend for StubBackend {
+        fn search(&self, query: String) -> SearchFuture {
+            let games = if query == "witcher" {
+                (1..=7)
+                    .map(|id| Game {
+                        game_id: id,
+                        game_name: format!("The Witcher {}", id),
+                        comp_main: 36000,
+                        release_world: 2015,
+                        ..Default::default()
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            Box::pin(async move { Ok(games) })
+        }
+    }
+
+    #[test]
+    fn test_result_description() {
+        let game = Game {
+            comp_main: 36000,
+            release_world: 2015,
+            ..Default::default()
+        };
+        let time_formatter = TimeFormatter::default();
+        assert_eq!(
+            result_description(&game, TimeCategory::Main, &time_formatter),
+            format!("Main Story {} · 2015", time_formatter.format(10.0))
+        );
+        assert_eq!(result_description(&game, TimeCategory::Completionist, &time_formatter), "2015");
+        assert_eq!(result_description(&Game::default(), TimeCategory::Main, &time_formatter), "");
+    }
+
+    #[test]
+    fn test_only_latest_result_sets_are_kept() {
+        struct EchoBackend;
+
+        impl SearchBackend for EchoBackend {
+            fn search(&self, query: String) -> SearchFuture {
+                let game = Game {
+                    game_id: query.len() as u64,
+                    game_name: query,
+                    ..Default::default()
+                };
+                Box::pin(async move { Ok(vec![game]) })
+            }
+        }
+
+        let provider = SearchProvider::new(EchoBackend, |_| {});
+        let context = glib::MainContext::new();
+        for query in ["wit", "witc", "witch"] {
+            context.block_on(provider.search(&[query.to_string()]));
+        }
+        assert!(provider.result("3").is_none(), "Older result sets are dropped");
+        assert_eq!(provider.result("4").unwrap().game_name, "witc");
+        assert_eq!(provider.result("5").unwrap().game_name, "witch");
+    }
+
+    #[test]
+    fn test_cover_path() {
+        let cover_dir = Path::new("/cache/covers");
+        let game = |image: &str| Game {
+            game_image: image.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            cover_path(cover_dir, &game("10270_The_Witcher_3.jpg")),
+            Some(cover_dir.join("10270_The_Witcher_3.jpg"))
+        );
+        assert_eq!(cover_path(cover_dir, &game("")), None);
+        assert_eq!(cover_path(cover_dir, &game("../../.config/autostart/x.desktop")), None);
+        assert_eq!(cover_path(cover_dir, &game("/etc/passwd")), None);
+        assert_eq!(cover_path(cover_dir, &game("covers/../x.jpg")), None);
+        assert_eq!(cover_path(cover_dir, &game("..")), None);
+    }
+
+    /// Talks to the provider over a private session bus, like GNOME Shell would
+    #[test]
+    fn test_search_provider_over_dbus() {
+        if glib::find_program_in_path("dbus-daemon").is_none() {
+            eprintln!("Skipping D-Bus test: dbus-daemon is not installed");
+            return;
+        }
+
+        let context = glib::MainContext::new();
+        context
+            .with_thread_default(|| {
+                let test_bus = gio::TestDBus::new(gio::TestDBusFlags::NONE);
+                test_bus.up();
+                let address = test_bus.bus_address().expect("The test bus is up").to_string();
+
+                let activated: Rc<Cell<Option<u64>>> = Rc::new(Cell::new(None));
+                let activated_clone = activated.clone();
+                let provider = Rc::new(SearchProvider::new(StubBackend, move |activation| {
+                    if let Activation::Game(id, Some(_)) = activation {
+                        activated_clone.set(Some(id));
+                    }
+                }));
+
+                context.block_on(async {
+                    let flags = gio::DBusConnectionFlags::AUTHENTICATION_CLIENT
+                        | gio::DBusConnectionFlags::MESSAGE_BUS_CONNECTION;
+                    let service = gio::DBusConnection::for_address_future(&address, flags, None)
+                        .await
+                        .unwrap();
+                    let client = gio::DBusConnection::for_address_future(&address, flags, None)
+                        .await
+                        .unwrap();
+                    provider.register(&service).unwrap();
+
+                    let call = |method: &'static str, args: glib::Variant| {
+                        client.call_future(
+                            service.unique_name().as_deref(),
+                            OBJECT_PATH,
+                            "org.gnome.Shell.SearchProvider2",
+                            method,
+                            Some(&args),
+                            None,
+                            gio::DBusCallFlags::NONE,
+                            5000,
+                        )
+                    };
+
+                    let terms = vec!["witcher".to_string()];
+                    let (ids,) = call("GetInitialResultSet", (terms.clone(),).to_variant())
+                        .await
+                        .unwrap()
+                        .get::<(Vec<String>,)>()
+                        .unwrap();
+                    assert_eq!(ids, vec!["1", "2", "3", "4", "5"], "Capped at MAX_RESULTS");
+
+                    let (too_short,) = call("GetInitialResultSet", (vec!["wi".to_string()],).to_variant())
+                        .await
+                        .unwrap()
+                        .get::<(Vec<String>,)>()
+                        .unwrap();
+                    assert!(too_short.is_empty());
+
+                    let (metas,) = call("GetResultMetas", (vec!["2".to_string()],).to_variant())
+                        .await
+                        .unwrap()
+                        .get::<(Vec<HashMap<String, glib::Variant>>,)>()
+                        .unwrap();
+                    assert_eq!(metas.len(), 1);
+                    assert_eq!(metas[0]["id"].get::<String>().unwrap(), "2");
+                    assert_eq!(metas[0]["name"].get::<String>().unwrap(), "The Witcher 2");
+                    assert_eq!(
+                        metas[0]["description"].get::<String>().unwrap(),
+                        format!("Main Story {} · 2015", TimeFormatter::default().format(10.0))
+                    );
+
+                    call("ActivateResult", ("3".to_string(), terms, 0u32).to_variant())
+                        .await
+                        .unwrap();
+                    assert_eq!(activated.get(), Some(3));
+
+                    assert!(call("ActivateResult", ("oops".to_string(), Vec::<String>::new(), 0u32).to_variant())
+                        .await
+                        .is_err());
+
+                    service.close_future().await.ok();
+                    client.close_future().await.ok();
+                });
+
+                test_bus.down();
+            })
+            .expect("The context isn't owned by another thread");
+    }
+}
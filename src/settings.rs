@@ -0,0 +1,387 @@
+use gio::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::api::Game;
+use crate::i18n::gettext;
+use crate::time_format::{TimeFormat, TimeFormatter, DEFAULT_EVENING_HOURS};
+
+/// GSettings schema id, the same as the application id
+pub const SCHEMA_ID: &str = "com.github.justadreamerfl.HowLongToBeat";
+
+// Fallbacks for when the schema isn't installed; they match the schema defaults
+const DEFAULT_WINDOW_WIDTH: i32 = 800;
+const DEFAULT_WINDOW_HEIGHT: i32 = 600;
+const DEFAULT_SEARCH_DELAY_MS: u32 = 300;
+const DEFAULT_HISTORY_SIZE: u32 = 50;
+const DEFAULT_CACHE_MAX_SIZE_MB: u32 = 200;
+const DEFAULT_CACHE_MAX_AGE_DAYS: u32 = 30;
+
+/// Layout of the search results
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewMode {
+    List,
+    Grid,
+}
+
+impl ViewMode {
+    fn key(self) -> &'static str {
+        match self {
+            Self::List => "list",
+            Self::Grid => "grid",
+        }
+    }
+
+    fn from_key(key: &str) -> Self {
+        match key {
+            "grid" => Self::Grid,
+            _ => Self::List,
+        }
+    }
+}
+
+/// The completion time highlighted where there's only room for one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeCategory {
+    Main,
+    MainExtras,
+    Completionist,
+    AllStyles,
+}
+
+impl TimeCategory {
+    pub const ALL: [Self; 4] = [Self::Main, Self::MainExtras, Self::Completionist, Self::AllStyles];
+
+    fn key(self) -> &'static str {
+        match self {
+            Self::Main => "main",
+            Self::MainExtras => "main-extras",
+            Self::Completionist => "completionist",
+            Self::AllStyles => "all-styles",
+        }
+    }
+
+    fn from_key(key: &str) -> Self {
+        match key {
+            "main-extras" => Self::MainExtras,
+            "completionist" => Self::Completionist,
+            "all-styles" => Self::AllStyles,
+            _ => Self::Main,
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::Main => gettext("Main Story"),
+            Self::MainExtras => gettext("Main + Extras"),
+            Self::Completionist => gettext("Completionist"),
+            Self::AllStyles => gettext("All Styles"),
+        }
+    }
+
+    /// The game's time in this category, or `None` if nobody submitted one
+    pub fn hours(self, game: &Game) -> Option<f64> {
+        let (seconds, hours) = match self {
+            Self::Main => (game.comp_main, game.main_story_hours()),
+            Self::MainExtras => (game.comp_plus, game.main_plus_hours()),
+            Self::Completionist => (game.comp_100, game.completionist_hours()),
+            Self::AllStyles => (game.comp_all, game.all_styles_hours()),
+        };
+        (seconds > 0).then_some(hours)
+    }
+
+    /// How many players submitted a time in this category
+    pub fn count(self, game: &Game) -> u32 {
+        match self {
+            Self::Main => game.comp_main_count,
+            Self::MainExtras => game.comp_plus_count,
+            Self::Completionist => game.comp_100_count,
+            Self::AllStyles => game.count_comp,
+        }
+    }
+}
+
+type ChangedCallbacks = Rc<RefCell<Vec<(String, Rc<dyn Fn()>)>>>;
+
+/// Typed access to the app's GSettings.
+///
+/// Running from a build tree usually means the schema isn't installed. Settings then live in
+/// memory for the session instead of failing, so `cargo run` keeps working.
+#[derive(Clone)]
+pub struct Settings {
+    backend: Backend,
+}
+
+#[derive(Clone)]
+enum Backend {
+    GSettings(gio::Settings),
+    Memory {
+        values: Rc<RefCell<HashMap<String, glib::Variant>>>,
+        callbacks: ChangedCallbacks,
+    },
+}
+
+impl Settings {
+    /// Opens the installed schema, or falls back to in-memory settings
+    pub fn load() -> Self {
+        let schema = gio::SettingsSchemaSource::default().and_then(|source| source.lookup(SCHEMA_ID, true));
+        match schema {
+            Some(_) => Self {
+                backend: Backend::GSettings(gio::Settings::new(SCHEMA_ID)),
+            },
+            None => {
+                eprintln!("GSettings schema {} is not installed; preferences won't be saved", SCHEMA_ID);
+                Self::in_memory()
+            }
+        }
+    }
+
+    /// Settings that start at their defaults and are forgotten on exit
+    pub fn in_memory() -> Self {
+        Self {
+            backend: Backend::Memory {
+                values: Rc::default(),
+                callbacks: Rc::default(),
+            },
+        }
+    }
+
+    fn get<T: glib::variant::FromVariant>(&self, key: &str, default: T) -> T {
+        let value = match &self.backend {
+            Backend::GSettings(settings) => Some(settings.value(key)),
+            Backend::Memory { values, .. } => values.borrow().get(key).cloned(),
+        };
+        value.and_then(|value| value.get()).unwrap_or(default)
+    }
+
+    fn set(&self, key: &str, value: glib::Variant) {
+        match &self.backend {
+            Backend::GSettings(settings) => {
+                if let Err(e) = settings.set_value(key, &value) {
+                    eprintln!("Failed to save setting {}: {}", key, e);
+                }
+            }
+            Backend::Memory { values, callbacks } => {
+                values.borrow_mut().insert(key.to_string(), value);
+                // Clone the callbacks out so they can read the settings again
+                let matching: Vec<Rc<dyn Fn()>> = callbacks
+                    .borrow()
+                    .iter()
+                    .filter(|(watched, _)| watched == key)
+                    .map(|(_, callback)| callback.clone())
+                    .collect();
+                for callback in matching {
+                    callback();
+                }
+            }
+        }
+    }
+
+    /// Calls `callback` whenever `key` changes, from the preferences or from outside the app
+    pub fn connect_changed(&self, key: &str, callback: impl Fn() + 'static) {
+        match &self.backend {
+            Backend::GSettings(settings) => {
+                settings.connect_changed(Some(key), move |_, _| callback());
+            }
+            Backend::Memory { callbacks, .. } => {
+                callbacks.borrow_mut().push((key.to_string(), Rc::new(callback)));
+            }
+        }
+    }
+
+    pub fn view_mode(&self) -> ViewMode {
+        ViewMode::from_key(&self.get("view-mode", String::new()))
+    }
+
+    pub fn set_view_mode(&self, mode: ViewMode) {
+        self.set("view-mode", mode.key().to_variant());
+    }
+
+    /// The size of the window when it isn't maximized
+    pub fn window_size(&self) -> (i32, i32) {
+        (
+            self.get("window-width", DEFAULT_WINDOW_WIDTH),
+            self.get("window-height", DEFAULT_WINDOW_HEIGHT),
+        )
+    }
+
+    pub fn window_maximized(&self) -> bool {
+        self.get("window-maximized", false)
+    }
+
+    pub fn save_window_state(&self, width: i32, height: i32, maximized: bool) {
+        self.set("window-width", width.to_variant());
+        self.set("window-height", height.to_variant());
+        self.set("window-maximized", maximized.to_variant());
+    }
+
+    pub fn time_category(&self) -> TimeCategory {
+        TimeCategory::from_key(&self.get("time-category", String::new()))
+    }
+
+    pub fn set_time_category(&self, category: TimeCategory) {
+        self.set("time-category", category.key().to_variant());
+    }
+
+    pub fn time_format(&self) -> TimeFormat {
+        TimeFormat::from_key(&self.get("time-format", String::new()))
+    }
+
+    pub fn set_time_format(&self, format: TimeFormat) {
+        self.set("time-format", format.key().to_variant());
+    }
+
+    pub fn evening_length(&self) -> u32 {
+        self.get("evening-length", DEFAULT_EVENING_HOURS)
+    }
+
+    pub fn set_evening_length(&self, hours: u32) {
+        self.set("evening-length", hours.to_variant());
+    }
+
+    /// A formatter for the chosen time format
+    pub fn time_formatter(&self) -> TimeFormatter {
+        TimeFormatter::new(self.time_format(), self.evening_length())
+    }
+
+    /// How long to wait after the last keystroke before searching
+    pub fn search_delay(&self) -> Duration {
+        Duration::from_millis(self.get("search-delay", DEFAULT_SEARCH_DELAY_MS).into())
+    }
+
+    pub fn set_search_delay(&self, delay: Duration) {
+        self.set("search-delay", (delay.as_millis().min(u32::MAX as u128) as u32).to_variant());
+    }
+
+    /// How many searches and opened games the search history keeps, 0 for none
+    pub fn history_size(&self) -> u32 {
+        self.get("history-size", DEFAULT_HISTORY_SIZE)
+    }
+
+    pub fn set_history_size(&self, size: u32) {
+        self.set("history-size", size.to_variant());
+    }
+
+    pub fn cache_max_size_mb(&self) -> u32 {
+        self.get("cache-max-size", DEFAULT_CACHE_MAX_SIZE_MB)
+    }
+
+    pub fn set_cache_max_size_mb(&self, size: u32) {
+        self.set("cache-max-size", size.to_variant());
+    }
+
+    pub fn cache_max_age_days(&self) -> u32 {
+        self.get("cache-max-age", DEFAULT_CACHE_MAX_AGE_DAYS)
+    }
+
+    pub fn set_cache_max_age_days(&self, days: u32) {
+        self.set("cache-max-age", days.to_variant());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Compiles the schema from data/ and opens it on a memory backend
+    fn compiled_settings() -> Option<Settings> {
+        let dir = std::env::temp_dir().join(format!("hltb-schema-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let schema_file = format!("{}.gschema.xml", SCHEMA_ID);
+        std::fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("data").join(&schema_file),
+            dir.join(&schema_file),
+        )
+        .unwrap();
+
+        let compiled = Command::new("glib-compile-schemas").arg(&dir).status().ok()?.success();
+        assert!(compiled, "The schema in data/ should compile");
+
+        let source = gio::SettingsSchemaSource::from_directory(&dir, None, false).unwrap();
+        let schema = source.lookup(SCHEMA_ID, false).unwrap();
+        let backend = gio::memory_settings_backend_new();
+        let settings = gio::Settings::new_full(&schema, Some(&backend), None);
+        std::fs::remove_dir_all(&dir).ok();
+
+        Some(Settings {
+            backend: Backend::GSettings(settings),
+        })
+    }
+
+    fn assert_defaults(settings: &Settings) {
+        assert_eq!(settings.view_mode(), ViewMode::List);
+        assert_eq!(settings.window_size(), (800, 600));
+        assert!(!settings.window_maximized());
+        assert_eq!(settings.time_category(), TimeCategory::Main);
+        assert_eq!(settings.time_format(), TimeFormat::Decimal);
+        assert_eq!(settings.evening_length(), 3);
+        assert_eq!(settings.search_delay(), Duration::from_millis(300));
+        assert_eq!(settings.history_size(), 50);
+        assert_eq!(settings.cache_max_size_mb(), 200);
+        assert_eq!(settings.cache_max_age_days(), 30);
+    }
+
+    fn assert_round_trip(settings: &Settings) {
+        let changes = Rc::new(Cell::new(0));
+        let changes_clone = changes.clone();
+        settings.connect_changed("view-mode", move || changes_clone.set(changes_clone.get() + 1));
+
+        settings.set_view_mode(ViewMode::Grid);
+        settings.save_window_state(1280, 720, true);
+        settings.set_time_category(TimeCategory::Completionist);
+        settings.set_time_format(TimeFormat::Evenings);
+        settings.set_evening_length(2);
+        settings.set_search_delay(Duration::from_millis(150));
+        settings.set_history_size(10);
+        settings.set_cache_max_size_mb(50);
+        settings.set_cache_max_age_days(7);
+
+        assert_eq!(settings.view_mode(), ViewMode::Grid);
+        assert_eq!(settings.window_size(), (1280, 720));
+        assert!(settings.window_maximized());
+        assert_eq!(settings.time_category(), TimeCategory::Completionist);
+        assert_eq!(settings.time_format(), TimeFormat::Evenings);
+        assert_eq!(settings.time_formatter().format(3.0), "2 evenings of 2 hours");
+        assert_eq!(settings.search_delay(), Duration::from_millis(150));
+        assert_eq!(settings.history_size(), 10);
+        assert_eq!(settings.cache_max_size_mb(), 50);
+        assert_eq!(settings.cache_max_age_days(), 7);
+        assert_eq!(changes.get(), 1);
+    }
+
+    #[test]
+    fn test_in_memory_settings() {
+        let settings = Settings::in_memory();
+        assert_defaults(&settings);
+        assert_round_trip(&settings);
+    }
+
+    #[test]
+    fn test_schema_matches_fallbacks() {
+        let Some(settings) = compiled_settings() else {
+            eprintln!("Skipping schema test: glib-compile-schemas is not installed");
+            return;
+        };
+        assert_defaults(&settings);
+        assert_round_trip(&settings);
+    }
+
+    #[test]
+    fn test_time_category_hours() {
+        let game = Game {
+            comp_main: 36000,
+            comp_100: 0,
+            ..Default::default()
+        };
+        assert_eq!(TimeCategory::Main.hours(&game), Some(10.0));
+        assert_eq!(TimeCategory::Completionist.hours(&game), None);
+        for category in TimeCategory::ALL {
+            assert_eq!(TimeCategory::from_key(category.key()), category);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use crate::i18n::{gettext, gettext_f, ngettext_f};
+
+/// Default length of an evening for [`TimeFormat::Evenings`]
+pub const DEFAULT_EVENING_HOURS: u32 = 3;
+
+/// How completion times are written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeFormat {
+    /// `45m`, `12.5h`, `1,234h`
+    Decimal,
+    /// `12h30m`
+    HoursMinutes,
+    /// `2d 4h`
+    DaysHours,
+    /// `5 evenings of 3 hours`
+    Evenings,
+}
+
+impl TimeFormat {
+    pub const ALL: [Self; 4] = [Self::Decimal, Self::HoursMinutes, Self::DaysHours, Self::Evenings];
+
+    /// The value stored in GSettings
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Decimal => "decimal",
+            Self::HoursMinutes => "hours-minutes",
+            Self::DaysHours => "days-hours",
+            Self::Evenings => "evenings",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "hours-minutes" => Self::HoursMinutes,
+            "days-hours" => Self::DaysHours,
+            "evenings" => Self::Evenings,
+            _ => Self::Decimal,
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::Decimal => gettext("Decimal Hours"),
+            Self::HoursMinutes => gettext("Hours and Minutes"),
+            Self::DaysHours => gettext("Days and Hours"),
+            Self::Evenings => gettext("Evenings"),
+        }
+    }
+}
+
+/// Decimal and thousands separators of a locale
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Separators {
+    decimal: char,
+    group: char,
+}
+
+impl Separators {
+    const ENGLISH: Self = Self { decimal: '.', group: ',' };
+
+    /// Separators for the `LC_NUMERIC` locale, falling back to English ones
+    fn current() -> Self {
+        glib::language_names_with_category("LC_NUMERIC")
+            .first()
+            .map(|name| Self::for_locale(name))
+            .unwrap_or(Self::ENGLISH)
+    }
+
+    /// Separators for a locale name like `de_DE.UTF-8`
+    fn for_locale(name: &str) -> Self {
+        let language = name.split(['_', '.', '@']).next().unwrap_or_default();
+        match language {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el" | "ro" | "hr" | "sl" | "sr" => {
+                Self { decimal: ',', group: '.' }
+            }
+            // Narrow no-break space, as CLDR uses for French
+            "fr" => Self { decimal: ',', group: '\u{202f}' },
+            "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "nn" | "no" | "hu" | "bg" | "lt" | "lv"
+            | "et" => Self { decimal: ',', group: '\u{a0}' },
+            _ => Self::ENGLISH,
+        }
+    }
+}
+
+/// Writes completion times in the user's chosen format
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeFormatter {
+    format: TimeFormat,
+    evening_hours: u32,
+    separators: Separators,
+}
+
+impl Default for TimeFormatter {
+    fn default() -> Self {
+        Self::new(TimeFormat::Decimal, DEFAULT_EVENING_HOURS)
+    }
+}
+
+impl TimeFormatter {
+    pub fn new(format: TimeFormat, evening_hours: u32) -> Self {
+        Self {
+            format,
+            evening_hours: evening_hours.max(1),
+            separators: Separators::current(),
+        }
+    }
+
+    pub fn format(&self, hours: f64) -> String {
+        let minutes = (hours.max(0.0) * 60.0).round() as u64;
+        if minutes < 60 && self.format != TimeFormat::HoursMinutes {
+            // Translators: a short time in minutes, like "45m"
+            return gettext_f("{minutes}m", &[("minutes", &minutes.to_string())]);
+        }
+
+        // Translators: a time in hours, like "12.5h"
+        let in_hours = |hours: &str| gettext_f("{hours}h", &[("hours", hours)]);
+        match self.format {
+            TimeFormat::Decimal if hours < 100.0 => {
+                in_hours(&format!("{:.1}", hours).replace('.', &self.separators.decimal.to_string()))
+            }
+            TimeFormat::Decimal => in_hours(&self.group(hours.round() as u64)),
+            TimeFormat::HoursMinutes => gettext_f(
+                // Translators: a time in hours and minutes, like "12h30m"
+                "{hours}h{minutes}m",
+                &[("hours", &self.group(minutes / 60)), ("minutes", &format!("{:02}", minutes % 60))],
+            ),
+            TimeFormat::DaysHours => {
+                let total_hours = (minutes + 30) / 60;
+                let (days, hours) = (total_hours / 24, total_hours % 24);
+                match (days, hours) {
+                    (0, hours) => in_hours(&hours.to_string()),
+                    // Translators: a time in days, like "3d"
+                    (days, 0) => gettext_f("{days}d", &[("days", &self.group(days))]),
+                    (days, hours) => gettext_f(
+                        // Translators: a time in days and hours, like "2d 4h"
+                        "{days}d {hours}h",
+                        &[("days", &self.group(days)), ("hours", &hours.to_string())],
+                    ),
+                }
+            }
+            TimeFormat::Evenings => {
+                let evenings = (hours / self.evening_hours as f64).ceil() as u64;
+                let evening_length = ngettext_f(
+                    "{hours} hour",
+                    "{hours} hours",
+                    self.evening_hours as usize,
+                    &[("hours", &self.evening_hours.to_string())],
+                );
+                ngettext_f(
+                    "{count} evening of {length}",
+                    "{count} evenings of {length}",
+                    evenings as usize,
+                    &[("count", &self.group(evenings)), ("length", &evening_length)],
+                )
+            }
+        }
+    }
+
+    /// Writes a whole number with the locale's thousands separator
+    fn group(&self, value: u64) -> String {
+        let digits = value.to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                grouped.push(self.separators.group);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formatter(format: TimeFormat) -> TimeFormatter {
+        TimeFormatter {
+            format,
+            evening_hours: DEFAULT_EVENING_HOURS,
+            separators: Separators::ENGLISH,
+        }
+    }
+
+    #[test]
+    fn test_decimal() {
+        let decimal = formatter(TimeFormat::Decimal);
+        assert_eq!(decimal.format(0.0), "0m");
+        assert_eq!(decimal.format(0.75), "45m");
+        assert_eq!(decimal.format(12.5), "12.5h");
+        assert_eq!(decimal.format(99.94), "99.9h");
+        assert_eq!(decimal.format(186.2), "186h");
+        assert_eq!(decimal.format(1000.0), "1,000h");
+        assert_eq!(decimal.format(1234567.0), "1,234,567h");
+    }
+
+    #[test]
+    fn test_hours_minutes() {
+        let hours_minutes = formatter(TimeFormat::HoursMinutes);
+        assert_eq!(hours_minutes.format(0.0), "0h00m");
+        assert_eq!(hours_minutes.format(0.75), "0h45m");
+        assert_eq!(hours_minutes.format(12.5), "12h30m");
+        assert_eq!(hours_minutes.format(12.999), "13h00m");
+        assert_eq!(hours_minutes.format(1000.1), "1,000h06m");
+    }
+
+    #[test]
+    fn test_days_hours() {
+        let days_hours = formatter(TimeFormat::DaysHours);
+        assert_eq!(days_hours.format(0.0), "0m");
+        assert_eq!(days_hours.format(0.5), "30m");
+        assert_eq!(days_hours.format(5.4), "5h");
+        assert_eq!(days_hours.format(24.0), "1d");
+        assert_eq!(days_hours.format(52.0), "2d 4h");
+        assert_eq!(days_hours.format(1000.0), "41d 16h");
+        assert_eq!(days_hours.format(24000.0), "1,000d");
+    }
+
+    #[test]
+    fn test_evenings() {
+        let evenings = formatter(TimeFormat::Evenings);
+        assert_eq!(evenings.format(0.0), "0m");
+        assert_eq!(evenings.format(2.0), "1 evening of 3 hours");
+        assert_eq!(evenings.format(3.0), "1 evening of 3 hours");
+        assert_eq!(evenings.format(3.1), "2 evenings of 3 hours");
+        assert_eq!(evenings.format(3000.0), "1,000 evenings of 3 hours");
+
+        let short_evenings = TimeFormatter {
+            evening_hours: 1,
+            ..evenings
+        };
+        assert_eq!(short_evenings.format(1.5), "2 evenings of 1 hour");
+    }
+
+    #[test]
+    fn test_locale_separators() {
+        assert_eq!(Separators::for_locale("en_US.UTF-8"), Separators::ENGLISH);
+        assert_eq!(Separators::for_locale("C"), Separators::ENGLISH);
+
+        let german = TimeFormatter {
+            separators: Separators::for_locale("de_DE.UTF-8"),
+            ..formatter(TimeFormat::Decimal)
+        };
+        assert_eq!(german.format(12.5), "12,5h");
+        assert_eq!(german.format(1000.0), "1.000h");
+
+        let french = TimeFormatter {
+            separators: Separators::for_locale("fr_FR@euro"),
+            ..formatter(TimeFormat::HoursMinutes)
+        };
+        assert_eq!(french.format(1234.5), "1\u{202f}234h30m");
+    }
+
+    #[test]
+    fn test_format_keys() {
+        for format in TimeFormat::ALL {
+            assert_eq!(TimeFormat::from_key(format.key()), format);
+        }
+        assert_eq!(TimeFormat::from_key("unknown"), TimeFormat::Decimal);
+    }
+}
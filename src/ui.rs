@@ -2,10 +2,61 @@ use libadwaita as adw;
 use adw::prelude::*;
 use gtk::{gdk, gdk_pixbuf, gio, glib};
 use gtk::Orientation;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
 
-use crate::api::{Game, HltbClient};
+use crate::api::{FailureKind, Fetched, Game, HltbClient, PlayMode};
+use crate::chart;
+use crate::discover;
+use crate::i18n::{gettext, gettext_f, ngettext_f};
+use crate::import::{self, steam};
+use crate::library::{Library, StatusMerge};
+use crate::links::{self, Link};
+use crate::local_index::{self, LocalIndex};
+use crate::matcher;
+use crate::offline_cache::ResultCache;
+use crate::query::{self, ParsedQuery};
+use crate::randomizer::RollFilters;
+use crate::reliability::{self, Reliability};
+use crate::settings::{Settings, TimeCategory, ViewMode};
+use crate::time_format::TimeFormatter;
+
+mod batch_lookup;
+mod comparison;
+mod discovery;
+mod game_page;
+mod home_page;
+mod import_review;
+mod library_page;
+mod match_picker;
+mod offline_banner;
+mod preferences;
+mod result_controls;
+mod results;
+mod search_suggestions;
+mod surprise;
+
+use comparison::CompareSelection;
+use game_page::GamePage;
+use home_page::HomePage;
+use library_page::LibraryPage;
+use offline_banner::OfflineBanner;
+use results::ResultViews;
+use search_suggestions::SearchSuggestions;
+use surprise::Surprise;
+
+/// Tooltip of the search entry
+fn search_hint() -> String {
+    gettext("Filter with platform:ps4, year:2015..2020, main:<60h or sort:rating")
+}
+
+/// What the search field asks for
+enum Lookup {
+    /// A pasted link or id, resolved to a single game
+    Link(Link),
+    Query(Box<ParsedQuery>),
+}
 
 #[allow(dead_code)]
 pub struct AppWindow {
@@ -15,39 +66,73 @@ pub struct AppWindow {
     list_box: gtk::ListBox,
     flow_box: gtk::FlowBox,
     results_stack: gtk::Stack, // Stack to switch between list and grid
-    status_page: adw::StatusPage,
+    home_page: HomePage,
     stack: gtk::Stack,
     client: Arc<HltbClient>,
-    view_mode: Arc<Mutex<ViewMode>>,
-}
-
-#[derive(Clone, Copy, PartialEq)]
-enum ViewMode {
-    List,
-    Grid,
+    settings: Settings,
+    library: Arc<Mutex<Library>>,
+    library_page: LibraryPage,
+    game_page: GamePage,
+    library_button: gtk::ToggleButton,
+    compare_selection: CompareSelection,
+    result_views: ResultViews,
+    surprise: Surprise,
+    search_suggestions: SearchSuggestions,
+    offline_banner: OfflineBanner,
+    local_index: Rc<RefCell<LocalIndex>>,
+    // What "Surprise Me" rolls from: the filters of the results on screen
+    roll_filters: Rc<RefCell<RollFilters>>,
 }
 
 impl AppWindow {
-    pub fn new(app: &adw::Application) -> Self {
-        let client = Arc::new(HltbClient::new());
+    pub fn new(app: &adw::Application, settings: Settings) -> Self {
+        let client = Arc::new(HltbClient::new().with_cache(ResultCache::new(ResultCache::default_dir())));
+        let library = Library::load_or_recover(Library::default_path());
+        let library = Arc::new(Mutex::new(library));
 
-        // Create the main window
+        // Create the main window at its size from the last session
+        let (width, height) = settings.window_size();
         let window = adw::ApplicationWindow::builder()
             .application(app)
-            .default_width(800)
-            .default_height(600)
-            .title("HowLongToBeat")
+            .default_width(width)
+            .default_height(height)
+            .maximized(settings.window_maximized())
+            .title(gettext("HowLongToBeat"))
             .build();
 
+        let settings_clone = settings.clone();
+        window.connect_close_request(move |window| {
+            let (width, height) = window.default_size();
+            settings_clone.save_window_state(width, height, window.is_maximized());
+            glib::Propagation::Proceed
+        });
+
         // Create header bar
         let header_bar = adw::HeaderBar::new();
 
         // Create search entry
         let search_entry = gtk::SearchEntry::builder()
-            .placeholder_text("Search for a game...")
+            .placeholder_text(gettext("Search for a game…"))
+            .tooltip_text(search_hint())
             .hexpand(true)
             .build();
 
+        // Says what's wrong with a broken filter, right under the search entry
+        let parse_error_label = gtk::Label::builder()
+            .wrap(true)
+            .justify(gtk::Justification::Center)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(12)
+            .margin_end(12)
+            .accessible_role(gtk::AccessibleRole::Alert)
+            .css_classes(vec!["error"])
+            .build();
+        let parse_error_revealer = gtk::Revealer::builder()
+            .transition_type(gtk::RevealerTransitionType::SlideDown)
+            .child(&parse_error_label)
+            .build();
+
         // Make the search entry draggable (allow dragging window from search field)
         let drag_controller = gtk::GestureDrag::new();
         let window_weak = window.downgrade();
@@ -74,32 +159,52 @@ impl AppWindow {
 
         header_bar.set_title_widget(Some(&search_entry));
 
-        // Add preferences button to header bar
-        let preferences_button = gtk::MenuButton::builder()
+        // Add library toggle to header bar
+        let library_button = gtk::ToggleButton::builder()
+            .icon_name("applications-games-symbolic")
+            .tooltip_text(gettext("Library"))
+            .build();
+        header_bar.pack_start(&library_button);
+
+        let surprise_button = gtk::Button::builder()
+            .icon_name("media-playlist-shuffle-symbolic")
+            .tooltip_text(gettext("Surprise Me"))
+            .action_name("win.surprise")
+            .build();
+        header_bar.pack_start(&surprise_button);
+
+        // Add main menu button to header bar
+        let menu_button = gtk::MenuButton::builder()
             .icon_name("open-menu-symbolic")
-            .tooltip_text("Preferences")
+            .tooltip_text(gettext("Main Menu"))
             .build();
 
-        // Create preferences menu
+        // Create main menu
         let menu = gio::Menu::new();
-        let view_section = gio::Menu::new();
-        view_section.append(Some("List View"), Some("app.view-mode-list"));
-        view_section.append(Some("Grid View"), Some("app.view-mode-grid"));
-        menu.append_section(Some("View Mode"), &view_section);
+        let preferences_section = gio::Menu::new();
+        preferences_section.append(Some(&gettext("Preferences")), Some("win.preferences"));
+        menu.append_section(None, &preferences_section);
+        let import_section = gio::Menu::new();
+        import_section.append(Some(&gettext("Import Steam Library")), Some("win.import-steam"));
+        import_section.append(Some(&gettext("Import Lutris Library")), Some("win.import-lutris"));
+        import_section.append(Some(&gettext("Import Heroic Library")), Some("win.import-heroic"));
+        import_section.append(Some(&gettext("Import HowLongToBeat CSV…")), Some("win.import-hltb-csv"));
+        import_section.append(Some(&gettext("Export Library as CSV…")), Some("win.export-hltb-csv"));
+        menu.append_section(None, &import_section);
+        let tools_section = gio::Menu::new();
+        tools_section.append(Some(&gettext("What Can I Finish?…")), Some("win.discover"));
+        tools_section.append(Some(&gettext("Batch Lookup…")), Some("win.batch-lookup"));
+        menu.append_section(None, &tools_section);
+        let history_section = gio::Menu::new();
+        history_section.append(Some(&gettext("Clear Search History")), Some("win.clear-history"));
+        menu.append_section(None, &history_section);
         
-        preferences_button.set_menu_model(Some(&menu));
-        header_bar.pack_end(&preferences_button);
+        menu_button.set_menu_model(Some(&menu));
+        header_bar.pack_end(&menu_button);
 
         // Create main content area with stack
         let stack = gtk::Stack::new();
 
-        // Create status page (shown when no search is performed)
-        let status_page = adw::StatusPage::builder()
-            .icon_name("system-search-symbolic")
-            .title("Search for a game")
-            .description("Enter a game title to see completion times")
-            .build();
-
         // Create list box for search results (list view)
         let list_box = gtk::ListBox::builder()
             .selection_mode(gtk::SelectionMode::None)
@@ -140,19 +245,104 @@ impl AppWindow {
         let results_stack = gtk::Stack::new();
         results_stack.add_named(&list_scrolled, Some("list"));
         results_stack.add_named(&grid_scrolled, Some("grid"));
-        results_stack.set_visible_child_name("list");
+        results_stack.set_visible_child_name(match settings.view_mode() {
+            ViewMode::List => "list",
+            ViewMode::Grid => "grid",
+        });
+
+        // Create library page
+        let library_page = LibraryPage::new(settings.clone());
+        if let Ok(library) = library.lock() {
+            library_page.refresh(&library);
+        }
+
+        // Games seen before, searched while HowLongToBeat is
+        let local_index = build_local_index(&library);
+        let local_index_clone = local_index.clone();
+        library_page.connect_refreshed(move |library| {
+            local_index_clone
+                .borrow_mut()
+                .extend(library.entries().iter().map(|entry| entry.game.clone()));
+        });
+
+        // Create the page for games opened from the shell overview
+        let game_page = GamePage::new(settings.clone());
+
+        // Results checked for comparison, shown in a bar under the pages
+        let compare_selection = CompareSelection::new(&window, settings.clone());
+
+        // Toasts over the pages, for errors and confirmations
+        let toast_overlay = adw::ToastOverlay::new();
+
+        // Sorting and filtering of the loaded results, above whichever view shows them
+        let result_views = ResultViews::new(
+            list_box.clone(),
+            flow_box.clone(),
+            toast_overlay.clone(),
+            settings.clone(),
+            compare_selection.clone(),
+        );
+        let roll_filters: Rc<RefCell<RollFilters>> = Rc::default();
+        let game_page_clone = game_page.clone();
+        let stack_clone = stack.clone();
+        let library_button_clone = library_button.clone();
+        let surprise = Surprise::new(client.clone(), settings.clone(), compare_selection.clone(), move |game| {
+            library_button_clone.set_active(false);
+            game_page_clone.show_game(game);
+            stack_clone.set_visible_child_name("game");
+        });
+
+        // Start screen, shown when no search is performed
+        let game_page_clone = game_page.clone();
+        let stack_clone = stack.clone();
+        let home_page = HomePage::new(client.clone(), settings.clone(), move |game| {
+            game_page_clone.show_game(game);
+            stack_clone.set_visible_child_name("game");
+        });
+        // Earlier searches and games, suggested under the search entry
+        let game_page_clone = game_page.clone();
+        let stack_clone = stack.clone();
+        let library_button_clone = library_button.clone();
+        let search_suggestions = SearchSuggestions::new(&search_entry, settings.clone(), move |game| {
+            library_button_clone.set_active(false);
+            game_page_clone.show_game(game);
+            stack_clone.set_visible_child_name("game");
+        });
+
+        // Whichever way a game gets opened, it's one the user viewed
+        let home_page_clone = home_page.clone();
+        let search_suggestions_clone = search_suggestions.clone();
+        let local_index_clone = local_index.clone();
+        game_page.connect_shown(move |game| {
+            home_page_clone.record_viewed(game);
+            search_suggestions_clone.record_game(game);
+            local_index_clone.borrow_mut().insert(game.clone());
+        });
+
+        let results_box = gtk::Box::new(Orientation::Vertical, 0);
+        results_box.append(&result_views.controls.bar);
+        results_box.append(&results_stack);
 
         // Add pages to main stack
-        stack.add_named(&status_page, Some("empty"));
-        stack.add_named(&results_stack, Some("results"));
+        stack.add_named(&home_page.page, Some("home"));
+        stack.add_named(&results_box, Some("results"));
+        stack.add_named(&library_page.stack, Some("library"));
+        stack.add_named(&game_page.page, Some("game"));
 
         // Set initial page
-        stack.set_visible_child_name("empty");
+        stack.set_visible_child_name("home");
 
         // Create main box
+        // Says when the results on screen come from the offline cache
+        let offline_banner = OfflineBanner::new();
+
         let main_box = gtk::Box::new(Orientation::Vertical, 0);
         main_box.append(&header_bar);
-        main_box.append(&stack);
+        main_box.append(&parse_error_revealer);
+        main_box.append(&offline_banner.banner);
+        toast_overlay.set_child(Some(&stack));
+        main_box.append(&toast_overlay);
+        main_box.append(&compare_selection.bar);
 
         window.set_content(Some(&main_box));
 
@@ -163,36 +353,124 @@ impl AppWindow {
             list_box: list_box.clone(),
             flow_box: flow_box.clone(),
             results_stack: results_stack.clone(),
-            status_page,
+            home_page,
             stack: stack.clone(),
             client,
-            view_mode: Arc::new(Mutex::new(ViewMode::List)),
+            settings: settings.clone(),
+            library: library.clone(),
+            library_page: library_page.clone(),
+            game_page: game_page.clone(),
+            library_button: library_button.clone(),
+            compare_selection: compare_selection.clone(),
+            result_views: result_views.clone(),
+            surprise: surprise.clone(),
+            search_suggestions: search_suggestions.clone(),
+            offline_banner: offline_banner.clone(),
+            local_index: local_index.clone(),
+            roll_filters: roll_filters.clone(),
         };
 
+        // Leave the game page for whatever the search entry shows
+        let stack_clone = stack.clone();
+        let search_entry_clone = search_entry.clone();
+        game_page.back_button.connect_clicked(move |_| {
+            if search_entry_clone.text().is_empty() {
+                stack_clone.set_visible_child_name("home");
+            } else {
+                stack_clone.set_visible_child_name("results");
+            }
+        });
+
+        // Switch between the library and the search results
+        let stack_clone = stack.clone();
+        let search_entry_clone = search_entry.clone();
+        library_button.connect_toggled(move |button| {
+            if button.is_active() {
+                stack_clone.set_visible_child_name("library");
+            } else if search_entry_clone.text().is_empty() {
+                stack_clone.set_visible_child_name("home");
+            } else {
+                stack_clone.set_visible_child_name("results");
+            }
+        });
+
         // Connect search entry signal with debouncing
         let client_clone = app_window.client.clone();
-        let list_box_clone = list_box.clone();
-        let flow_box_clone = flow_box.clone();
+        let result_views_clone = result_views.clone();
         let stack_clone = stack.clone();
         let search_timeout: Arc<Mutex<Option<glib::SourceId>>> = Arc::new(Mutex::new(None));
 
+        let library_button_clone = library_button.clone();
+        let settings_clone = settings.clone();
+        let roll_filters_clone = roll_filters.clone();
+        let search_suggestions_clone = search_suggestions.clone();
+        let offline_banner_clone = offline_banner.clone();
+        let local_index_clone = local_index.clone();
+        let parse_error_label_clone = parse_error_label.clone();
+        let parse_error_revealer_clone = parse_error_revealer.clone();
+
         search_entry.connect_search_changed(move |entry| {
+            // Typing a new search leaves the library page
+            library_button_clone.set_active(false);
+
             let query = entry.text().to_string();
-            if query.is_empty() {
+            // Only suggest while typing, not for searches started from outside the window
+            if entry.state_flags().contains(gtk::StateFlags::FOCUS_WITHIN) && !query.is_empty() {
+                search_suggestions_clone.update(&query);
+            } else {
+                search_suggestions_clone.hide();
+            }
+            // Pasted links and ids are looked up directly instead of searched for
+            let parsed = match links::parse_pasted(&query) {
+                Some(link) => Ok(Some(Lookup::Link(link))),
+                None => query::parse(&query).map(|parsed| {
+                    Some(parsed)
+                        .filter(|parsed| !parsed.terms.is_empty() || !parsed.filters.is_empty())
+                        .map(|parsed| Lookup::Query(Box::new(parsed)))
+                }),
+            };
+            let lookup = match parsed {
+                Ok(lookup) => {
+                    entry.remove_css_class("error");
+                    parse_error_revealer_clone.set_reveal_child(false);
+                    lookup
+                }
+                Err(e) => {
+                    // Point at the broken filter instead of searching for it
+                    entry.add_css_class("error");
+                    parse_error_label_clone.set_label(&e.to_string());
+                    parse_error_revealer_clone.set_reveal_child(true);
+                    None
+                }
+            };
+            *roll_filters_clone.borrow_mut() = match &lookup {
+                Some(Lookup::Query(parsed)) => RollFilters::from_query(parsed, &query),
+                Some(Lookup::Link(_)) => RollFilters::default(),
+                // A broken filter keeps the results, and the filters, from before
+                None if !query.is_empty() => roll_filters_clone.borrow().clone(),
+                None => RollFilters::default(),
+            };
+            let Some(lookup) = lookup else {
                 // Cancel pending search
                 if let Ok(mut timeout) = search_timeout.lock() {
                     if let Some(id) = timeout.take() {
                         id.remove();
                     }
                 }
-                stack_clone.set_visible_child_name("empty");
+                if query.is_empty() {
+                    offline_banner_clone.hide();
+                    stack_clone.set_visible_child_name("home");
+                }
                 return;
-            }
+            };
 
             let client = client_clone.clone();
-            let list_box = list_box_clone.clone();
-            let flow_box = flow_box_clone.clone();
+            let local_index = local_index_clone.clone();
+            let result_views = result_views_clone.clone();
             let stack = stack_clone.clone();
+            let search_entry = entry.clone();
+            let search_suggestions = search_suggestions_clone.clone();
+            let offline_banner = offline_banner_clone.clone();
             let search_timeout_clone = search_timeout.clone();
 
             // Cancel previous search timeout
@@ -201,95 +479,97 @@ impl AppWindow {
                     id.remove();
                 }
 
-                // Add new debounced search with 300ms delay
-                let new_id = glib::timeout_add_local_once(Duration::from_millis(300), move || {
+                // Add new debounced search after the configured delay
+                let new_id = glib::timeout_add_local_once(settings_clone.search_delay(), move || {
                     // Clear the timeout reference
                     if let Ok(mut timeout) = search_timeout_clone.lock() {
                         *timeout = None;
                     }
 
+                    let request = result_views.start_request();
+
+                    // Show what's already known while HowLongToBeat is searched; filters need
+                    // its data to apply
+                    let local_hits = match &lookup {
+                        Lookup::Query(parsed) if parsed.filters.is_empty() => {
+                            local_index.borrow().search(&query, MAX_LOCAL_HITS)
+                        }
+                        _ => Vec::new(),
+                    };
+                    if !local_hits.is_empty() {
+                        result_views.show_local_hits(local_hits.clone());
+                        stack.set_visible_child_name("results");
+                    }
+
                     // Spawn async search
                     glib::spawn_future_local(async move {
-                // Clear previous results
-                while let Some(child) = list_box.first_child() {
-                    list_box.remove(&child);
-                }
-                while let Some(child) = flow_box.first_child() {
-                    flow_box.remove(&child);
+                // Replace previous results with the loading state, unless local hits stand in
+                if local_hits.is_empty() {
+                    result_views.show_loading();
                 }
-
-                // Show loading state
-                let loading_row = adw::ActionRow::builder().title("Searching...").build();
-                let spinner = gtk::Spinner::new();
-                spinner.start();
-                loading_row.add_suffix(&spinner);
-                list_box.append(&loading_row);
                 stack.set_visible_child_name("results");
 
                 // Perform search
                 eprintln!("Searching for: {}", query);
-                match client.search(&query).await {
-                    Ok(games) => {
+                let result = match &lookup {
+                    Lookup::Link(link) => resolve_link(&client, link, &query)
+                        .await
+                        .map(|games| Fetched { value: games, cached_at: None }),
+                    Lookup::Query(parsed) => {
+                        client
+                            .search_or_cached(&parsed.text(), parsed.options.clone(), offline_banner.online())
+                            .await
+                    }
+                };
+                match result {
+                    Ok(Fetched { value: games, cached_at }) => {
                         eprintln!("Search successful, found {} games", games.len());
-                        // Clear loading indicator
-                        while let Some(child) = list_box.first_child() {
-                            list_box.remove(&child);
-                        }
-                        while let Some(child) = flow_box.first_child() {
-                            flow_box.remove(&child);
+                        local_index.borrow_mut().extend(games.iter().cloned());
+
+                        // HLTB matches literally, so look for looser queries that do find something
+                        let suggestions = match &lookup {
+                            Lookup::Query(parsed) if games.is_empty() && local_hits.is_empty() && cached_at.is_none() => {
+                                find_suggestions(&client, parsed).await
+                            }
+                            _ => Vec::new(),
+                        };
+                        if search_entry.text() != query || !result_views.is_current(request) {
+                            // A newer search or discovery has taken over the results
+                            return;
                         }
+                        let search_entry_clone = search_entry.clone();
+                        offline_banner.update(cached_at, move || {
+                            search_entry_clone.emit_by_name::<()>("search-changed", &[]);
+                        });
 
+                        let games = local_index::merge(local_hits, games);
                         if games.is_empty() {
-                            let no_results = adw::ActionRow::builder()
-                                .title("No results found")
-                                .subtitle(format!("No games found for '{}'", query))
-                                .build();
-                            list_box.append(&no_results);
+                            result_views.clear();
+                            show_no_results(
+                                &result_views.list_box,
+                                &result_views.flow_box,
+                                &search_entry,
+                                &query,
+                                &suggestions,
+                            );
                         } else {
-                            for game in &games {
-                                // Add to list view
-                                let row = create_game_row(game);
-                                list_box.append(&row);
-                                
-                                // Add to grid view
-                                let card = create_game_card(game);
-                                flow_box.insert(&card, -1);
-                            }
+                            search_suggestions.remember_results(&query, &games);
+                            result_views.show_games(games);
                         }
                     }
                     Err(e) => {
-                        eprintln!("Search failed: {}", e);
-                        // Clear loading indicator from both views
-                        while let Some(child) = list_box.first_child() {
-                            list_box.remove(&child);
+                        eprintln!("Search failed: {:#}", e);
+                        if search_entry.text() != query || !result_views.is_current(request) {
+                            return;
                         }
-                        while let Some(child) = flow_box.first_child() {
-                            flow_box.remove(&child);
+                        let search_entry_clone = search_entry.clone();
+                        let retry = move || search_entry_clone.emit_by_name::<()>("search-changed", &[]);
+                        if FailureKind::of(&e) == FailureKind::Offline {
+                            offline_banner.show_unreachable(retry.clone());
+                        } else {
+                            offline_banner.hide();
                         }
-
-                        // Create error row with prominent error message
-                        let error_row = adw::ActionRow::builder()
-                            .title("Search Failed")
-                            .build();
-                        
-                        // Create a label for the full error message
-                        let error_label = gtk::Label::builder()
-                            .label(format!("{}", e))
-                            .wrap(true)
-                            .wrap_mode(gtk::pango::WrapMode::Word)
-                            .xalign(0.0)
-                            .margin_top(8)
-                            .margin_bottom(8)
-                            .margin_start(12)
-                            .margin_end(12)
-                            .css_classes(vec!["dim-label"])
-                            .build();
-                        
-                        let error_box = gtk::Box::new(Orientation::Vertical, 4);
-                        error_box.append(&error_row);
-                        error_box.append(&error_label);
-                        
-                        list_box.append(&error_box);
+                        result_views.show_error(&e, retry);
                     }
                 }
                     });
@@ -299,30 +579,189 @@ impl AppWindow {
             }
         });
 
-        // Add view mode actions
-        let view_mode_clone = app_window.view_mode.clone();
+        // Enter submits the search, or picks the highlighted suggestion
+        let search_suggestions_clone = search_suggestions.clone();
+        search_entry.connect_activate(move |_| {
+            search_suggestions_clone.activate();
+        });
+
+        // Follow the layout chosen in the preferences
         let results_stack_clone = results_stack.clone();
-        
-        let list_action = gio::SimpleAction::new("view-mode-list", None);
-        list_action.connect_activate(move |_, _| {
-            if let Ok(mut mode) = view_mode_clone.lock() {
-                *mode = ViewMode::List;
-                results_stack_clone.set_visible_child_name("list");
-            }
+        let settings_clone = settings.clone();
+        settings.connect_changed("view-mode", move || {
+            results_stack_clone.set_visible_child_name(match settings_clone.view_mode() {
+                ViewMode::List => "list",
+                ViewMode::Grid => "grid",
+            });
         });
-        window.add_action(&list_action);
 
-        let view_mode_clone2 = app_window.view_mode.clone();
-        let results_stack_clone2 = results_stack.clone();
-        
-        let grid_action = gio::SimpleAction::new("view-mode-grid", None);
-        grid_action.connect_activate(move |_, _| {
-            if let Ok(mut mode) = view_mode_clone2.lock() {
-                *mode = ViewMode::Grid;
-                results_stack_clone2.set_visible_child_name("grid");
-            }
+        // Rewrite the times already on screen in the new format
+        for key in ["time-format", "evening-length", "time-category"] {
+            let library_clone = library.clone();
+            let library_page_clone = library_page.clone();
+            let result_views_clone = result_views.clone();
+            let game_page_clone = game_page.clone();
+            settings.connect_changed(key, move || {
+                if let Ok(library) = library_clone.lock() {
+                    library_page_clone.refresh(&library);
+                }
+                result_views_clone.refresh();
+                game_page_clone.refresh();
+            });
+        }
+
+        let window_clone = window.clone();
+        let settings_clone = settings.clone();
+
+        let preferences_action = gio::SimpleAction::new("preferences", None);
+        preferences_action.connect_activate(move |_, _| {
+            preferences::present_preferences(&window_clone, &settings_clone);
+        });
+        window.add_action(&preferences_action);
+        app.set_accels_for_action("win.preferences", &["<Control>comma"]);
+
+        // Add import actions
+        let window_clone = window.clone();
+        let client_clone = app_window.client.clone();
+        let library_clone = library.clone();
+        let library_page_clone = library_page.clone();
+
+        let import_steam_action = gio::SimpleAction::new("import-steam", None);
+        import_steam_action.connect_activate(move |_, _| {
+            import_steam_library(
+                &window_clone,
+                client_clone.clone(),
+                library_clone.clone(),
+                library_page_clone.clone(),
+            );
+        });
+        window.add_action(&import_steam_action);
+
+        for (action_name, launcher) in [
+            ("import-lutris", Launcher::Lutris),
+            ("import-heroic", Launcher::Heroic),
+        ] {
+            let window_clone = window.clone();
+            let client_clone = app_window.client.clone();
+            let library_clone = library.clone();
+            let library_page_clone = library_page.clone();
+            let settings_clone = settings.clone();
+
+            let action = gio::SimpleAction::new(action_name, None);
+            action.connect_activate(move |_, _| {
+                import_launcher_library(
+                    &window_clone,
+                    launcher,
+                    client_clone.clone(),
+                    library_clone.clone(),
+                    library_page_clone.clone(),
+                    settings_clone.time_formatter(),
+                );
+            });
+            window.add_action(&action);
+        }
+
+        let window_clone = window.clone();
+        let client_clone = app_window.client.clone();
+        let library_clone = library.clone();
+        let library_page_clone = library_page.clone();
+        let settings_clone = settings.clone();
+
+        let import_csv_action = gio::SimpleAction::new("import-hltb-csv", None);
+        import_csv_action.connect_activate(move |_, _| {
+            import_hltb_csv(
+                &window_clone,
+                client_clone.clone(),
+                library_clone.clone(),
+                library_page_clone.clone(),
+                settings_clone.time_formatter(),
+            );
         });
-        window.add_action(&grid_action);
+        window.add_action(&import_csv_action);
+
+        let window_clone = window.clone();
+        let library_clone = library.clone();
+
+        let export_csv_action = gio::SimpleAction::new("export-hltb-csv", None);
+        export_csv_action.connect_activate(move |_, _| {
+            export_hltb_csv(&window_clone, library_clone.clone());
+        });
+        window.add_action(&export_csv_action);
+
+        let window_clone = window.clone();
+        let client_clone = app_window.client.clone();
+        let library_clone = library.clone();
+        let library_page_clone = library_page.clone();
+        let settings_clone = settings.clone();
+
+        let batch_lookup_action = gio::SimpleAction::new("batch-lookup", None);
+        batch_lookup_action.connect_activate(move |_, _| {
+            batch_lookup::present_batch_dialog(
+                &window_clone,
+                client_clone.clone(),
+                library_clone.clone(),
+                library_page_clone.clone(),
+                settings_clone.time_formatter(),
+            );
+        });
+        window.add_action(&batch_lookup_action);
+
+        // Suggest games that fit in an hour budget, shown in place of the search results
+        let window_clone = window.clone();
+        let client_clone = app_window.client.clone();
+        let settings_clone = settings.clone();
+        let result_views_clone = result_views.clone();
+        let stack_clone = stack.clone();
+        let library_button_clone = library_button.clone();
+        let roll_filters_clone = roll_filters.clone();
+
+        let discover_action = gio::SimpleAction::new("discover", None);
+        discover_action.connect_activate(move |_, _| {
+            let client = client_clone.clone();
+            let result_views = result_views_clone.clone();
+            let stack = stack_clone.clone();
+            let library_button = library_button_clone.clone();
+            let roll_filters = roll_filters_clone.clone();
+            let time_formatter = settings_clone.time_formatter();
+            discovery::present_discovery(&window_clone, &settings_clone, move |budget| {
+                library_button.set_active(false);
+                *roll_filters.borrow_mut() = RollFilters {
+                    terms: String::new(),
+                    options: discover::search_options(&budget),
+                    description: discover::describe(&budget, &time_formatter),
+                };
+                stack.set_visible_child_name("results");
+                discover_games(client.clone(), result_views.clone(), budget);
+            });
+        });
+        window.add_action(&discover_action);
+        app.set_accels_for_action("win.discover", &["<Control>d"]);
+
+        // Roll a random game from the filters on screen, and roll again with the same ones
+        let window_clone = window.clone();
+        let surprise_clone = surprise.clone();
+        let roll_filters_clone = roll_filters.clone();
+        let surprise_action = gio::SimpleAction::new("surprise", None);
+        surprise_action.connect_activate(move |_, _| {
+            surprise_clone.roll(&window_clone, roll_filters_clone.borrow().clone());
+        });
+        window.add_action(&surprise_action);
+
+        let window_clone = window.clone();
+        let surprise_clone = surprise.clone();
+        let reroll_action = gio::SimpleAction::new("reroll", None);
+        reroll_action.connect_activate(move |_, _| {
+            surprise_clone.reroll(&window_clone);
+        });
+        window.add_action(&reroll_action);
+        app.set_accels_for_action("win.reroll", &["<Control>r"]);
+
+        let search_suggestions_clone = search_suggestions.clone();
+        let clear_history_action = gio::SimpleAction::new("clear-history", None);
+        clear_history_action.connect_activate(move |_, _| {
+            search_suggestions_clone.clear();
+        });
+        window.add_action(&clear_history_action);
 
         app_window
     }
@@ -330,6 +769,614 @@ impl AppWindow {
     pub fn present(&self) {
         self.window.present();
     }
+
+    /// Switches to the library page
+    pub fn show_library(&self) {
+        self.library_button.set_active(true);
+    }
+
+    /// Runs a search as if it had been typed into the search entry
+    pub fn search(&self, query: &str) {
+        self.library_button.set_active(false);
+        if self.search_entry.text() == query && !query.is_empty() {
+            // Setting the same text doesn't search again, so just go back to its results
+            self.stack.set_visible_child_name("results");
+        }
+        self.search_entry.set_text(query);
+        self.search_entry.set_position(-1);
+        self.search_suggestions.record_query(query);
+    }
+
+    /// Rolls a random game matching `query`, or the filters on screen if it's empty
+    pub fn surprise(&self, query: &str) {
+        let filters = if query.trim().is_empty() {
+            self.roll_filters.borrow().clone()
+        } else {
+            match query::parse(query) {
+                Ok(parsed) => RollFilters::from_query(&parsed, query),
+                Err(e) => {
+                    show_message(&self.window, &gettext("Invalid Filter"), &e.to_string());
+                    return;
+                }
+            }
+        };
+        self.surprise.roll(&self.window, filters);
+    }
+
+    /// Shows a single game on its own page
+    pub fn show_game(&self, game: &Game) {
+        self.library_button.set_active(false);
+        self.game_page.show_game(game);
+        self.stack.set_visible_child_name("game");
+    }
+
+    /// Fetches a game by its HowLongToBeat id and shows it on its own page
+    pub fn show_game_id(&self, game_id: u64) {
+        self.library_button.set_active(false);
+        self.stack.set_visible_child_name("game");
+        load_game_page(self.client.clone(), self.game_page.clone(), self.offline_banner.clone(), game_id);
+    }
+}
+
+/// Fills the results with the games that fit `budget`
+fn discover_games(client: Arc<HltbClient>, result_views: ResultViews, budget: discover::Budget) {
+    let request = result_views.start_request();
+    result_views.show_loading();
+    glib::spawn_future_local(async move {
+        eprintln!("Discovering games for {:?}", budget);
+        let result = client.search_with_options("", discover::search_options(&budget)).await;
+        if !result_views.is_current(request) {
+            // A newer search or discovery has taken over the results
+            return;
+        }
+        match result {
+            Ok(games) => {
+                let ranked = discover::rank(games, &budget);
+                if ranked.is_empty() {
+                    result_views.show_empty(
+                        &gettext("Nothing Fits"),
+                        &gettext("Try more hours, or any platform or genre"),
+                    );
+                } else {
+                    result_views.show_games(ranked);
+                }
+            }
+            Err(e) => {
+                eprintln!("Discovery failed: {:#}", e);
+                let (client, result_views_clone) = (client.clone(), result_views.clone());
+                result_views.show_error(&e, move || {
+                    discover_games(client.clone(), result_views_clone.clone(), budget.clone());
+                });
+            }
+        }
+    });
+}
+
+/// Fills the game page with a game fetched by id, or the copy in the offline cache
+fn load_game_page(client: Arc<HltbClient>, game_page: GamePage, offline_banner: OfflineBanner, game_id: u64) {
+    game_page.show_loading();
+    glib::spawn_future_local(async move {
+        match client.game_by_id_or_cached(game_id, offline_banner.online()).await {
+            Ok(Fetched { value: game, cached_at }) => {
+                game_page.show_game(&game);
+                let (client, game_page, offline_banner_clone) =
+                    (client.clone(), game_page.clone(), offline_banner.clone());
+                offline_banner.update(cached_at, move || {
+                    load_game_page(client.clone(), game_page.clone(), offline_banner_clone.clone(), game_id);
+                });
+            }
+            Err(e) => {
+                eprintln!("Failed to load game {}: {:#}", game_id, e);
+                if FailureKind::of(&e) == FailureKind::Offline {
+                    let (client, game_page) = (client.clone(), game_page.clone());
+                    let offline_banner_clone = offline_banner.clone();
+                    offline_banner.show_unreachable(move || {
+                        load_game_page(client.clone(), game_page.clone(), offline_banner_clone.clone(), game_id);
+                    });
+                } else {
+                    offline_banner.hide();
+                }
+                game_page.show_error(&e.to_string());
+            }
+        }
+    });
+}
+
+fn import_steam_library(
+    window: &adw::ApplicationWindow,
+    client: Arc<HltbClient>,
+    library: Arc<Mutex<Library>>,
+    library_page: LibraryPage,
+) {
+    let games = match steam::find_steam_root() {
+        Some(steam_root) => steam::scan_library(&steam_root),
+        None => Err(anyhow::anyhow!(gettext("No Steam installation was found in your home folder."))),
+    };
+    let games = match games {
+        Ok(games) if !games.is_empty() => games,
+        Ok(_) => {
+            show_message(
+                window,
+                &gettext("Nothing to Import"),
+                &gettext("Your Steam library doesn't contain any games."),
+            );
+            return;
+        }
+        Err(e) => {
+            show_message(window, &gettext("Steam Import Failed"), &e.to_string());
+            return;
+        }
+    };
+
+    let (dialog, progress_bar) = present_progress_dialog(
+        window,
+        &gettext("Importing Steam Library"),
+        &looking_up_message(games.len()),
+    );
+
+    glib::spawn_future_local(async move {
+        let summary = import::import_steam_games(&client, &games, move |done, total| {
+            progress_bar.set_fraction(done as f64 / total as f64);
+            progress_bar.set_text(Some(&format!("{} / {}", done, total)));
+        })
+        .await;
+
+        let unmatched = summary.unmatched.len();
+        let failed = summary.failed.len();
+        let failure_kind = summary.failed.first().map(|(_, e)| FailureKind::of(e));
+        let added = match library.lock() {
+            Ok(mut library) => {
+                let added = library.add_all(summary.entries, StatusMerge::Keep);
+                if let Err(e) = library.save() {
+                    eprintln!("Failed to save library: {}", e);
+                }
+                library_page.refresh(&library);
+                added
+            }
+            Err(_) => 0,
+        };
+
+        let mut body = ngettext_f(
+            "Added {count} new game to your library.",
+            "Added {count} new games to your library.",
+            added,
+            &[("count", &added.to_string())],
+        );
+        if unmatched > 0 {
+            body.push(' ');
+            body.push_str(&ngettext_f(
+                "{count} game couldn't be found on HowLongToBeat.",
+                "{count} games couldn't be found on HowLongToBeat.",
+                unmatched,
+                &[("count", &unmatched.to_string())],
+            ));
+        }
+        if let Some(failure_kind) = failure_kind {
+            let failed_count = failed.to_string();
+            let count = [("count", failed_count.as_str())];
+            body.push(' ');
+            body.push_str(&match failure_kind {
+                FailureKind::Offline => ngettext_f(
+                    "{count} game couldn't be looked up because HowLongToBeat couldn't be reached.",
+                    "{count} games couldn't be looked up because HowLongToBeat couldn't be reached.",
+                    failed,
+                    &count,
+                ),
+                FailureKind::RateLimited => ngettext_f(
+                    "{count} game couldn't be looked up because HowLongToBeat asked to slow down.",
+                    "{count} games couldn't be looked up because HowLongToBeat asked to slow down.",
+                    failed,
+                    &count,
+                ),
+                _ => ngettext_f(
+                    "{count} game couldn't be looked up because of an error.",
+                    "{count} games couldn't be looked up because of an error.",
+                    failed,
+                    &count,
+                ),
+            });
+            body.push(' ');
+            body.push_str(&gettext("Import again to retry them."));
+        }
+
+        dialog.set_heading(Some(&gettext("Import Complete")));
+        dialog.set_body(&body);
+        dialog.set_extra_child(None::<&gtk::Widget>);
+        dialog.set_response_enabled("close", true);
+        dialog.set_can_close(true);
+    });
+}
+
+#[derive(Clone, Copy)]
+enum Launcher {
+    Lutris,
+    Heroic,
+}
+
+impl Launcher {
+    fn name(&self) -> &'static str {
+        match self {
+            Launcher::Lutris => "Lutris",
+            Launcher::Heroic => "Heroic",
+        }
+    }
+
+    fn read_games(&self) -> anyhow::Result<Vec<import::ImportedGame>> {
+        match self {
+            Launcher::Lutris => match import::lutris::find_database() {
+                Some(db_path) => import::lutris::read_games(&db_path),
+                None => Err(anyhow::anyhow!(gettext("No Lutris game database was found."))),
+            },
+            Launcher::Heroic => match import::heroic::find_config_dir() {
+                Some(config_dir) => import::heroic::read_games(&config_dir),
+                None => Err(anyhow::anyhow!(gettext("No Heroic Games Launcher configuration was found."))),
+            },
+        }
+    }
+}
+
+fn import_launcher_library(
+    window: &adw::ApplicationWindow,
+    launcher: Launcher,
+    client: Arc<HltbClient>,
+    library: Arc<Mutex<Library>>,
+    library_page: LibraryPage,
+    time_formatter: TimeFormatter,
+) {
+    let games = match launcher.read_games() {
+        Ok(games) if !games.is_empty() => games,
+        Ok(_) => {
+            show_message(
+                window,
+                &gettext("Nothing to Import"),
+                &gettext_f(
+                    "Your {launcher} library doesn't contain any games.",
+                    &[("launcher", launcher.name())],
+                ),
+            );
+            return;
+        }
+        Err(e) => {
+            show_message(
+                window,
+                &gettext_f("{launcher} Import Failed", &[("launcher", launcher.name())]),
+                &e.to_string(),
+            );
+            return;
+        }
+    };
+
+    stage_and_review_import(
+        window,
+        launcher.name(),
+        games,
+        StatusMerge::Keep,
+        client,
+        library,
+        library_page,
+        time_formatter,
+    );
+}
+
+/// Looks up imported games on HLTB, then lets the user review the matches before adding them.
+/// `status_merge` says whether games already in the library take the imported status.
+#[allow(clippy::too_many_arguments)]
+fn stage_and_review_import(
+    window: &adw::ApplicationWindow,
+    source_name: &str,
+    games: Vec<import::ImportedGame>,
+    status_merge: StatusMerge,
+    client: Arc<HltbClient>,
+    library: Arc<Mutex<Library>>,
+    library_page: LibraryPage,
+    time_formatter: TimeFormatter,
+) {
+    let (dialog, progress_bar) = present_progress_dialog(
+        window,
+        &gettext_f("Importing {source} Library", &[("source", source_name)]),
+        &looking_up_message(games.len()),
+    );
+
+    let window = window.clone();
+    let review_title = gettext_f("Review {source} Matches", &[("source", source_name)]);
+    glib::spawn_future_local(async move {
+        let staged = import::stage_imported_games(&client, games, move |done, total| {
+            progress_bar.set_fraction(done as f64 / total as f64);
+            progress_bar.set_text(Some(&format!("{} / {}", done, total)));
+        })
+        .await;
+
+        dialog.set_can_close(true);
+        dialog.close();
+
+        // Let the user confirm the matches before anything is added
+        import_review::present_review_dialog(
+            &window,
+            &review_title,
+            staged,
+            client,
+            time_formatter,
+            move |entries| {
+                if let Ok(mut library) = library.lock() {
+                    library.add_all(entries, status_merge);
+                    if let Err(e) = library.save() {
+                        eprintln!("Failed to save library: {}", e);
+                    }
+                    library_page.refresh(&library);
+                }
+            },
+        );
+    });
+}
+
+fn import_hltb_csv(
+    window: &adw::ApplicationWindow,
+    client: Arc<HltbClient>,
+    library: Arc<Mutex<Library>>,
+    library_page: LibraryPage,
+    time_formatter: TimeFormatter,
+) {
+    let file_dialog = gtk::FileDialog::builder()
+        .title(gettext("Import HowLongToBeat CSV"))
+        .filters(&csv_file_filters())
+        .build();
+
+    let window = window.clone();
+    glib::spawn_future_local(async move {
+        // An error here means the user cancelled the file chooser
+        let Ok(file) = file_dialog.open_future(Some(&window)).await else {
+            return;
+        };
+        let Some(path) = file.path() else {
+            return;
+        };
+
+        let games = std::fs::File::open(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(import::hltb_csv::read_csv);
+        match games {
+            Ok(games) if !games.is_empty() => {
+                stage_and_review_import(
+                    &window,
+                    "HowLongToBeat",
+                    games,
+                    StatusMerge::Replace,
+                    client,
+                    library,
+                    library_page,
+                    time_formatter,
+                );
+            }
+            Ok(_) => show_message(
+                &window,
+                &gettext("Nothing to Import"),
+                &gettext("The CSV file doesn't contain any games."),
+            ),
+            Err(e) => show_message(&window, &gettext("CSV Import Failed"), &e.to_string()),
+        }
+    });
+}
+
+fn export_hltb_csv(window: &adw::ApplicationWindow, library: Arc<Mutex<Library>>) {
+    let file_dialog = gtk::FileDialog::builder()
+        .title(gettext("Export Library as CSV"))
+        .initial_name("howlongtobeat-library.csv")
+        .filters(&csv_file_filters())
+        .build();
+
+    let window = window.clone();
+    glib::spawn_future_local(async move {
+        // An error here means the user cancelled the file chooser
+        let Ok(file) = file_dialog.save_future(Some(&window)).await else {
+            return;
+        };
+        let Some(path) = file.path() else {
+            return;
+        };
+
+        let result = match library.lock() {
+            Ok(library) => std::fs::File::create(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|file| import::hltb_csv::write_csv(file, library.entries())),
+            Err(_) => Err(anyhow::anyhow!(gettext("The library is unavailable"))),
+        };
+        if let Err(e) = result {
+            show_message(&window, &gettext("CSV Export Failed"), &e.to_string());
+        }
+    });
+}
+
+fn csv_file_filters() -> gio::ListStore {
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some(&gettext("CSV Files")));
+    filter.add_mime_type("text/csv");
+    filter.add_suffix("csv");
+
+    let filters = gio::ListStore::new::<gtk::FileFilter>();
+    filters.append(&filter);
+    filters
+}
+
+/// Presents a dialog with a progress bar that can't be dismissed until the caller allows it
+fn present_progress_dialog(
+    window: &adw::ApplicationWindow,
+    heading: &str,
+    body: &str,
+) -> (adw::AlertDialog, gtk::ProgressBar) {
+    let progress_bar = gtk::ProgressBar::builder()
+        .show_text(true)
+        .build();
+
+    let dialog = adw::AlertDialog::new(Some(heading), Some(body));
+    dialog.set_extra_child(Some(&progress_bar));
+    dialog.add_response("close", &gettext("Close"));
+    dialog.set_response_enabled("close", false);
+    dialog.set_can_close(false);
+    dialog.present(Some(window));
+
+    (dialog, progress_bar)
+}
+
+/// Body of the progress dialog while imported games are looked up
+fn looking_up_message(count: usize) -> String {
+    ngettext_f(
+        "Looking up {count} game on HowLongToBeat…",
+        "Looking up {count} games on HowLongToBeat…",
+        count,
+        &[("count", &count.to_string())],
+    )
+}
+
+fn show_message(window: &adw::ApplicationWindow, heading: &str, body: &str) {
+    let dialog = adw::AlertDialog::new(Some(heading), Some(body));
+    dialog.add_response("close", &gettext("Close"));
+    dialog.present(Some(window));
+}
+
+// Games the local index shows while HowLongToBeat is searched
+const MAX_LOCAL_HITS: usize = 20;
+
+// Heights of the completion time charts
+const CARD_CHART_HEIGHT: i32 = 36;
+const PAGE_CHART_HEIGHT: i32 = 72;
+
+// Relaxed queries tried after an empty search, and how many hits are worth offering
+const MAX_RELAXED_QUERIES: usize = 6;
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Starts with the library, then adds the games in the offline cache once they're read on a
+/// worker thread, which takes a moment with a full cache
+fn build_local_index(library: &Arc<Mutex<Library>>) -> Rc<RefCell<LocalIndex>> {
+    let library_games: Vec<Game> = library
+        .lock()
+        .map(|library| library.entries().iter().map(|entry| entry.game.clone()).collect())
+        .unwrap_or_default();
+    let local_index: Rc<RefCell<LocalIndex>> = Rc::default();
+    local_index.borrow_mut().extend(library_games.clone());
+
+    let local_index_clone = local_index.clone();
+    glib::spawn_future_local(async move {
+        let built = gio::spawn_blocking(move || {
+            let mut index = LocalIndex::default();
+            index.extend(ResultCache::new(ResultCache::default_dir()).games());
+            index.extend(library_games);
+            index
+        })
+        .await;
+        match built {
+            Ok(mut index) => {
+                // Games seen while the cache was read are the freshest copies
+                index.extend(local_index_clone.borrow().games().iter().cloned());
+                eprintln!("Indexed {} games for local search", index.len());
+                *local_index_clone.borrow_mut() = index;
+            }
+            Err(_) => eprintln!("Failed to index the offline cache"),
+        }
+    });
+    local_index
+}
+
+/// Looks up the game behind a pasted link or id. `text` is what was pasted.
+async fn resolve_link(client: &HltbClient, link: &Link, text: &str) -> anyhow::Result<Vec<Game>> {
+    match link {
+        Link::Game(game_id) => match client.game_by_id(*game_id).await {
+            Ok(game) => Ok(vec![game]),
+            // Titles like "1942" or "2048" are numbers too
+            Err(_) if text.trim().chars().all(|c| c.is_ascii_digit()) => client.search(text.trim()).await,
+            Err(e) => Err(e),
+        },
+        Link::SteamApp(app_id) => {
+            let name = steam::fetch_store_name(&reqwest::Client::new(), *app_id).await?;
+            let results = client.search(&name).await?;
+            Ok(import::pick_steam_match(results, *app_id, &name).into_iter().collect())
+        }
+        Link::Search(query) => client.search(query).await,
+    }
+}
+
+/// Tries relaxed variants of a query that found nothing and returns the ones with hits,
+/// along with their result counts. Filters are kept; only the search terms are relaxed.
+async fn find_suggestions(client: &HltbClient, parsed: &ParsedQuery) -> Vec<(String, usize)> {
+    let mut suggestions = Vec::new();
+    for variant in matcher::relax_query(&parsed.text()).into_iter().take(MAX_RELAXED_QUERIES) {
+        match client.search_with_options(&variant, parsed.options.clone()).await {
+            Ok(games) if !games.is_empty() => {
+                suggestions.push((parsed.with_terms(&variant), games.len()));
+                if suggestions.len() == MAX_SUGGESTIONS {
+                    break;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Suggestion search failed for '{}': {}", variant, e);
+                break;
+            }
+        }
+    }
+    suggestions
+}
+
+/// Shows the empty state in both views, with "did you mean" suggestions that re-run the search
+fn show_no_results(
+    list_box: &gtk::ListBox,
+    flow_box: &gtk::FlowBox,
+    search_entry: &gtk::SearchEntry,
+    query: &str,
+    suggestions: &[(String, usize)],
+) {
+    let no_results = adw::ActionRow::builder()
+        .title(gettext("No Results Found"))
+        .subtitle(gettext_f("No games found for “{query}”", &[("query", query)]))
+        .use_markup(false)
+        .build();
+    list_box.append(&no_results);
+
+    let suggestion_box = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(6)
+        .halign(gtk::Align::Center)
+        .build();
+    let status_page = adw::StatusPage::builder()
+        .icon_name("system-search-symbolic")
+        .title(gettext("No Results Found"))
+        .description(if suggestions.is_empty() {
+            gettext("Try a shorter title or check the spelling")
+        } else {
+            gettext("Did you mean…")
+        })
+        .child(&suggestion_box)
+        .build();
+    flow_box.insert(&status_page, -1);
+
+    for (suggestion, count) in suggestions {
+        let subtitle = ngettext_f("{count} game", "{count} games", *count, &[("count", &count.to_string())]);
+
+        let row = adw::ActionRow::builder()
+            .title(gettext_f("Did you mean “{query}”?", &[("query", suggestion)]))
+            .subtitle(&subtitle)
+            .use_markup(false)
+            .activatable(true)
+            .build();
+        row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+        let search_entry_clone = search_entry.clone();
+        let suggestion_clone = suggestion.clone();
+        row.connect_activated(move |_| {
+            search_entry_clone.set_text(&suggestion_clone);
+        });
+        list_box.append(&row);
+
+        let button = gtk::Button::builder()
+            .label(suggestion.as_str())
+            .tooltip_text(&subtitle)
+            .css_classes(vec!["pill"])
+            .build();
+        let search_entry_clone = search_entry.clone();
+        let suggestion_clone = suggestion.clone();
+        button.connect_clicked(move |_| {
+            search_entry_clone.set_text(&suggestion_clone);
+        });
+        suggestion_box.append(&button);
+    }
 }
 
 fn load_game_image(image: &gtk::Picture, image_url: String) {
@@ -352,13 +1399,17 @@ fn load_game_image(image: &gtk::Picture, image_url: String) {
     });
 }
 
-fn create_game_row(game: &Game) -> adw::ExpanderRow {
+fn create_game_row(
+    game: &Game,
+    time_formatter: &TimeFormatter,
+    compare_selection: &CompareSelection,
+) -> adw::ExpanderRow {
     let row = adw::ExpanderRow::builder()
         .title(&game.game_name)
         .subtitle(if !game.profile_platform.is_empty() {
-            &game.profile_platform
+            game.profile_platform.clone()
         } else {
-            "Multiple Platforms"
+            gettext("Multiple Platforms")
         })
         .build();
 
@@ -375,6 +1426,8 @@ fn create_game_row(game: &Game) -> adw::ExpanderRow {
         row.add_prefix(&image);
     }
 
+    row.add_suffix(&compare_selection.check_button(game));
+
     // Create details box
     let details_box = gtk::Box::new(Orientation::Vertical, 12);
     details_box.set_margin_top(12);
@@ -384,35 +1437,85 @@ fn create_game_row(game: &Game) -> adw::ExpanderRow {
 
     // Add completion times
     if game.comp_main > 0 {
-        let main_time = format_time(game.main_story_hours());
-        let main_row = create_time_row("Main Story", &main_time, game.comp_main_count);
+        let main_time = time_formatter.format(game.main_story_hours());
+        let main_row = create_time_row(&TimeCategory::Main.label(), &main_time, game.comp_main_count);
         details_box.append(&main_row);
     }
 
     if game.comp_plus > 0 {
-        let plus_time = format_time(game.main_plus_hours());
-        let plus_row = create_time_row("Main + Extras", &plus_time, game.comp_plus_count);
+        let plus_time = time_formatter.format(game.main_plus_hours());
+        let plus_row = create_time_row(&TimeCategory::MainExtras.label(), &plus_time, game.comp_plus_count);
         details_box.append(&plus_row);
     }
 
     if game.comp_100 > 0 {
-        let comp_time = format_time(game.completionist_hours());
-        let comp_row = create_time_row("Completionist", &comp_time, game.comp_100_count);
+        let comp_time = time_formatter.format(game.completionist_hours());
+        let comp_row = create_time_row(&TimeCategory::Completionist.label(), &comp_time, game.comp_100_count);
         details_box.append(&comp_row);
     }
 
     if game.comp_all > 0 {
-        let all_time = format_time(game.all_styles_hours());
-        let all_row = create_time_row("All Styles", &all_time, game.count_comp);
+        let all_time = time_formatter.format(game.all_styles_hours());
+        let all_row = create_time_row(&TimeCategory::AllStyles.label(), &all_time, game.count_comp);
         details_box.append(&all_row);
     }
 
+    // Co-op and multiplayer don't end, so these are how long people keep playing
+    if game.invested_co > 0 {
+        let co_op_time = time_formatter.format(game.co_op_hours());
+        let co_op_row = create_time_row(&PlayMode::CoOp.label(), &co_op_time, game.invested_co_count);
+        details_box.append(&co_op_row);
+    }
+
+    if game.invested_mp > 0 {
+        let mp_time = time_formatter.format(game.multiplayer_hours());
+        let mp_row = create_time_row(&PlayMode::Multiplayer.label(), &mp_time, game.invested_mp_count);
+        details_box.append(&mp_row);
+    }
+
+    details_box.append(&gtk::Separator::new(Orientation::Horizontal));
+
+    // Add the rest of what HLTB knows about the game
+    if let Some(rating) = game.rating() {
+        let rating_row = create_time_row(&gettext("Rating"), &format!("{}%", rating), game.count_review);
+        details_box.append(&rating_row);
+    }
+
+    if !game.profile_dev.is_empty() {
+        details_box.append(&create_stat_row(&gettext("Developer"), &game.profile_dev));
+    }
+
+    if game.release_world > 0 {
+        details_box.append(&create_stat_row(&gettext("Released"), &game.release_world.to_string()));
+    }
+
+    let play_modes = play_mode_labels(game);
+    if !play_modes.is_empty() {
+        details_box.append(&create_stat_row(&gettext("Modes"), &play_modes));
+    }
+
+    if game.has_speedruns() {
+        details_box.append(&create_stat_row(&gettext("Speedruns"), &game.count_speedrun.to_string()));
+    }
+
+    if game.count_playing > 0 || game.count_backlog > 0 || game.count_retired > 0 {
+        let players = gettext_f(
+            "{playing} playing · {backlog} backlogged · {retired} retired",
+            &[
+                ("playing", &game.count_playing.to_string()),
+                ("backlog", &game.count_backlog.to_string()),
+                ("retired", &game.count_retired.to_string()),
+            ],
+        );
+        details_box.append(&create_stat_row(&gettext("Players"), &players));
+    }
+
     // Add link to game page
     let link_box = gtk::Box::new(Orientation::Horizontal, 6);
     link_box.set_halign(gtk::Align::Start);
 
     let link_button = gtk::LinkButton::builder()
-        .label("View on HowLongToBeat")
+        .label(gettext("View on HowLongToBeat"))
         .uri(game.game_url())
         .build();
 
@@ -440,29 +1543,134 @@ fn create_time_row(label: &str, time: &str, count: u32) -> gtk::Box {
         .build();
 
     let count_label = gtk::Label::builder()
-        .label(format!("({} ratings)", count))
+        .label(ngettext_f(
+            "({count} rating)",
+            "({count} ratings)",
+            count as usize,
+            &[("count", &count.to_string())],
+        ))
         .halign(gtk::Align::End)
         .css_classes(vec!["dim-label", "caption"])
         .build();
 
     row.append(&label_widget);
+    if let Some(warning) = Reliability::from_count(count).warning() {
+        row.append(&create_reliability_badge(&warning));
+        count_label.add_css_class("warning");
+    }
     row.append(&time_label);
     row.append(&count_label);
 
     row
 }
 
-fn format_time(hours: f64) -> String {
-    if hours < 1.0 {
-        format!("{}m", (hours * 60.0) as u32)
-    } else if hours >= 100.0 {
-        format!("{:.0}h", hours)
-    } else {
-        format!("{:.1}h", hours)
+/// A warning sign for numbers too few players submitted, explained in its tooltip
+fn create_reliability_badge(warning: &str) -> gtk::Image {
+    let badge = gtk::Image::builder()
+        .icon_name("dialog-warning-symbolic")
+        .tooltip_text(warning)
+        .css_classes(vec!["warning"])
+        .build();
+    badge.update_property(&[gtk::accessible::Property::Description(warning)]);
+    badge
+}
+
+/// A row of the expanded details with a value that isn't a completion time
+fn create_stat_row(label: &str, value: &str) -> gtk::Box {
+    let row = gtk::Box::new(Orientation::Horizontal, 12);
+
+    let label_widget = gtk::Label::builder()
+        .label(label)
+        .halign(gtk::Align::Start)
+        .hexpand(true)
+        .build();
+
+    let value_label = gtk::Label::builder()
+        .label(value)
+        .halign(gtk::Align::End)
+        .wrap(true)
+        .justify(gtk::Justification::Right)
+        .css_classes(vec!["dim-label"])
+        .build();
+
+    row.append(&label_widget);
+    row.append(&value_label);
+
+    row
+}
+
+/// The game's play modes, like "Single-Player, Co-Op"
+fn play_mode_labels(game: &Game) -> String {
+    game.play_modes()
+        .into_iter()
+        .map(|mode| mode.label())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A small chart comparing the game's completion times, or `None` if there's nothing to compare
+fn create_time_chart(game: &Game, time_formatter: &TimeFormatter, height: i32) -> Option<gtk::DrawingArea> {
+    let bars = chart::chart_bars(game);
+    if bars.len() < 2 && bars.iter().all(|bar| bar.spread.is_none()) {
+        return None;
     }
+
+    // Spell the chart out for tooltips and screen readers
+    let description = bars
+        .iter()
+        .map(|bar| {
+            let time = gettext_f(
+                "{category}: {time}",
+                &[("category", &bar.category.label()), ("time", &time_formatter.format(bar.hours))],
+            );
+            match bar.spread {
+                Some(spread) => gettext_f(
+                    "{time} (rushed {rushed}, average {average}, leisure {leisure})",
+                    &[
+                        ("time", &time),
+                        ("rushed", &time_formatter.format(spread.rushed)),
+                        ("average", &time_formatter.format(spread.average)),
+                        ("leisure", &time_formatter.format(spread.leisure)),
+                    ],
+                ),
+                None => time,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let area = gtk::DrawingArea::builder()
+        .content_height(height)
+        .hexpand(true)
+        .tooltip_text(&description)
+        .build();
+    area.update_property(&[gtk::accessible::Property::Description(&description)]);
+    area.set_draw_func(move |area, cr, width, height| {
+        let color = area.color();
+        let palette = chart::Palette::new((
+            color.red().into(),
+            color.green().into(),
+            color.blue().into(),
+            color.alpha().into(),
+        ));
+        if let Err(e) = chart::draw(cr, width.into(), height.into(), &bars, &palette) {
+            eprintln!("Failed to draw the time chart: {}", e);
+        }
+    });
+    Some(area)
+}
+
+/// How sure a title match is, like "87% match"
+fn match_percentage(score: f64) -> String {
+    gettext_f("{percent}% match", &[("percent", &format!("{:.0}", score * 100.0))])
 }
 
-fn create_game_card(game: &Game) -> gtk::Box {
+fn create_game_card(
+    game: &Game,
+    time_category: TimeCategory,
+    time_formatter: &TimeFormatter,
+    compare_selection: &CompareSelection,
+) -> gtk::Box {
     let card = gtk::Box::new(Orientation::Vertical, 0);
     card.set_css_classes(&["card"]);
     card.set_width_request(250);
@@ -509,8 +1717,53 @@ fn create_game_card(game: &Game) -> gtk::Box {
         info_box.append(&platform_label);
     }
 
-    // Main completion time
-    if game.comp_main > 0 {
+    // Developer and release year
+    let mut credits = Vec::new();
+    if !game.profile_dev.is_empty() {
+        credits.push(game.profile_dev.clone());
+    }
+    if game.release_world > 0 {
+        credits.push(game.release_world.to_string());
+    }
+    if !credits.is_empty() {
+        let credits_label = gtk::Label::builder()
+            .label(credits.join(" · "))
+            .xalign(0.0)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .css_classes(vec!["dim-label", "caption"])
+            .build();
+        info_box.append(&credits_label);
+    }
+
+    // Rating, play modes and speedruns
+    let mut stats = Vec::new();
+    if let Some(rating) = game.rating() {
+        stats.push(format!("★ {}%", rating));
+    }
+    let play_modes = play_mode_labels(game);
+    if !play_modes.is_empty() {
+        stats.push(play_modes);
+    }
+    if game.has_speedruns() {
+        stats.push(ngettext_f(
+            "{count} speedrun",
+            "{count} speedruns",
+            game.count_speedrun as usize,
+            &[("count", &game.count_speedrun.to_string())],
+        ));
+    }
+    if !stats.is_empty() {
+        let stats_label = gtk::Label::builder()
+            .label(stats.join(" · "))
+            .xalign(0.0)
+            .wrap(true)
+            .css_classes(vec!["caption"])
+            .build();
+        info_box.append(&stats_label);
+    }
+
+    // Highlighted completion time
+    if let Some(hours) = time_category.hours(game) {
         let time_box = gtk::Box::new(Orientation::Horizontal, 6);
         time_box.set_margin_top(6);
         
@@ -520,14 +1773,53 @@ fn create_game_card(game: &Game) -> gtk::Box {
         time_box.append(&time_icon);
         
         let time_label = gtk::Label::builder()
-            .label(format!("Main: {}", format_time(game.main_story_hours())))
+            .label(gettext_f(
+                "{category}: {time}",
+                &[("category", &time_category.label()), ("time", &time_formatter.format(hours))],
+            ))
             .xalign(0.0)
             .build();
         time_box.append(&time_label);
+
+        // Dim times that rest on a handful of ratings
+        if let Some(warning) = reliability::grade(game, time_category).and_then(Reliability::warning) {
+            time_label.add_css_class("dim-label");
+            time_box.append(&create_reliability_badge(&warning));
+        }
         
         info_box.append(&time_box);
     }
 
+    if let Some(chart) = create_time_chart(game, time_formatter, CARD_CHART_HEIGHT) {
+        chart.set_margin_top(6);
+        info_box.append(&chart);
+    }
+
+    let compare_check = compare_selection.check_button(game);
+    compare_check.set_label(Some(&gettext("Compare")));
+    compare_check.set_halign(gtk::Align::Start);
+    compare_check.set_margin_top(6);
+    info_box.append(&compare_check);
+
+    // Co-op and multiplayer play time
+    for (mode, seconds, hours) in [
+        (PlayMode::CoOp, game.invested_co, game.co_op_hours()),
+        (PlayMode::Multiplayer, game.invested_mp, game.multiplayer_hours()),
+    ] {
+        if seconds == 0 {
+            continue;
+        }
+        let mode_label = gtk::Label::builder()
+            .label(gettext_f(
+                "{category}: {time}",
+                &[("category", &mode.label()), ("time", &time_formatter.format(hours))],
+            ))
+            .xalign(0.0)
+            .css_classes(vec!["caption"])
+            .build();
+        info_box.append(&mode_label);
+    }
+
     card.append(&info_box);
 
     // Make the card clickable to open the game URL
@@ -0,0 +1,489 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use gtk::{gio, glib};
+use gtk::Orientation;
+use std::sync::{Arc, Mutex};
+
+use crate::api::HltbClient;
+use crate::batch::{self, BatchResult};
+use crate::i18n::{gettext, gettext_f, ngettext_f};
+use crate::library::{Library, LibraryEntry, StatusMerge};
+use crate::settings::TimeCategory;
+use crate::time_format::TimeFormatter;
+
+use super::library_page::LibraryPage;
+use super::match_picker::present_match_picker;
+use super::csv_file_filters;
+
+/// Presents the batch lookup dialog: paste or open a list of titles, look them all up
+/// and review the matches as a table that can be exported or added to the library.
+pub fn present_batch_dialog(
+    window: &adw::ApplicationWindow,
+    client: Arc<HltbClient>,
+    library: Arc<Mutex<Library>>,
+    library_page: LibraryPage,
+    time_formatter: TimeFormatter,
+) {
+    let text_view = gtk::TextView::builder()
+        .wrap_mode(gtk::WrapMode::WordChar)
+        .top_margin(12)
+        .bottom_margin(12)
+        .left_margin(12)
+        .right_margin(12)
+        .css_classes(vec!["card"])
+        .build();
+
+    let input_scrolled = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vexpand(true)
+        .child(&text_view)
+        .build();
+
+    let hint = gtk::Label::builder()
+        .label(gettext("One title per line, or a CSV file with a Title column"))
+        .css_classes(vec!["dim-label"])
+        .halign(gtk::Align::Start)
+        .build();
+
+    let input_box = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    input_box.append(&hint);
+    input_box.append(&input_scrolled);
+
+    let progress_bar = gtk::ProgressBar::builder()
+        .show_text(true)
+        .valign(gtk::Align::Center)
+        .margin_start(24)
+        .margin_end(24)
+        .build();
+
+    let grid = gtk::Grid::builder()
+        .column_spacing(18)
+        .row_spacing(6)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+
+    let results_scrolled = gtk::ScrolledWindow::builder()
+        .vexpand(true)
+        .child(&grid)
+        .build();
+
+    let stack = gtk::Stack::new();
+    stack.add_named(&input_box, Some("input"));
+    stack.add_named(&progress_bar, Some("progress"));
+    stack.add_named(&results_scrolled, Some("results"));
+
+    let open_button = gtk::Button::builder()
+        .label(gettext("Open File…"))
+        .build();
+    let lookup_button = gtk::Button::builder()
+        .label(gettext("Look Up"))
+        .css_classes(vec!["suggested-action"])
+        .build();
+    let retry_button = gtk::Button::builder()
+        .label(gettext("Retry Failed"))
+        .build();
+    let export_button = gtk::Button::builder()
+        .label(gettext("Export CSV…"))
+        .build();
+    let add_button = gtk::Button::builder()
+        .css_classes(vec!["suggested-action"])
+        .build();
+
+    let bottom_bar = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .halign(gtk::Align::End)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    bottom_bar.append(&open_button);
+    bottom_bar.append(&lookup_button);
+    bottom_bar.append(&retry_button);
+    bottom_bar.append(&export_button);
+    bottom_bar.append(&add_button);
+    retry_button.set_visible(false);
+    export_button.set_visible(false);
+    add_button.set_visible(false);
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&adw::HeaderBar::new());
+    toolbar_view.set_content(Some(&stack));
+    toolbar_view.add_bottom_bar(&bottom_bar);
+
+    let dialog = adw::Dialog::builder()
+        .title(gettext("Batch Lookup"))
+        .content_width(760)
+        .content_height(560)
+        .child(&toolbar_view)
+        .build();
+
+    // Results with a flag for whether each row goes into the library
+    let results: Arc<Mutex<Vec<(BatchResult, bool)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let buffer = text_view.buffer();
+    lookup_button.set_sensitive(false);
+    let lookup_button_clone = lookup_button.clone();
+    buffer.connect_changed(move |buffer| {
+        let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+        lookup_button_clone.set_sensitive(!batch::parse_title_list(&text).is_empty());
+    });
+
+    let window_clone = window.clone();
+    let buffer_clone = buffer.clone();
+    open_button.connect_clicked(move |_| {
+        let file_dialog = gtk::FileDialog::builder()
+            .title(gettext("Open List of Games"))
+            .filters(&title_list_filters())
+            .build();
+
+        let window = window_clone.clone();
+        let buffer = buffer_clone.clone();
+        glib::spawn_future_local(async move {
+            // An error here means the user cancelled the file chooser
+            let Ok(file) = file_dialog.open_future(Some(&window)).await else {
+                return;
+            };
+            let Some(path) = file.path() else {
+                return;
+            };
+            match std::fs::read_to_string(&path) {
+                Ok(text) => buffer.set_text(&text),
+                Err(e) => super::show_message(&window, &gettext("Failed to Open File"), &e.to_string()),
+            }
+        });
+    });
+
+    let client_clone = client.clone();
+    let grid_clone = grid.clone();
+    let stack_clone = stack.clone();
+    let open_button_clone = open_button.clone();
+    let retry_button_clone = retry_button.clone();
+    let export_button_clone = export_button.clone();
+    let add_button_clone = add_button.clone();
+    let results_clone = results.clone();
+    let time_formatter_clone = time_formatter.clone();
+    lookup_button.connect_clicked(move |lookup_button| {
+        let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+        let titles = batch::parse_title_list(&text);
+        if titles.is_empty() {
+            return;
+        }
+
+        stack_clone.set_visible_child_name("progress");
+        lookup_button.set_visible(false);
+        open_button_clone.set_visible(false);
+        progress_bar.set_fraction(0.0);
+        progress_bar.set_text(Some(&format!("0 / {}", titles.len())));
+
+        let client = client.clone();
+        let progress_bar = progress_bar.clone();
+        let stack = stack_clone.clone();
+        let grid = grid.clone();
+        let retry_button = retry_button_clone.clone();
+        let export_button = export_button_clone.clone();
+        let add_button = add_button_clone.clone();
+        let results = results_clone.clone();
+        let time_formatter = time_formatter_clone.clone();
+        glib::spawn_future_local(async move {
+            let looked_up = batch::lookup_titles(
+                client,
+                titles,
+                batch::DEFAULT_CONCURRENCY,
+                move |done, total| {
+                    progress_bar.set_fraction(done as f64 / total as f64);
+                    progress_bar.set_text(Some(&format!("{} / {}", done, total)));
+                },
+            )
+            .await;
+
+            if let Ok(mut results) = results.lock() {
+                *results = looked_up
+                    .into_iter()
+                    .map(|result| {
+                        // Ambiguous matches wait for the user to choose one
+                        let include = result.game.is_some() && !result.is_ambiguous();
+                        (result, include)
+                    })
+                    .collect();
+            }
+
+            populate_grid(&grid, &results, &add_button, &time_formatter);
+            update_add_button(&add_button, &results);
+            retry_button.set_visible(has_failures(&results));
+            export_button.set_visible(true);
+            add_button.set_visible(true);
+            stack.set_visible_child_name("results");
+        });
+    });
+
+    // Titles whose lookup failed, like when HLTB couldn't be reached, can be looked up again
+    let add_button_clone = add_button.clone();
+    let results_clone = results.clone();
+    let time_formatter_clone = time_formatter.clone();
+    retry_button.connect_clicked(move |retry_button| {
+        let (indexes, titles): (Vec<usize>, Vec<String>) = match results_clone.lock() {
+            Ok(rows) => rows
+                .iter()
+                .enumerate()
+                .filter(|(_, (result, _))| result.failed.is_some())
+                .map(|(index, (result, _))| (index, result.query.clone()))
+                .unzip(),
+            Err(_) => return,
+        };
+        retry_button.set_sensitive(false);
+        retry_button.set_label(&gettext("Retrying…"));
+
+        let client = client_clone.clone();
+        let retry_button = retry_button.clone();
+        let grid = grid_clone.clone();
+        let add_button = add_button_clone.clone();
+        let results = results_clone.clone();
+        let time_formatter = time_formatter_clone.clone();
+        glib::spawn_future_local(async move {
+            let looked_up = batch::lookup_titles(client, titles, batch::DEFAULT_CONCURRENCY, |_, _| {}).await;
+            if let Ok(mut rows) = results.lock() {
+                for (index, result) in indexes.into_iter().zip(looked_up) {
+                    let include = result.game.is_some() && !result.is_ambiguous();
+                    rows[index] = (result, include);
+                }
+            }
+
+            populate_grid(&grid, &results, &add_button, &time_formatter);
+            update_add_button(&add_button, &results);
+            retry_button.set_label(&gettext("Retry Failed"));
+            retry_button.set_sensitive(true);
+            retry_button.set_visible(has_failures(&results));
+        });
+    });
+
+    let window_clone = window.clone();
+    let results_clone = results.clone();
+    export_button.connect_clicked(move |_| {
+        export_results(&window_clone, results_clone.clone(), time_formatter.clone());
+    });
+
+    let dialog_clone = dialog.clone();
+    add_button.connect_clicked(move |_| {
+        let entries: Vec<LibraryEntry> = match results.lock() {
+            Ok(results) => results
+                .iter()
+                .filter(|(_, include)| *include)
+                .filter_map(|(result, _)| result.game.clone())
+                .map(|game| LibraryEntry::new(game, "batch"))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+
+        if let Ok(mut library) = library.lock() {
+            library.add_all(entries, StatusMerge::Keep);
+            if let Err(e) = library.save() {
+                eprintln!("Failed to save library: {}", e);
+            }
+            library_page.refresh(&library);
+        }
+        dialog_clone.close();
+    });
+
+    dialog.present(Some(window));
+}
+
+fn populate_grid(
+    grid: &gtk::Grid,
+    results: &Arc<Mutex<Vec<(BatchResult, bool)>>>,
+    add_button: &gtk::Button,
+    time_formatter: &TimeFormatter,
+) {
+    while let Some(child) = grid.first_child() {
+        grid.remove(&child);
+    }
+
+    let headers = [
+        String::new(),
+        gettext("Title"),
+        gettext("Match"),
+        TimeCategory::Main.label(),
+        TimeCategory::MainExtras.label(),
+        TimeCategory::Completionist.label(),
+        gettext("Confidence"),
+    ];
+    for (column, header) in headers.iter().enumerate() {
+        let label = gtk::Label::builder()
+            .label(header)
+            .css_classes(vec!["heading"])
+            .halign(gtk::Align::Start)
+            .build();
+        grid.attach(&label, column as i32, 0, 1, 1);
+    }
+
+    let Ok(rows) = results.lock() else {
+        return;
+    };
+
+    let hours = |value: f64| if value > 0.0 { time_formatter.format(value) } else { "--".to_string() };
+
+    for (index, (result, include)) in rows.iter().enumerate() {
+        let row = index as i32 + 1;
+
+        let check = gtk::CheckButton::builder()
+            .active(*include)
+            .sensitive(result.game.is_some())
+            .build();
+        let results_clone = results.clone();
+        let add_button_clone = add_button.clone();
+        check.connect_toggled(move |check| {
+            if let Ok(mut rows) = results_clone.lock() {
+                rows[index].1 = check.is_active();
+            }
+            update_add_button(&add_button_clone, &results_clone);
+        });
+        grid.attach(&check, 0, row, 1, 1);
+
+        let cells = match &result.game {
+            Some(game) => vec![
+                result.query.clone(),
+                game.game_name.clone(),
+                hours(game.main_story_hours()),
+                hours(game.main_plus_hours()),
+                hours(game.completionist_hours()),
+                format!("{:.0}%", result.confidence * 100.0),
+            ],
+            None => vec![
+                result.query.clone(),
+                match &result.failed {
+                    Some(reason) => gettext_f("Lookup failed: {reason}", &[("reason", reason)]),
+                    None => gettext("No match"),
+                },
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ],
+        };
+
+        for (column, text) in cells.into_iter().enumerate() {
+            let label = gtk::Label::builder()
+                .label(text)
+                .halign(gtk::Align::Start)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .max_width_chars(32)
+                .build();
+            // Flag matches that are worth a second look
+            if column == 5 && result.is_ambiguous() {
+                label.add_css_class("warning");
+            }
+            if result.game.is_none() && column == 1 {
+                label.add_css_class(if result.failed.is_some() { "error" } else { "dim-label" });
+            }
+            grid.attach(&label, column as i32 + 1, row, 1, 1);
+        }
+
+        if result.candidates.len() > 1 {
+            let choose_button = gtk::Button::builder()
+                .label(gettext("Choose…"))
+                .css_classes(vec![if result.is_ambiguous() { "suggested-action" } else { "flat" }])
+                .build();
+            grid.attach(&choose_button, headers.len() as i32, row, 1, 1);
+
+            let query = result.query.clone();
+            let candidates = result.candidates.clone();
+            let grid = grid.clone();
+            let results = results.clone();
+            let add_button = add_button.clone();
+            let time_formatter = time_formatter.clone();
+            choose_button.connect_clicked(move |button| {
+                let grid = grid.clone();
+                let results = results.clone();
+                let add_button = add_button.clone();
+                let time_formatter_clone = time_formatter.clone();
+                present_match_picker(button, &query, &candidates, &time_formatter, move |game| {
+                    if let Ok(mut rows) = results.lock() {
+                        rows[index].0.pick(game);
+                        rows[index].1 = true;
+                    }
+                    populate_grid(&grid, &results, &add_button, &time_formatter_clone);
+                    update_add_button(&add_button, &results);
+                });
+            });
+        }
+    }
+}
+
+fn has_failures(results: &Arc<Mutex<Vec<(BatchResult, bool)>>>) -> bool {
+    results
+        .lock()
+        .is_ok_and(|rows| rows.iter().any(|(result, _)| result.failed.is_some()))
+}
+
+fn update_add_button(button: &gtk::Button, results: &Arc<Mutex<Vec<(BatchResult, bool)>>>) {
+    let selected = results
+        .lock()
+        .map(|rows| rows.iter().filter(|(_, include)| *include).count())
+        .unwrap_or(0);
+
+    button.set_label(&ngettext_f(
+        "Add {count} to Library",
+        "Add {count} to Library",
+        selected,
+        &[("count", &selected.to_string())],
+    ));
+    button.set_sensitive(selected > 0);
+}
+
+fn export_results(
+    window: &adw::ApplicationWindow,
+    results: Arc<Mutex<Vec<(BatchResult, bool)>>>,
+    time_formatter: TimeFormatter,
+) {
+    let file_dialog = gtk::FileDialog::builder()
+        .title(gettext("Export Lookup Results"))
+        .initial_name("howlongtobeat-lookup.csv")
+        .filters(&csv_file_filters())
+        .build();
+
+    let window = window.clone();
+    glib::spawn_future_local(async move {
+        // An error here means the user cancelled the file chooser
+        let Ok(file) = file_dialog.save_future(Some(&window)).await else {
+            return;
+        };
+        let Some(path) = file.path() else {
+            return;
+        };
+
+        let rows: Vec<BatchResult> = match results.lock() {
+            Ok(rows) => rows.iter().map(|(result, _)| result.clone()).collect(),
+            Err(_) => Vec::new(),
+        };
+        let result = std::fs::File::create(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| batch::write_results_csv(file, &rows, &time_formatter));
+        if let Err(e) = result {
+            super::show_message(&window, &gettext("Export Failed"), &e.to_string());
+        }
+    });
+}
+
+fn title_list_filters() -> gio::ListStore {
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some(&gettext("Text and CSV Files")));
+    filter.add_mime_type("text/plain");
+    filter.add_mime_type("text/csv");
+    filter.add_suffix("txt");
+    filter.add_suffix("csv");
+
+    let filters = gio::ListStore::new::<gtk::FileFilter>();
+    filters.append(&filter);
+    filters
+}
@@ -0,0 +1,186 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::api::Game;
+use crate::compare::{self, MAX_GAMES, MIN_GAMES};
+use crate::i18n::{gettext, ngettext_f};
+use crate::settings::Settings;
+use crate::time_format::TimeFormatter;
+
+/// Games picked for comparison, kept across searches so candidates can be gathered one search
+/// at a time. The bar at the bottom of the window shows the selection and opens the comparison.
+#[derive(Clone)]
+pub struct CompareSelection {
+    pub bar: gtk::ActionBar,
+    games: Rc<RefCell<Vec<Game>>>,
+    // The checks of the results on screen, by game id
+    checks: Rc<RefCell<Vec<(u64, gtk::CheckButton)>>>,
+    count_label: gtk::Label,
+    compare_button: gtk::Button,
+}
+
+impl CompareSelection {
+    pub fn new(window: &adw::ApplicationWindow, settings: Settings) -> Self {
+        let clear_button = gtk::Button::builder().label(gettext("Clear")).build();
+        let count_label = gtk::Label::new(None);
+        let compare_button = gtk::Button::builder()
+            .label(gettext("Compare"))
+            .css_classes(vec!["suggested-action"])
+            .build();
+
+        let bar = gtk::ActionBar::builder().revealed(false).build();
+        bar.pack_start(&clear_button);
+        bar.set_center_widget(Some(&count_label));
+        bar.pack_end(&compare_button);
+
+        let selection = Self {
+            bar,
+            games: Rc::default(),
+            checks: Rc::default(),
+            count_label,
+            compare_button,
+        };
+
+        let selection_clone = selection.clone();
+        clear_button.connect_clicked(move |_| {
+            selection_clone.games.borrow_mut().clear();
+            selection_clone.sync();
+        });
+
+        let games_clone = selection.games.clone();
+        let window = window.clone();
+        selection.compare_button.connect_clicked(move |_| {
+            present_comparison(&window, &games_clone.borrow(), &settings.time_formatter());
+        });
+
+        selection
+    }
+
+    /// A check that adds `game` to the comparison, or takes it out again
+    pub fn check_button(&self, game: &Game) -> gtk::CheckButton {
+        let check = gtk::CheckButton::builder()
+            .active(self.contains(game.game_id))
+            .tooltip_text(gettext("Compare"))
+            .valign(gtk::Align::Center)
+            .build();
+        self.checks.borrow_mut().push((game.game_id, check.clone()));
+
+        let selection = self.clone();
+        let game = game.clone();
+        check.connect_toggled(move |check| {
+            if check.is_active() == selection.contains(game.game_id) {
+                // Following a change made elsewhere
+                return;
+            }
+            if check.is_active() {
+                if selection.games.borrow().len() >= MAX_GAMES {
+                    check.set_active(false);
+                    return;
+                }
+                selection.games.borrow_mut().push(game.clone());
+            } else {
+                selection.games.borrow_mut().retain(|selected| selected.game_id != game.game_id);
+            }
+            selection.sync();
+        });
+        check
+    }
+
+    /// Drops the checks of results that are no longer shown; their games stay selected
+    pub fn forget_checks(&self) {
+        self.checks.borrow_mut().clear();
+    }
+
+    fn contains(&self, game_id: u64) -> bool {
+        self.games.borrow().iter().any(|game| game.game_id == game_id)
+    }
+
+    /// Brings the checks and the bar in line with the selection. A game can be shown in both
+    /// the list and the grid, so checking one of its checks checks the other.
+    fn sync(&self) {
+        let checks: Vec<(u64, gtk::CheckButton)> = self.checks.borrow().clone();
+        for (game_id, check) in checks {
+            check.set_active(self.contains(game_id));
+        }
+
+        let count = self.games.borrow().len();
+        self.count_label.set_label(&ngettext_f(
+            "{count} of {max} selected",
+            "{count} of {max} selected",
+            count,
+            &[("count", &count.to_string()), ("max", &MAX_GAMES.to_string())],
+        ));
+        self.compare_button.set_sensitive(count >= MIN_GAMES);
+        self.bar.set_revealed(count > 0);
+    }
+}
+
+/// Shows the games side by side, with the best value of each row highlighted
+fn present_comparison(parent: &impl IsA<gtk::Widget>, games: &[Game], time_formatter: &TimeFormatter) {
+    let grid = gtk::Grid::builder()
+        .column_spacing(18)
+        .row_spacing(6)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+
+    for (column, game) in games.iter().enumerate() {
+        let name = gtk::Label::builder()
+            .label(&game.game_name)
+            .wrap(true)
+            .max_width_chars(20)
+            .xalign(0.0)
+            .css_classes(vec!["heading"])
+            .build();
+        grid.attach(&name, column as i32 + 1, 0, 1, 1);
+    }
+
+    for (index, row) in compare::compare(games, time_formatter).iter().enumerate() {
+        let line = index as i32 + 1;
+        let label = gtk::Label::builder()
+            .label(&row.label)
+            .xalign(0.0)
+            .css_classes(vec!["dim-label"])
+            .build();
+        grid.attach(&label, 0, line, 1, 1);
+
+        for (column, (cell, best)) in row.cells.iter().zip(&row.best).enumerate() {
+            let value = gtk::Label::builder()
+                .label(cell.as_deref().unwrap_or("—"))
+                .wrap(true)
+                .max_width_chars(20)
+                .xalign(0.0)
+                .build();
+            if *best {
+                value.add_css_class("success");
+                value.add_css_class("heading");
+            } else if cell.is_none() {
+                value.add_css_class("dim-label");
+            }
+            grid.attach(&value, column as i32 + 1, line, 1, 1);
+        }
+    }
+
+    let scrolled = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Automatic)
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .propagate_natural_width(true)
+        .vexpand(true)
+        .child(&grid)
+        .build();
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&adw::HeaderBar::new());
+    toolbar_view.set_content(Some(&scrolled));
+
+    let dialog = adw::Dialog::builder()
+        .title(gettext("Compare Games"))
+        .content_height(560)
+        .child(&toolbar_view)
+        .build();
+    dialog.present(Some(parent));
+}
@@ -0,0 +1,111 @@
+use libadwaita as adw;
+use adw::prelude::*;
+
+use crate::discover::{Budget, GENRES};
+use crate::i18n::gettext;
+use crate::query;
+use crate::settings::{Settings, TimeCategory};
+
+const DEFAULT_HOURS: f64 = 10.0;
+const MAX_HOURS: f64 = 500.0;
+
+/// Asks how many hours the player has, and on which platform and in which genre, then hands
+/// the budget to `on_find`
+pub fn present_discovery(
+    parent: &impl IsA<gtk::Widget>,
+    settings: &Settings,
+    on_find: impl Fn(Budget) + 'static,
+) {
+    let group = adw::PreferencesGroup::builder()
+        .description(gettext("Find well rated games you can finish in the time you have"))
+        .build();
+
+    let hours_row = adw::SpinRow::builder()
+        .title(gettext("Hours"))
+        .adjustment(&gtk::Adjustment::new(DEFAULT_HOURS, 1.0, MAX_HOURS, 1.0, 10.0, 0.0))
+        .build();
+    group.add(&hours_row);
+
+    let labels: Vec<String> = TimeCategory::ALL.iter().map(|category| category.label()).collect();
+    let category_row = adw::ComboRow::builder()
+        .title(gettext("To Finish"))
+        .model(&gtk::StringList::new(&labels.iter().map(String::as_str).collect::<Vec<_>>()))
+        .selected(
+            TimeCategory::ALL
+                .iter()
+                .position(|category| *category == settings.time_category())
+                .unwrap_or(0) as u32,
+        )
+        .build();
+    group.add(&category_row);
+
+    // The first choice of each leaves the filter out
+    let platforms = query::platform_names();
+    let any_platform = gettext("Any Platform");
+    let platform_row = adw::ComboRow::builder()
+        .title(gettext("Platform"))
+        .model(&gtk::StringList::new(
+            &std::iter::once(any_platform.as_str()).chain(platforms.iter().copied()).collect::<Vec<_>>(),
+        ))
+        .build();
+    group.add(&platform_row);
+
+    let any_genre = gettext("Any Genre");
+    let genre_row = adw::ComboRow::builder()
+        .title(gettext("Genre"))
+        .model(&gtk::StringList::new(
+            &std::iter::once(any_genre.as_str()).chain(GENRES.iter().copied()).collect::<Vec<_>>(),
+        ))
+        .build();
+    group.add(&genre_row);
+
+    let find_button = gtk::Button::builder()
+        .label(gettext("Find Games"))
+        .halign(gtk::Align::Center)
+        .css_classes(vec!["suggested-action", "pill"])
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(24)
+        .margin_top(12)
+        .margin_bottom(24)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    content.append(&group);
+    content.append(&find_button);
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&adw::HeaderBar::new());
+    toolbar_view.set_content(Some(&content));
+
+    let dialog = adw::Dialog::builder()
+        .title(gettext("What Can I Finish?"))
+        .content_width(420)
+        .child(&toolbar_view)
+        .build();
+
+    let dialog_clone = dialog.clone();
+    find_button.connect_clicked(move |_| {
+        let choice = |row: &adw::ComboRow, names: &[&str]| {
+            (row.selected() as usize)
+                .checked_sub(1)
+                .and_then(|index| names.get(index))
+                .map(|name| name.to_string())
+                .unwrap_or_default()
+        };
+        on_find(Budget {
+            hours: hours_row.value(),
+            category: TimeCategory::ALL
+                .get(category_row.selected() as usize)
+                .copied()
+                .unwrap_or(TimeCategory::Main),
+            platform: choice(&platform_row, &platforms),
+            genre: choice(&genre_row, GENRES),
+        });
+        dialog_clone.close();
+    });
+
+    dialog.present(Some(parent));
+}
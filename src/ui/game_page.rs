@@ -0,0 +1,258 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use gtk::{gio, Orientation};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::api::Game;
+use crate::i18n::{gettext, ngettext_f};
+use crate::reliability::Reliability;
+use crate::settings::{Settings, TimeCategory};
+
+use super::{create_reliability_badge, create_time_chart, load_game_image, PAGE_CHART_HEIGHT};
+
+type ShownCallback = Rc<RefCell<Option<Rc<dyn Fn(&Game)>>>>;
+
+/// Widgets of the single game page, shown when a game is opened from outside the app
+#[derive(Clone)]
+pub struct GamePage {
+    pub page: gtk::Box,
+    pub back_button: gtk::Button,
+    stack: gtk::Stack,
+    cover: gtk::Picture,
+    title: gtk::Label,
+    details: gtk::Label,
+    chart_slot: gtk::Box,
+    times: gtk::ListBox,
+    link_button: gtk::Button,
+    /// The game on the page, to write its times again when their format changes
+    game: Rc<RefCell<Option<Game>>>,
+    settings: Settings,
+    shown: ShownCallback,
+}
+
+impl GamePage {
+    pub fn new(settings: Settings) -> Self {
+        let back_button = gtk::Button::builder()
+            .icon_name("go-previous-symbolic")
+            .tooltip_text(gettext("Back"))
+            .halign(gtk::Align::Start)
+            .css_classes(vec!["flat"])
+            .build();
+
+        let cover = gtk::Picture::builder()
+            .width_request(250)
+            .height_request(150)
+            .halign(gtk::Align::Center)
+            .can_shrink(true)
+            .build();
+
+        let title = gtk::Label::builder()
+            .wrap(true)
+            .justify(gtk::Justification::Center)
+            .css_classes(vec!["title-1"])
+            .build();
+
+        let details = gtk::Label::builder()
+            .wrap(true)
+            .justify(gtk::Justification::Center)
+            .css_classes(vec!["dim-label"])
+            .build();
+
+        // Holds the chart of the game on show
+        let chart_slot = gtk::Box::builder()
+            .orientation(Orientation::Vertical)
+            .margin_start(6)
+            .margin_end(6)
+            .build();
+
+        let times = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(vec!["boxed-list"])
+            .build();
+
+        let link_button = gtk::Button::builder()
+            .label(gettext("Open on HowLongToBeat"))
+            .halign(gtk::Align::Center)
+            .css_classes(vec!["pill"])
+            .build();
+
+        let content = gtk::Box::new(Orientation::Vertical, 12);
+        content.set_margin_top(12);
+        content.set_margin_bottom(24);
+        content.append(&cover);
+        content.append(&title);
+        content.append(&details);
+        content.append(&chart_slot);
+        content.append(&times);
+        content.append(&link_button);
+
+        let clamp = adw::Clamp::builder()
+            .maximum_size(600)
+            .margin_start(12)
+            .margin_end(12)
+            .child(&content)
+            .build();
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .child(&clamp)
+            .build();
+
+        let loading = gtk::Spinner::builder()
+            .spinning(true)
+            .width_request(32)
+            .height_request(32)
+            .halign(gtk::Align::Center)
+            .valign(gtk::Align::Center)
+            .build();
+
+        let error_page = adw::StatusPage::builder()
+            .icon_name("dialog-error-symbolic")
+            .title(gettext("Game Not Found"))
+            .build();
+
+        let stack = gtk::Stack::new();
+        stack.add_named(&scrolled, Some("game"));
+        stack.add_named(&loading, Some("loading"));
+        stack.add_named(&error_page, Some("error"));
+
+        let page = gtk::Box::new(Orientation::Vertical, 0);
+        back_button.set_margin_top(6);
+        back_button.set_margin_start(6);
+        page.append(&back_button);
+        page.append(&stack);
+
+        let game: Rc<RefCell<Option<Game>>> = Rc::default();
+        let game_clone = game.clone();
+        link_button.connect_clicked(move |_| {
+            let uri = game_clone.borrow().as_ref().map(Game::game_url);
+            if let Some(uri) = uri {
+                let _ = gio::AppInfo::launch_default_for_uri(&uri, None::<&gio::AppLaunchContext>);
+            }
+        });
+
+        Self {
+            page,
+            back_button,
+            stack,
+            cover,
+            title,
+            details,
+            chart_slot,
+            times,
+            link_button,
+            game,
+            settings,
+            shown: Rc::default(),
+        }
+    }
+
+    /// Calls `callback` with every game the page shows
+    pub fn connect_shown(&self, callback: impl Fn(&Game) + 'static) {
+        *self.shown.borrow_mut() = Some(Rc::new(callback));
+    }
+
+    /// Shows a spinner while the game is fetched
+    pub fn show_loading(&self) {
+        self.stack.set_visible_child_name("loading");
+    }
+
+    /// Shows why the game couldn't be loaded
+    pub fn show_error(&self, message: &str) {
+        if let Some(status_page) = self.stack.child_by_name("error").and_downcast::<adw::StatusPage>() {
+            status_page.set_description(Some(message));
+        }
+        self.stack.set_visible_child_name("error");
+    }
+
+    /// Fills the page with a game
+    pub fn show_game(&self, game: &Game) {
+        self.cover.set_paintable(None::<&gtk::gdk::Paintable>);
+        self.cover.set_visible(!game.game_image.is_empty());
+        if !game.game_image.is_empty() {
+            load_game_image(&self.cover, game.image_url());
+        }
+
+        self.title.set_label(&game.game_name);
+
+        let mut details = Vec::new();
+        if !game.profile_platform.is_empty() {
+            details.push(game.profile_platform.clone());
+        }
+        if game.release_world > 0 {
+            details.push(game.release_world.to_string());
+        }
+        if !game.profile_dev.is_empty() {
+            details.push(game.profile_dev.clone());
+        }
+        self.details.set_label(&details.join(" · "));
+        self.details.set_visible(!details.is_empty());
+
+        self.show_times(game);
+
+        *self.game.borrow_mut() = Some(game.clone());
+        self.link_button.set_visible(game.game_id > 0);
+        self.stack.set_visible_child_name("game");
+
+        let callback = self.shown.borrow().clone();
+        if let Some(callback) = callback {
+            callback(game);
+        }
+    }
+
+    /// Writes the times of the game on the page again, in the format from the preferences
+    pub fn refresh(&self) {
+        let game = self.game.borrow().clone();
+        if let Some(game) = game {
+            self.show_times(&game);
+        }
+    }
+
+    fn show_times(&self, game: &Game) {
+        let time_formatter = self.settings.time_formatter();
+        while let Some(child) = self.chart_slot.first_child() {
+            self.chart_slot.remove(&child);
+        }
+        if let Some(chart) = create_time_chart(game, &time_formatter, PAGE_CHART_HEIGHT) {
+            self.chart_slot.append(&chart);
+        }
+        self.chart_slot.set_visible(self.chart_slot.first_child().is_some());
+
+        while let Some(child) = self.times.first_child() {
+            self.times.remove(&child);
+        }
+        for (category, count) in [
+            (TimeCategory::Main, game.comp_main_count),
+            (TimeCategory::MainExtras, game.comp_plus_count),
+            (TimeCategory::Completionist, game.comp_100_count),
+            (TimeCategory::AllStyles, game.count_comp),
+        ] {
+            let Some(hours) = category.hours(game) else {
+                continue;
+            };
+            let row = adw::ActionRow::builder()
+                .title(category.label())
+                .subtitle(ngettext_f(
+                    "{count} rating",
+                    "{count} ratings",
+                    count as usize,
+                    &[("count", &count.to_string())],
+                ))
+                .build();
+            row.add_suffix(
+                &gtk::Label::builder()
+                    .label(time_formatter.format(hours))
+                    .css_classes(vec!["title-3"])
+                    .build(),
+            );
+            if let Some(warning) = Reliability::from_count(count).warning() {
+                row.add_suffix(&create_reliability_badge(&warning));
+            }
+            self.times.append(&row);
+        }
+        self.times.set_visible(self.times.first_child().is_some());
+    }
+}
@@ -0,0 +1,200 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use gtk::{glib, Orientation};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::api::{Game, HltbClient};
+use crate::home_feed::{HomeFeed, SectionKind};
+use crate::i18n::gettext;
+use crate::library::unix_now;
+use crate::settings::Settings;
+
+use super::load_game_image;
+
+/// The start screen: rows of popular, new and short games, and the games viewed last. Shows
+/// what was cached last time at once and fetches sections that went stale in the background.
+#[derive(Clone)]
+pub struct HomePage {
+    pub page: gtk::ScrolledWindow,
+    sections: gtk::Box,
+    feed: Rc<RefCell<HomeFeed>>,
+    client: Arc<HltbClient>,
+    settings: Settings,
+    on_open: Rc<dyn Fn(&Game)>,
+}
+
+impl HomePage {
+    /// `on_open` shows a game picked from one of the sections
+    pub fn new(client: Arc<HltbClient>, settings: Settings, on_open: impl Fn(&Game) + 'static) -> Self {
+        let hint = adw::StatusPage::builder()
+            .icon_name("system-search-symbolic")
+            .title(gettext("Search for a game"))
+            .description(gettext("Enter a game title to see completion times"))
+            .css_classes(vec!["compact"])
+            .build();
+
+        let sections = gtk::Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(24)
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(12)
+            .margin_bottom(24)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        content.append(&hint);
+        content.append(&sections);
+
+        let clamp = adw::Clamp::builder()
+            .maximum_size(1000)
+            .child(&content)
+            .build();
+        let page = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vexpand(true)
+            .child(&clamp)
+            .build();
+
+        let home_page = Self {
+            page,
+            sections,
+            feed: Rc::new(RefCell::new(HomeFeed::load_from(HomeFeed::default_path()))),
+            client,
+            settings,
+            on_open: Rc::new(on_open),
+        };
+        home_page.render();
+        home_page.refresh();
+        home_page
+    }
+
+    /// Fetches the sections that are missing or stale. Sections that can't be fetched, like
+    /// when offline, keep showing what was cached.
+    pub fn refresh(&self) {
+        let now = unix_now();
+        let year = glib::DateTime::now_local().map(|now| now.year()).unwrap_or(1970);
+        for kind in SectionKind::FETCHED {
+            if !self.feed.borrow().needs_refresh(kind, now) {
+                continue;
+            }
+            let Some(options) = kind.search_options(year) else {
+                continue;
+            };
+
+            let home_page = self.clone();
+            glib::spawn_future_local(async move {
+                match home_page.client.search_with_options("", options).await {
+                    Ok(games) => {
+                        home_page.feed.borrow_mut().set_section(kind, kind.pick(games), unix_now());
+                        home_page.save();
+                        home_page.render();
+                    }
+                    Err(e) => eprintln!("Failed to fetch home section {:?}: {}", kind, e),
+                }
+            });
+        }
+    }
+
+    /// Adds `game` to the recently viewed games
+    pub fn record_viewed(&self, game: &Game) {
+        self.feed.borrow_mut().record_viewed(game);
+        self.save();
+        self.render();
+    }
+
+    fn save(&self) {
+        if let Err(e) = self.feed.borrow().save() {
+            eprintln!("Failed to save home feed: {}", e);
+        }
+    }
+
+    fn render(&self) {
+        while let Some(child) = self.sections.first_child() {
+            self.sections.remove(&child);
+        }
+
+        let feed = self.feed.borrow();
+        self.append_section(SectionKind::RecentlyViewed, feed.recently_viewed());
+        for kind in SectionKind::FETCHED {
+            if let Some(section) = feed.section(kind) {
+                self.append_section(kind, &section.games);
+            }
+        }
+    }
+
+    fn append_section(&self, kind: SectionKind, games: &[Game]) {
+        if games.is_empty() {
+            return;
+        }
+
+        let title = gtk::Label::builder()
+            .label(kind.title())
+            .xalign(0.0)
+            .css_classes(vec!["title-4"])
+            .build();
+
+        let tiles = gtk::Box::new(Orientation::Horizontal, 12);
+        for game in games {
+            tiles.append(&self.create_tile(game));
+        }
+        let scrolled = gtk::ScrolledWindow::builder()
+            .vscrollbar_policy(gtk::PolicyType::Never)
+            .hscrollbar_policy(gtk::PolicyType::Automatic)
+            .child(&tiles)
+            .build();
+
+        let section = gtk::Box::new(Orientation::Vertical, 6);
+        section.append(&title);
+        section.append(&scrolled);
+        self.sections.append(&section);
+    }
+
+    fn create_tile(&self, game: &Game) -> gtk::Button {
+        let cover = gtk::Picture::builder()
+            .width_request(160)
+            .height_request(96)
+            .can_shrink(true)
+            .content_fit(gtk::ContentFit::Cover)
+            .build();
+        if !game.game_image.is_empty() {
+            load_game_image(&cover, game.image_url());
+        }
+
+        let name = gtk::Label::builder()
+            .label(&game.game_name)
+            .xalign(0.0)
+            .max_width_chars(18)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .build();
+
+        let tile_box = gtk::Box::new(Orientation::Vertical, 6);
+        tile_box.append(&cover);
+        tile_box.append(&name);
+
+        let time_category = self.settings.time_category();
+        if let Some(hours) = time_category.hours(game) {
+            tile_box.append(
+                &gtk::Label::builder()
+                    .label(self.settings.time_formatter().format(hours))
+                    .xalign(0.0)
+                    .css_classes(vec!["dim-label", "caption"])
+                    .build(),
+            );
+        }
+
+        let tile = gtk::Button::builder()
+            .child(&tile_box)
+            .tooltip_text(&game.game_name)
+            .css_classes(vec!["flat"])
+            .build();
+        let on_open = self.on_open.clone();
+        let game = game.clone();
+        tile.connect_clicked(move |_| on_open(&game));
+        tile
+    }
+}
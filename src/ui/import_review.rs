@@ -0,0 +1,272 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use std::sync::{Arc, Mutex};
+
+use crate::api::HltbClient;
+use crate::i18n::{gettext, gettext_f, ngettext_f};
+use crate::import::{self, ImportedGame, StagedMatch};
+use crate::library::LibraryEntry;
+use crate::matcher;
+use crate::time_format::TimeFormatter;
+
+use super::match_percentage;
+use super::match_picker::present_match_picker;
+
+/// Shows the staged matches of an import and lets the user pick which ones to add.
+/// `on_confirm` receives the library entries for the selected matches.
+pub fn present_review_dialog(
+    parent: &impl IsA<gtk::Widget>,
+    title: &str,
+    staged: Vec<StagedMatch>,
+    client: Arc<HltbClient>,
+    time_formatter: TimeFormatter,
+    on_confirm: impl Fn(Vec<LibraryEntry>) + 'static,
+) {
+    let add_button = gtk::Button::builder()
+        .css_classes(vec!["suggested-action", "pill"])
+        .halign(gtk::Align::Center)
+        .margin_top(12)
+        .margin_bottom(12)
+        .build();
+
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(vec!["boxed-list"])
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .valign(gtk::Align::Start)
+        .build();
+
+    let staged = Arc::new(Mutex::new(staged));
+    update_add_button(&add_button, &staged);
+    fill_list(&list_box, &staged, &add_button, &time_formatter);
+
+    // Games whose lookup failed, like when HLTB couldn't be reached, can be looked up again
+    let retry_button = gtk::Button::builder()
+        .label(gettext("Retry Failed"))
+        .visible(has_failures(&staged))
+        .build();
+
+    let staged_clone = staged.clone();
+    let list_box_clone = list_box.clone();
+    let add_button_clone = add_button.clone();
+    retry_button.connect_clicked(move |button| {
+        let failed: Vec<(usize, ImportedGame)> = match staged_clone.lock() {
+            Ok(matches) => matches
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.failed.is_some())
+                .map(|(index, m)| (index, m.imported_game.clone()))
+                .collect(),
+            Err(_) => return,
+        };
+        button.set_sensitive(false);
+        button.set_label(&gettext("Retrying…"));
+
+        let client = client.clone();
+        let button = button.clone();
+        let staged_clone = staged_clone.clone();
+        let list_box_clone = list_box_clone.clone();
+        let add_button_clone = add_button_clone.clone();
+        let time_formatter = time_formatter.clone();
+        glib::spawn_future_local(async move {
+            let (indexes, games): (Vec<usize>, Vec<ImportedGame>) = failed.into_iter().unzip();
+            let restaged = import::stage_imported_games(&client, games, |_, _| {}).await;
+            if let Ok(mut matches) = staged_clone.lock() {
+                for (index, staged_match) in indexes.into_iter().zip(restaged) {
+                    matches[index] = staged_match;
+                }
+            }
+
+            fill_list(&list_box_clone, &staged_clone, &add_button_clone, &time_formatter);
+            update_add_button(&add_button_clone, &staged_clone);
+            button.set_label(&gettext("Retry Failed"));
+            button.set_sensitive(true);
+            button.set_visible(has_failures(&staged_clone));
+        });
+    });
+
+    let scrolled = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vexpand(true)
+        .child(&list_box)
+        .build();
+
+    let header_bar = adw::HeaderBar::new();
+    header_bar.pack_start(&retry_button);
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&scrolled));
+    toolbar_view.add_bottom_bar(&add_button);
+
+    let dialog = adw::Dialog::builder()
+        .title(title)
+        .content_width(560)
+        .content_height(640)
+        .child(&toolbar_view)
+        .build();
+
+    let dialog_clone = dialog.clone();
+    add_button.connect_clicked(move |_| {
+        let entries = match staged.lock() {
+            Ok(matches) => matches
+                .iter()
+                .filter(|m| m.include)
+                .filter_map(|m| m.to_library_entry())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        on_confirm(entries);
+        dialog_clone.close();
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Lists a row for every staged match, replacing the rows already in `list_box`
+fn fill_list(
+    list_box: &gtk::ListBox,
+    staged: &Arc<Mutex<Vec<StagedMatch>>>,
+    add_button: &gtk::Button,
+    time_formatter: &TimeFormatter,
+) {
+    while let Some(child) = list_box.first_child() {
+        list_box.remove(&child);
+    }
+
+    let Ok(matches) = staged.lock() else {
+        return;
+    };
+    for (index, staged_match) in matches.iter().enumerate() {
+        let row = adw::ActionRow::builder()
+            .title(&staged_match.imported_game.title)
+            .subtitle(review_subtitle(staged_match))
+            .subtitle_lines(2)
+            .use_markup(false)
+            .build();
+
+        let check = gtk::CheckButton::builder()
+            .active(staged_match.include)
+            .sensitive(staged_match.candidate.is_some())
+            .valign(gtk::Align::Center)
+            .build();
+        row.add_prefix(&check);
+        row.set_activatable_widget(Some(&check));
+
+        let staged_clone = staged.clone();
+        let add_button_clone = add_button.clone();
+        check.connect_toggled(move |check| {
+            if let Ok(mut matches) = staged_clone.lock() {
+                matches[index].include = check.is_active();
+            }
+            update_add_button(&add_button_clone, &staged_clone);
+        });
+
+        if staged_match.candidates.len() > 1 {
+            let choose_button = gtk::Button::builder()
+                .label(gettext("Choose…"))
+                .valign(gtk::Align::Center)
+                .build();
+            if !staged_match.include {
+                choose_button.add_css_class("suggested-action");
+            } else {
+                choose_button.add_css_class("flat");
+            }
+            row.add_suffix(&choose_button);
+
+            let staged_clone = staged.clone();
+            let row_clone = row.clone();
+            let check_clone = check.clone();
+            let time_formatter = time_formatter.clone();
+            choose_button.connect_clicked(move |button| {
+                let (query, candidates) = match staged_clone.lock() {
+                    Ok(matches) => (
+                        matches[index].imported_game.title.clone(),
+                        matches[index].candidates.clone(),
+                    ),
+                    Err(_) => return,
+                };
+
+                let staged_clone = staged_clone.clone();
+                let row_clone = row_clone.clone();
+                let check_clone = check_clone.clone();
+                present_match_picker(button, &query, &candidates, &time_formatter, move |game| {
+                    if let Ok(mut matches) = staged_clone.lock() {
+                        let staged_match = &mut matches[index];
+                        staged_match.candidate = Some(game);
+                        // The user's own pick is certain
+                        staged_match.score = 1.0;
+                        staged_match.include = true;
+                        row_clone.set_subtitle(&review_subtitle(staged_match));
+                    }
+                    // Toggling the check updates the selection and the add button
+                    check_clone.set_active(true);
+                });
+            });
+        }
+
+        list_box.append(&row);
+    }
+}
+
+fn has_failures(staged: &Arc<Mutex<Vec<StagedMatch>>>) -> bool {
+    staged.lock().is_ok_and(|matches| matches.iter().any(|m| m.failed.is_some()))
+}
+
+fn review_subtitle(staged_match: &StagedMatch) -> String {
+    let imported_game = &staged_match.imported_game;
+
+    let mut details = vec![imported_game.platform.clone()];
+    if let Some(status) = imported_game.status {
+        details.push(status.label());
+    }
+    if let Some(year) = imported_game.year {
+        details.push(year.to_string());
+    }
+    if imported_game.installed {
+        details.push(gettext("Installed"));
+    }
+
+    match &staged_match.candidate {
+        // Ambiguous matches start out unselected until the user picks one
+        Some(game) if !staged_match.include && matcher::is_ambiguous(&staged_match.candidates) => format!(
+            "{}\n{}",
+            gettext_f("→ {game}? Several games match, choose one", &[("game", &game.game_name)]),
+            details.join(" · ")
+        ),
+        Some(game) => format!(
+            "{}\n{}",
+            gettext_f(
+                "→ {game} ({match})",
+                &[("game", &game.game_name), ("match", &match_percentage(staged_match.score))],
+            ),
+            details.join(" · ")
+        ),
+        None => match &staged_match.failed {
+            Some(reason) => format!(
+                "{}\n{}",
+                gettext_f("Lookup failed: {reason}", &[("reason", reason)]),
+                details.join(" · ")
+            ),
+            None => format!("{}\n{}", gettext("No match on HowLongToBeat"), details.join(" · ")),
+        },
+    }
+}
+
+fn update_add_button(button: &gtk::Button, staged: &Arc<Mutex<Vec<StagedMatch>>>) {
+    let selected = staged
+        .lock()
+        .map(|matches| matches.iter().filter(|m| m.include).count())
+        .unwrap_or(0);
+
+    button.set_label(&ngettext_f(
+        "Add {count} Game",
+        "Add {count} Games",
+        selected,
+        &[("count", &selected.to_string())],
+    ));
+    button.set_sensitive(selected > 0);
+}
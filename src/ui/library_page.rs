@@ -0,0 +1,130 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use gtk::Orientation;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::i18n::{gettext, gettext_f};
+use crate::library::{Library, LibraryEntry};
+use crate::settings::{Settings, TimeCategory};
+use crate::time_format::TimeFormatter;
+
+type RefreshedCallback = Rc<RefCell<Option<Rc<dyn Fn(&Library)>>>>;
+
+/// Widgets of the library page
+#[derive(Clone)]
+pub struct LibraryPage {
+    pub stack: gtk::Stack,
+    list_box: gtk::ListBox,
+    settings: Settings,
+    refreshed: RefreshedCallback,
+}
+
+impl LibraryPage {
+    pub fn new(settings: Settings) -> Self {
+        let empty_page = adw::StatusPage::builder()
+            .icon_name("applications-games-symbolic")
+            .title(gettext("Your Library Is Empty"))
+            .description(gettext("Import your Steam, Lutris or Heroic library from the main menu"))
+            .build();
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(vec!["boxed-list"])
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .valign(gtk::Align::Start)
+            .build();
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .child(&list_box)
+            .build();
+
+        let stack = gtk::Stack::new();
+        stack.add_named(&empty_page, Some("empty"));
+        stack.add_named(&scrolled, Some("entries"));
+        stack.set_visible_child_name("empty");
+
+        Self {
+            stack,
+            list_box,
+            settings,
+            refreshed: Rc::default(),
+        }
+    }
+
+    /// Calls `callback` with the library every time the page is rebuilt from it
+    pub fn connect_refreshed(&self, callback: impl Fn(&Library) + 'static) {
+        *self.refreshed.borrow_mut() = Some(Rc::new(callback));
+    }
+
+    /// Rebuilds the list from the current library contents
+    pub fn refresh(&self, library: &Library) {
+        let callback = self.refreshed.borrow().clone();
+        if let Some(callback) = callback {
+            callback(library);
+        }
+
+        while let Some(child) = self.list_box.first_child() {
+            self.list_box.remove(&child);
+        }
+
+        if library.is_empty() {
+            self.stack.set_visible_child_name("empty");
+            return;
+        }
+
+        let mut entries: Vec<&LibraryEntry> = library.entries().iter().collect();
+        entries.sort_by_key(|entry| entry.game.game_name.to_lowercase());
+
+        let time_formatter = self.settings.time_formatter();
+        for entry in entries {
+            self.list_box.append(&create_library_row(entry, &time_formatter));
+        }
+        self.stack.set_visible_child_name("entries");
+    }
+}
+
+fn create_library_row(entry: &LibraryEntry, time_formatter: &TimeFormatter) -> adw::ActionRow {
+    let mut subtitle = vec![entry.status.label()];
+    if !entry.platform.is_empty() {
+        subtitle.push(entry.platform.clone());
+    }
+    if entry.playtime_minutes > 0 {
+        subtitle.push(gettext_f(
+            "{time} played",
+            &[("time", &time_formatter.format(entry.playtime_hours()))],
+        ));
+    }
+
+    let row = adw::ActionRow::builder()
+        .title(&entry.game.game_name)
+        .subtitle(subtitle.join(" · "))
+        .build();
+
+    if entry.game.comp_main > 0 {
+        let time_box = gtk::Box::new(Orientation::Vertical, 0);
+        time_box.set_valign(gtk::Align::Center);
+
+        let time_label = gtk::Label::builder()
+            .label(time_formatter.format(entry.game.main_story_hours()))
+            .halign(gtk::Align::End)
+            .build();
+        let caption = gtk::Label::builder()
+            .label(TimeCategory::Main.label())
+            .halign(gtk::Align::End)
+            .css_classes(vec!["dim-label", "caption"])
+            .build();
+
+        time_box.append(&time_label);
+        time_box.append(&caption);
+        row.add_suffix(&time_box);
+    }
+
+    row
+}
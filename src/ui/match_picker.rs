@@ -0,0 +1,89 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use std::rc::Rc;
+
+use crate::api::Game;
+use crate::i18n::gettext_f;
+use crate::settings::TimeCategory;
+use crate::time_format::TimeFormatter;
+
+use super::match_percentage;
+
+/// Lets the user pick the right game for `query` from the ranked candidates
+pub fn present_match_picker(
+    parent: &impl IsA<gtk::Widget>,
+    query: &str,
+    candidates: &[(Game, f64)],
+    time_formatter: &TimeFormatter,
+    on_pick: impl Fn(Game) + 'static,
+) {
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(vec!["boxed-list"])
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .valign(gtk::Align::Start)
+        .build();
+
+    let scrolled = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vexpand(true)
+        .child(&list_box)
+        .build();
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&adw::HeaderBar::new());
+    toolbar_view.set_content(Some(&scrolled));
+
+    let dialog = adw::Dialog::builder()
+        .title(gettext_f("Choose a Match for “{query}”", &[("query", query)]))
+        .content_width(480)
+        .content_height(480)
+        .child(&toolbar_view)
+        .build();
+
+    let on_pick = Rc::new(on_pick);
+    for (game, score) in candidates {
+        let row = adw::ActionRow::builder()
+            .title(&game.game_name)
+            .subtitle(candidate_subtitle(game, *score, time_formatter))
+            .use_markup(false)
+            .activatable(true)
+            .build();
+        row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+
+        let game = game.clone();
+        let on_pick = on_pick.clone();
+        let dialog_clone = dialog.clone();
+        row.connect_activated(move |_| {
+            on_pick(game.clone());
+            dialog_clone.close();
+        });
+        list_box.append(&row);
+    }
+
+    dialog.present(Some(parent));
+}
+
+fn candidate_subtitle(game: &Game, score: f64, time_formatter: &TimeFormatter) -> String {
+    let mut details = Vec::new();
+    if game.release_world > 0 {
+        details.push(game.release_world.to_string());
+    }
+    if !game.profile_platform.is_empty() {
+        details.push(game.profile_platform.clone());
+    }
+    if game.comp_main > 0 {
+        details.push(gettext_f(
+            "{category} {time}",
+            &[
+                ("category", &TimeCategory::Main.label()),
+                ("time", &time_formatter.format(game.main_story_hours())),
+            ],
+        ));
+    }
+    details.push(match_percentage(score));
+    details.join(" · ")
+}
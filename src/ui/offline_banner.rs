@@ -0,0 +1,84 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use gtk::gio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::i18n::{gettext, gettext_f};
+use crate::library::unix_now;
+use crate::offline_cache;
+
+type RetryCallback = Rc<RefCell<Option<Rc<dyn Fn()>>>>;
+
+/// The banner over the pages saying what's on screen came from the offline cache, and how
+/// old it is, or that nothing could be loaded. Retries by itself once the network monitor
+/// reports a connection again.
+#[derive(Clone)]
+pub struct OfflineBanner {
+    pub banner: adw::Banner,
+    network_monitor: gio::NetworkMonitor,
+    retry: RetryCallback,
+}
+
+impl OfflineBanner {
+    pub fn new() -> Self {
+        let offline_banner = Self {
+            banner: adw::Banner::builder().button_label(gettext("Retry")).build(),
+            network_monitor: gio::NetworkMonitor::default(),
+            retry: Rc::default(),
+        };
+
+        let offline_banner_clone = offline_banner.clone();
+        offline_banner.banner.connect_button_clicked(move |_| offline_banner_clone.retry());
+
+        let offline_banner_clone = offline_banner.clone();
+        offline_banner.network_monitor.connect_network_changed(move |_, available| {
+            if available && offline_banner_clone.banner.is_revealed() {
+                offline_banner_clone.retry();
+            }
+        });
+
+        offline_banner
+    }
+
+    /// Whether the network monitor thinks HowLongToBeat can be reached
+    pub fn online(&self) -> bool {
+        self.network_monitor.is_network_available()
+    }
+
+    /// Shows the banner for data cached at `cached_at`, with `retry` loading it again, or
+    /// hides it for fresh data
+    pub fn update(&self, cached_at: Option<u64>, retry: impl Fn() + 'static) {
+        let Some(fetched) = cached_at else {
+            self.hide();
+            return;
+        };
+        self.banner.set_title(&gettext_f(
+            "Offline: showing what was saved {age}",
+            &[("age", &offline_cache::describe_age(fetched, unix_now()))],
+        ));
+        *self.retry.borrow_mut() = Some(Rc::new(retry));
+        self.banner.set_revealed(true);
+    }
+
+    /// Shows the banner for a request that failed with nothing cached, with `retry` making it
+    /// again, by hand or once the network is back
+    pub fn show_unreachable(&self, retry: impl Fn() + 'static) {
+        self.banner.set_title(&gettext("Offline: HowLongToBeat can't be reached"));
+        *self.retry.borrow_mut() = Some(Rc::new(retry));
+        self.banner.set_revealed(true);
+    }
+
+    pub fn hide(&self) {
+        self.banner.set_revealed(false);
+        self.retry.borrow_mut().take();
+    }
+
+    fn retry(&self) {
+        let retry = self.retry.borrow().clone();
+        self.hide();
+        if let Some(retry) = retry {
+            retry();
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use std::time::Duration;
+
+use crate::i18n::gettext;
+use crate::settings::{Settings, TimeCategory, ViewMode};
+use crate::time_format::TimeFormat;
+
+/// Presents the preferences window; changes are saved as they're made
+pub fn present_preferences(window: &adw::ApplicationWindow, settings: &Settings) {
+    let preferences = adw::PreferencesWindow::builder()
+        .title(gettext("Preferences"))
+        .transient_for(window)
+        .modal(true)
+        .search_enabled(false)
+        .build();
+
+    let page = adw::PreferencesPage::new();
+
+    // Results
+    let results_group = adw::PreferencesGroup::builder().title(gettext("Search Results")).build();
+
+    let view_row = adw::ComboRow::builder()
+        .title(gettext("Layout"))
+        .model(&gtk::StringList::new(&[&gettext("List"), &gettext("Grid")]))
+        .selected(match settings.view_mode() {
+            ViewMode::List => 0,
+            ViewMode::Grid => 1,
+        })
+        .build();
+    let settings_clone = settings.clone();
+    view_row.connect_selected_notify(move |row| {
+        settings_clone.set_view_mode(if row.selected() == 1 { ViewMode::Grid } else { ViewMode::List });
+    });
+    results_group.add(&view_row);
+
+    let labels: Vec<String> = TimeCategory::ALL.iter().map(|category| category.label()).collect();
+    let category_row = adw::ComboRow::builder()
+        .title(gettext("Highlighted Time"))
+        .subtitle(gettext("Shown on grid cards and in the GNOME Shell search"))
+        .model(&gtk::StringList::new(&labels.iter().map(String::as_str).collect::<Vec<_>>()))
+        .selected(
+            TimeCategory::ALL
+                .iter()
+                .position(|category| *category == settings.time_category())
+                .unwrap_or(0) as u32,
+        )
+        .build();
+    let settings_clone = settings.clone();
+    category_row.connect_selected_notify(move |row| {
+        if let Some(category) = TimeCategory::ALL.get(row.selected() as usize) {
+            settings_clone.set_time_category(*category);
+        }
+    });
+    results_group.add(&category_row);
+
+    page.add(&results_group);
+
+    // Completion times
+    let times_group = adw::PreferencesGroup::builder().title(gettext("Completion Times")).build();
+
+    let labels: Vec<String> = TimeFormat::ALL.iter().map(|format| format.label()).collect();
+    let format_row = adw::ComboRow::builder()
+        .title(gettext("Format"))
+        .model(&gtk::StringList::new(&labels.iter().map(String::as_str).collect::<Vec<_>>()))
+        .selected(
+            TimeFormat::ALL
+                .iter()
+                .position(|format| *format == settings.time_format())
+                .unwrap_or(0) as u32,
+        )
+        .build();
+    times_group.add(&format_row);
+
+    let evening_row = adw::SpinRow::builder()
+        .title(gettext("Evening Length"))
+        .subtitle(gettext("Hours of play in an evening"))
+        .sensitive(settings.time_format() == TimeFormat::Evenings)
+        .adjustment(&gtk::Adjustment::new(
+            settings.evening_length() as f64,
+            1.0,
+            12.0,
+            1.0,
+            1.0,
+            0.0,
+        ))
+        .build();
+    let settings_clone = settings.clone();
+    evening_row.connect_value_notify(move |row| {
+        settings_clone.set_evening_length(row.value() as u32);
+    });
+    times_group.add(&evening_row);
+
+    let settings_clone = settings.clone();
+    let evening_row_clone = evening_row.clone();
+    format_row.connect_selected_notify(move |row| {
+        if let Some(format) = TimeFormat::ALL.get(row.selected() as usize) {
+            settings_clone.set_time_format(*format);
+            evening_row_clone.set_sensitive(*format == TimeFormat::Evenings);
+        }
+    });
+    page.add(&times_group);
+
+    // Searching
+    let search_group = adw::PreferencesGroup::builder().title(gettext("Searching")).build();
+
+    let delay_row = adw::SpinRow::builder()
+        .title(gettext("Search Delay"))
+        .subtitle(gettext("Milliseconds to wait after typing before searching"))
+        .adjustment(&gtk::Adjustment::new(
+            settings.search_delay().as_millis() as f64,
+            0.0,
+            2000.0,
+            50.0,
+            100.0,
+            0.0,
+        ))
+        .build();
+    let settings_clone = settings.clone();
+    delay_row.connect_value_notify(move |row| {
+        settings_clone.set_search_delay(Duration::from_millis(row.value() as u64));
+    });
+    search_group.add(&delay_row);
+
+    let history_row = adw::SpinRow::builder()
+        .title(gettext("History Size"))
+        .subtitle(gettext("Searches and opened games to suggest again, 0 to keep none"))
+        .adjustment(&gtk::Adjustment::new(
+            settings.history_size() as f64,
+            0.0,
+            500.0,
+            10.0,
+            50.0,
+            0.0,
+        ))
+        .build();
+    let settings_clone = settings.clone();
+    history_row.connect_value_notify(move |row| {
+        settings_clone.set_history_size(row.value() as u32);
+    });
+    search_group.add(&history_row);
+    page.add(&search_group);
+
+    // Cache
+    let cache_group = adw::PreferencesGroup::builder()
+        .title(gettext("Cache"))
+        .description(gettext("Covers for the GNOME Shell search, and results kept for offline use"))
+        .build();
+
+    let size_row = adw::SpinRow::builder()
+        .title(gettext("Maximum Size"))
+        .subtitle(gettext("In megabytes"))
+        .adjustment(&gtk::Adjustment::new(
+            settings.cache_max_size_mb() as f64,
+            0.0,
+            10240.0,
+            10.0,
+            100.0,
+            0.0,
+        ))
+        .build();
+    let settings_clone = settings.clone();
+    size_row.connect_value_notify(move |row| {
+        settings_clone.set_cache_max_size_mb(row.value() as u32);
+    });
+    cache_group.add(&size_row);
+
+    let age_row = adw::SpinRow::builder()
+        .title(gettext("Keep For"))
+        .subtitle(gettext("In days"))
+        .adjustment(&gtk::Adjustment::new(
+            settings.cache_max_age_days() as f64,
+            1.0,
+            365.0,
+            1.0,
+            7.0,
+            0.0,
+        ))
+        .build();
+    let settings_clone = settings.clone();
+    age_row.connect_value_notify(move |row| {
+        settings_clone.set_cache_max_age_days(row.value() as u32);
+    });
+    cache_group.add(&age_row);
+    page.add(&cache_group);
+
+    preferences.add(&page);
+    preferences.present();
+}
@@ -0,0 +1,184 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::i18n::{gettext, gettext_f, ngettext_f};
+use crate::refine::{Filters, SortKey};
+use crate::reliability::FAIR_RATINGS;
+
+type ChangedCallback = Rc<RefCell<Option<Rc<dyn Fn()>>>>;
+
+/// The sort menu and filter chips above the search results. They rearrange the results
+/// already loaded; nothing is searched again.
+#[derive(Clone)]
+pub struct ResultControls {
+    pub bar: gtk::Box,
+    sort: Rc<Cell<SortKey>>,
+    filters: Rc<RefCell<Filters>>,
+    platform_button: gtk::MenuButton,
+    platform_list: gtk::Box,
+    changed: ChangedCallback,
+}
+
+impl ResultControls {
+    pub fn new() -> Self {
+        let labels: Vec<String> = SortKey::ALL.iter().map(|key| key.label()).collect();
+        let sort_dropdown = gtk::DropDown::builder()
+            .model(&gtk::StringList::new(&labels.iter().map(String::as_str).collect::<Vec<_>>()))
+            .tooltip_text(gettext("Sort Results"))
+            .build();
+
+        let untimed_chip = gtk::ToggleButton::builder()
+            .label(gettext("Has Times"))
+            .tooltip_text(gettext("Hide games nobody has submitted a time for"))
+            .css_classes(vec!["pill"])
+            .build();
+        let low_sample_chip = gtk::ToggleButton::builder()
+            .label(gettext("Enough Ratings"))
+            .tooltip_text(gettext_f(
+                "Hide times submitted by fewer than {count} players",
+                &[("count", &FAIR_RATINGS.to_string())],
+            ))
+            .css_classes(vec!["pill"])
+            .build();
+
+        let platform_list = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(6)
+            .margin_end(6)
+            .build();
+        let platform_scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .propagate_natural_height(true)
+            .max_content_height(320)
+            .child(&platform_list)
+            .build();
+        let platform_button = gtk::MenuButton::builder()
+            .label(gettext("All Platforms"))
+            .popover(&gtk::Popover::builder().child(&platform_scrolled).build())
+            .css_classes(vec!["pill"])
+            .build();
+
+        let chips = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        chips.append(&untimed_chip);
+        chips.append(&low_sample_chip);
+        chips.append(&platform_button);
+
+        let bar = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(12)
+            .margin_end(12)
+            .visible(false)
+            .build();
+        bar.append(&chips);
+        let spacer = gtk::Box::builder().hexpand(true).build();
+        bar.append(&spacer);
+        bar.append(&sort_dropdown);
+
+        let controls = Self {
+            bar,
+            sort: Rc::default(),
+            filters: Rc::default(),
+            platform_button,
+            platform_list,
+            changed: Rc::default(),
+        };
+
+        let controls_clone = controls.clone();
+        sort_dropdown.connect_selected_notify(move |dropdown| {
+            let sort = SortKey::ALL.get(dropdown.selected() as usize).copied().unwrap_or_default();
+            controls_clone.sort.set(sort);
+            controls_clone.notify();
+        });
+
+        let controls_clone = controls.clone();
+        untimed_chip.connect_toggled(move |chip| {
+            controls_clone.filters.borrow_mut().hide_untimed = chip.is_active();
+            controls_clone.notify();
+        });
+
+        let controls_clone = controls.clone();
+        low_sample_chip.connect_toggled(move |chip| {
+            controls_clone.filters.borrow_mut().hide_low_sample = chip.is_active();
+            controls_clone.notify();
+        });
+
+        controls
+    }
+
+    pub fn sort(&self) -> SortKey {
+        self.sort.get()
+    }
+
+    pub fn filters(&self) -> Filters {
+        self.filters.borrow().clone()
+    }
+
+    /// Runs `callback` whenever the sort or a filter changes
+    pub fn connect_changed(&self, callback: impl Fn() + 'static) {
+        *self.changed.borrow_mut() = Some(Rc::new(callback));
+    }
+
+    /// Offers `platforms`, those of newly loaded results, in the platform filter. Platforms
+    /// picked before stay picked if the new results have them too, like when local hits are
+    /// replaced by HowLongToBeat's results.
+    pub fn set_platforms(&self, platforms: &[String]) {
+        self.filters.borrow_mut().platforms.retain(|picked| platforms.contains(picked));
+        self.update_platform_label();
+        while let Some(child) = self.platform_list.first_child() {
+            self.platform_list.remove(&child);
+        }
+
+        for platform in platforms {
+            let check = gtk::CheckButton::builder()
+                .label(platform.as_str())
+                .active(self.filters.borrow().platforms.contains(platform))
+                .build();
+            let controls = self.clone();
+            let platform = platform.clone();
+            check.connect_toggled(move |check| {
+                {
+                    let mut filters = controls.filters.borrow_mut();
+                    if check.is_active() {
+                        filters.platforms.insert(platform.clone());
+                    } else {
+                        filters.platforms.remove(&platform);
+                    }
+                }
+                controls.update_platform_label();
+                controls.notify();
+            });
+            self.platform_list.append(&check);
+        }
+        self.platform_button.set_sensitive(!platforms.is_empty());
+    }
+
+    fn update_platform_label(&self) {
+        let filters = self.filters.borrow();
+        let label = match filters.platforms.len() {
+            0 => gettext("All Platforms"),
+            1 => filters.platforms.iter().next().cloned().unwrap_or_default(),
+            count => ngettext_f(
+                "{count} Platforms",
+                "{count} Platforms",
+                count,
+                &[("count", &count.to_string())],
+            ),
+        };
+        self.platform_button.set_label(&label);
+    }
+
+    fn notify(&self) {
+        let callback = self.changed.borrow().clone();
+        if let Some(callback) = callback {
+            callback();
+        }
+    }
+}
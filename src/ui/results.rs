@@ -0,0 +1,266 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use gtk::Orientation;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::api::{FailureKind, Game};
+use crate::i18n::{gettext, ngettext_f};
+use crate::refine;
+use crate::settings::Settings;
+
+use super::comparison::CompareSelection;
+use super::result_controls::ResultControls;
+use super::{create_game_card, create_game_row};
+
+/// The results page: the same games in the list and the grid, under the sort and filter
+/// controls. Keeps the games as loaded so the controls can rearrange them.
+#[derive(Clone)]
+pub struct ResultViews {
+    pub list_box: gtk::ListBox,
+    pub flow_box: gtk::FlowBox,
+    pub controls: ResultControls,
+    /// Over the window's pages, for errors that leave the results on screen
+    toast_overlay: adw::ToastOverlay,
+    games: Rc<RefCell<Vec<Game>>>,
+    /// Whether the games shown are local hits, with HowLongToBeat still being searched
+    searching: Rc<Cell<bool>>,
+    /// Counts the searches and discoveries started, so one that finishes late can tell it has
+    /// been overtaken
+    requests: Rc<Cell<u64>>,
+    settings: Settings,
+    compare_selection: CompareSelection,
+}
+
+impl ResultViews {
+    pub fn new(
+        list_box: gtk::ListBox,
+        flow_box: gtk::FlowBox,
+        toast_overlay: adw::ToastOverlay,
+        settings: Settings,
+        compare_selection: CompareSelection,
+    ) -> Self {
+        let views = Self {
+            list_box,
+            flow_box,
+            controls: ResultControls::new(),
+            toast_overlay,
+            games: Rc::default(),
+            searching: Rc::default(),
+            requests: Rc::default(),
+            settings,
+            compare_selection,
+        };
+
+        // Rearrange the loaded results without searching again
+        let views_clone = views.clone();
+        views.controls.connect_changed(move || views_clone.render());
+
+        views
+    }
+
+    /// Starts a new search or discovery for the views, overtaking any still running
+    pub fn start_request(&self) -> u64 {
+        let request = self.requests.get() + 1;
+        self.requests.set(request);
+        request
+    }
+
+    /// Whether `request` is still the latest one started, and may show its results
+    pub fn is_current(&self, request: u64) -> bool {
+        self.requests.get() == request
+    }
+
+    /// Empties both views and forgets the loaded games
+    pub fn clear(&self) {
+        self.games.borrow_mut().clear();
+        self.searching.set(false);
+        self.controls.bar.set_visible(false);
+        self.remove_children();
+    }
+
+    pub fn show_loading(&self) {
+        self.clear();
+        let loading_row = adw::ActionRow::builder().title(gettext("Searching…")).build();
+        let spinner = gtk::Spinner::new();
+        spinner.start();
+        loading_row.add_suffix(&spinner);
+        self.list_box.append(&loading_row);
+    }
+
+    /// Shows freshly loaded games, in the order they came in unless the controls say otherwise.
+    /// Use [`Self::show_empty`] when there are none.
+    pub fn show_games(&self, games: Vec<Game>) {
+        self.searching.set(false);
+        self.set_games(games);
+    }
+
+    /// Shows games found without going online while HowLongToBeat is searched, with a spinner
+    /// after them until [`Self::show_games`] replaces them
+    pub fn show_local_hits(&self, games: Vec<Game>) {
+        self.searching.set(true);
+        self.set_games(games);
+    }
+
+    fn set_games(&self, games: Vec<Game>) {
+        self.controls.set_platforms(&refine::platforms(&games));
+        self.controls.bar.set_visible(true);
+        *self.games.borrow_mut() = games;
+        self.render();
+    }
+
+    /// Writes the loaded games again, like after the time format changed. Errors and empty
+    /// states have no times and stay as they are.
+    pub fn refresh(&self) {
+        if !self.games.borrow().is_empty() {
+            self.render();
+        }
+    }
+
+    /// Says what went wrong in both views, with `retry` to search again and the full error to
+    /// copy for a report. Games already on screen, like local hits, stay, and the error comes
+    /// as a toast instead.
+    pub fn show_error(&self, error: &anyhow::Error, retry: impl Fn() + 'static) {
+        let kind = FailureKind::of(error);
+        let retry: Rc<dyn Fn()> = Rc::new(retry);
+
+        if !self.games.borrow().is_empty() {
+            self.searching.set(false);
+            self.render();
+            let toast = adw::Toast::builder()
+                .title(kind.title())
+                .button_label(gettext("Retry"))
+                .priority(adw::ToastPriority::High)
+                .build();
+            toast.connect_button_clicked(move |_| retry());
+            self.toast_overlay.add_toast(toast);
+            return;
+        }
+
+        self.clear();
+        let details = format!("{:#}", error);
+        let row = gtk::ListBoxRow::builder()
+            .activatable(false)
+            .selectable(false)
+            .child(&self.error_state(kind, &details, &retry))
+            .build();
+        self.list_box.append(&row);
+        self.flow_box.insert(&self.error_state(kind, &details, &retry), -1);
+    }
+
+    fn error_state(&self, kind: FailureKind, details: &str, retry: &Rc<dyn Fn()>) -> adw::StatusPage {
+        let retry_button = gtk::Button::builder()
+            .label(gettext("Retry"))
+            .css_classes(vec!["pill", "suggested-action"])
+            .build();
+        let retry = retry.clone();
+        retry_button.connect_clicked(move |_| retry());
+
+        let copy_button = gtk::Button::builder()
+            .label(gettext("Copy Details"))
+            .css_classes(vec!["pill"])
+            .build();
+        let details = details.to_string();
+        let toast_overlay = self.toast_overlay.clone();
+        copy_button.connect_clicked(move |button| {
+            button.clipboard().set_text(&details);
+            toast_overlay.add_toast(adw::Toast::new(&gettext("Error details copied")));
+        });
+
+        let buttons = gtk::Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(12)
+            .halign(gtk::Align::Center)
+            .build();
+        buttons.append(&retry_button);
+        buttons.append(&copy_button);
+
+        adw::StatusPage::builder()
+            .icon_name(kind.icon_name())
+            .title(kind.title())
+            .description(kind.description())
+            .child(&buttons)
+            .build()
+    }
+
+    /// Says why there's nothing to show, in both views
+    pub fn show_empty(&self, title: &str, description: &str) {
+        self.clear();
+        self.append_empty_state(title, description);
+    }
+
+    fn append_empty_state(&self, title: &str, description: &str) {
+        let row = adw::ActionRow::builder()
+            .title(title)
+            .subtitle(description)
+            .build();
+        self.list_box.append(&row);
+
+        let status_page = adw::StatusPage::builder()
+            .icon_name("system-search-symbolic")
+            .title(title)
+            .description(description)
+            .build();
+        self.flow_box.insert(&status_page, -1);
+    }
+
+    fn remove_children(&self) {
+        self.compare_selection.forget_checks();
+        while let Some(child) = self.list_box.first_child() {
+            self.list_box.remove(&child);
+        }
+        while let Some(child) = self.flow_box.first_child() {
+            self.flow_box.remove(&child);
+        }
+    }
+
+    /// Replaces the results in both views with the loaded games, sorted and filtered
+    fn render(&self) {
+        self.remove_children();
+
+        let games = self.games.borrow();
+        let time_category = self.settings.time_category();
+        let time_formatter = self.settings.time_formatter();
+        let shown = refine::refine(&games, self.controls.sort(), &self.controls.filters(), time_category);
+
+        if shown.is_empty() {
+            let description = ngettext_f(
+                "The filters hide the only result",
+                "The filters hide all {count} results",
+                games.len(),
+                &[("count", &games.len().to_string())],
+            );
+            self.append_empty_state(&gettext("No Matching Results"), &description);
+            return;
+        }
+
+        for game in &shown {
+            // Add to list view
+            let row = create_game_row(game, &time_formatter, &self.compare_selection);
+            self.list_box.append(&row);
+
+            // Add to grid view
+            let card = create_game_card(game, time_category, &time_formatter, &self.compare_selection);
+            self.flow_box.insert(&card, -1);
+        }
+
+        if self.searching.get() {
+            let searching_row = adw::ActionRow::builder().title(gettext("Searching HowLongToBeat…")).build();
+            let spinner = gtk::Spinner::new();
+            spinner.start();
+            searching_row.add_suffix(&spinner);
+            self.list_box.append(&searching_row);
+
+            let spinner = gtk::Spinner::builder()
+                .spinning(true)
+                .width_request(32)
+                .height_request(32)
+                .halign(gtk::Align::Center)
+                .valign(gtk::Align::Center)
+                .tooltip_text(gettext("Searching HowLongToBeat…"))
+                .build();
+            let child = gtk::FlowBoxChild::builder().child(&spinner).focusable(false).build();
+            self.flow_box.insert(&child, -1);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use gtk::{gdk, glib};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::api::Game;
+use crate::i18n::{gettext, gettext_f};
+use crate::library::unix_now;
+use crate::search_history::{SearchHistory, Suggestion};
+use crate::settings::Settings;
+
+use super::load_game_image;
+
+/// Suggestions shown at once
+const MAX_SUGGESTIONS: usize = 8;
+
+/// The popover under the search entry suggesting earlier searches, opened games and games
+/// earlier searches found. Focus stays in the entry: Up and Down move through the
+/// suggestions, Enter picks one and Escape closes them.
+#[derive(Clone)]
+pub struct SearchSuggestions {
+    popover: gtk::Popover,
+    list: gtk::ListBox,
+    entry: gtk::SearchEntry,
+    history: Rc<RefCell<SearchHistory>>,
+    shown: Rc<RefCell<Vec<Suggestion>>>,
+    settings: Settings,
+    // Set while the entry's text is changed from here, so it doesn't pop the suggestions up again
+    filling: Rc<Cell<bool>>,
+    on_game: Rc<dyn Fn(&Game)>,
+}
+
+impl SearchSuggestions {
+    /// `on_game` opens a suggested game
+    pub fn new(entry: &gtk::SearchEntry, settings: Settings, on_game: impl Fn(&Game) + 'static) -> Self {
+        let limit = settings.history_size() as usize;
+        let history = SearchHistory::load_or_recover(SearchHistory::default_path(), limit);
+
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Single)
+            .focusable(false)
+            .css_classes(vec!["navigation-sidebar"])
+            .build();
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .propagate_natural_height(true)
+            .max_content_height(420)
+            .child(&list)
+            .build();
+        let popover = gtk::Popover::builder()
+            .autohide(false)
+            .has_arrow(false)
+            .position(gtk::PositionType::Bottom)
+            .halign(gtk::Align::Start)
+            .child(&scrolled)
+            .build();
+        popover.set_parent(entry);
+
+        let suggestions = Self {
+            popover,
+            list,
+            entry: entry.clone(),
+            history: Rc::new(RefCell::new(history)),
+            shown: Rc::default(),
+            settings: settings.clone(),
+            filling: Rc::default(),
+            on_game: Rc::new(on_game),
+        };
+
+        let suggestions_clone = suggestions.clone();
+        suggestions.list.connect_row_activated(move |_, row| {
+            suggestions_clone.pick(row.index());
+        });
+
+        // Move through the suggestions without leaving the entry
+        let key_controller = gtk::EventControllerKey::new();
+        key_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+        let suggestions_clone = suggestions.clone();
+        key_controller.connect_key_pressed(move |_, key, _, _| match key {
+            gdk::Key::Down | gdk::Key::KP_Down => {
+                if suggestions_clone.popover.is_visible() {
+                    suggestions_clone.move_selection(1);
+                } else {
+                    suggestions_clone.update(&suggestions_clone.entry.text());
+                }
+                glib::Propagation::Stop
+            }
+            gdk::Key::Up | gdk::Key::KP_Up if suggestions_clone.popover.is_visible() => {
+                suggestions_clone.move_selection(-1);
+                glib::Propagation::Stop
+            }
+            gdk::Key::Escape if suggestions_clone.popover.is_visible() => {
+                suggestions_clone.popover.popdown();
+                glib::Propagation::Stop
+            }
+            _ => glib::Propagation::Proceed,
+        });
+        entry.add_controller(key_controller);
+
+        let focus_controller = gtk::EventControllerFocus::new();
+        let popover_clone = suggestions.popover.clone();
+        focus_controller.connect_leave(move |_| popover_clone.popdown());
+        entry.add_controller(focus_controller);
+
+        let suggestions_clone = suggestions.clone();
+        settings.connect_changed("history-size", move || {
+            let limit = suggestions_clone.settings.history_size() as usize;
+            suggestions_clone.history.borrow_mut().set_limit(limit);
+            suggestions_clone.save();
+        });
+
+        suggestions
+    }
+
+    /// Shows the suggestions for `text`, or hides them when there are none
+    pub fn update(&self, text: &str) {
+        if self.filling.get() {
+            return;
+        }
+        let suggestions = self.history.borrow().suggestions(text, MAX_SUGGESTIONS);
+        while let Some(child) = self.list.first_child() {
+            self.list.remove(&child);
+        }
+        for suggestion in &suggestions {
+            self.list.append(&self.create_row(suggestion));
+        }
+        let empty = suggestions.is_empty();
+        *self.shown.borrow_mut() = suggestions;
+
+        if empty {
+            self.popover.popdown();
+        } else {
+            self.popover.set_width_request(self.entry.width());
+            self.popover.popup();
+        }
+    }
+
+    /// Handles Enter in the entry: picks the highlighted suggestion if there is one, otherwise
+    /// remembers the search as submitted
+    pub fn activate(&self) {
+        if self.popover.is_visible() {
+            if let Some(row) = self.list.selected_row() {
+                self.pick(row.index());
+                return;
+            }
+        }
+        self.popover.popdown();
+        self.record_query(&self.entry.text());
+    }
+
+    pub fn hide(&self) {
+        self.popover.popdown();
+    }
+
+    /// Remembers a submitted search
+    pub fn record_query(&self, text: &str) {
+        self.history.borrow_mut().record_query(text, unix_now());
+        self.save();
+    }
+
+    /// Keeps the first results of a search, if it was submitted
+    pub fn remember_results(&self, text: &str, games: &[Game]) {
+        self.history.borrow_mut().remember_results(text, games);
+        self.save();
+    }
+
+    /// Remembers an opened game
+    pub fn record_game(&self, game: &Game) {
+        self.history.borrow_mut().record_game(game, unix_now());
+        self.save();
+    }
+
+    pub fn clear(&self) {
+        self.history.borrow_mut().clear();
+        self.save();
+        self.popover.popdown();
+    }
+
+    fn save(&self) {
+        if let Err(e) = self.history.borrow().save() {
+            eprintln!("Failed to save search history: {}", e);
+        }
+    }
+
+    fn move_selection(&self, step: i32) {
+        let count = self.shown.borrow().len() as i32;
+        if count == 0 {
+            return;
+        }
+        let index = match self.list.selected_row() {
+            Some(row) => (row.index() + step).rem_euclid(count),
+            None if step > 0 => 0,
+            None => count - 1,
+        };
+        if let Some(row) = self.list.row_at_index(index) {
+            self.list.select_row(Some(&row));
+        }
+    }
+
+    fn pick(&self, index: i32) {
+        let Some(suggestion) = usize::try_from(index).ok().and_then(|i| self.shown.borrow().get(i).cloned()) else {
+            return;
+        };
+        self.popover.popdown();
+        match suggestion {
+            Suggestion::Query(text) => {
+                self.filling.set(true);
+                self.entry.set_text(&text);
+                self.entry.set_position(-1);
+                self.filling.set(false);
+                self.record_query(&text);
+            }
+            Suggestion::Game(game) => (self.on_game)(&game),
+        }
+    }
+
+    fn create_row(&self, suggestion: &Suggestion) -> adw::ActionRow {
+        match suggestion {
+            Suggestion::Query(text) => {
+                let row = adw::ActionRow::builder()
+                    .title(text)
+                    .use_markup(false)
+                    .activatable(true)
+                    .focusable(false)
+                    .build();
+                row.add_prefix(&gtk::Image::from_icon_name("document-open-recent-symbolic"));
+                row
+            }
+            Suggestion::Game(game) => {
+                let row = adw::ActionRow::builder()
+                    .title(&game.game_name)
+                    .use_markup(false)
+                    .activatable(true)
+                    .focusable(false)
+                    .build();
+                let time_category = self.settings.time_category();
+                if let Some(hours) = time_category.hours(game) {
+                    row.set_subtitle(&gettext_f(
+                        "{category}: {time}",
+                        &[
+                            ("category", &time_category.label()),
+                            ("time", &self.settings.time_formatter().format(hours)),
+                        ],
+                    ));
+                }
+
+                let cover = gtk::Picture::builder()
+                    .width_request(32)
+                    .height_request(32)
+                    .can_shrink(true)
+                    .content_fit(gtk::ContentFit::Cover)
+                    .build();
+                if !game.game_image.is_empty() {
+                    load_game_image(&cover, game.image_url());
+                }
+                row.add_prefix(&cover);
+                row.add_suffix(
+                    &gtk::Image::builder()
+                        .icon_name("go-next-symbolic")
+                        .tooltip_text(gettext("Open Game"))
+                        .build(),
+                );
+                row
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use libadwaita as adw;
+use adw::prelude::*;
+use gtk::glib;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::api::{Game, HltbClient};
+use crate::i18n::{gettext, gettext_f};
+use crate::randomizer::{RollFilters, RollHistory, MAX_REROLLS};
+use crate::settings::Settings;
+
+use super::comparison::CompareSelection;
+use super::create_game_row;
+
+/// The "Surprise Me" dialog: a random game matching the filters on screen, with a reroll
+/// button and the games rolled before. Kept around so the history lasts the session.
+#[derive(Clone)]
+pub struct Surprise {
+    dialog: adw::Dialog,
+    filters_label: gtk::Label,
+    current: gtk::ListBox,
+    earlier_box: gtk::Box,
+    earlier: gtk::ListBox,
+    client: Arc<HltbClient>,
+    settings: Settings,
+    compare_selection: CompareSelection,
+    filters: Rc<RefCell<RollFilters>>,
+    history: Rc<RefCell<RollHistory>>,
+    rolling: Rc<Cell<bool>>,
+    on_open: Rc<dyn Fn(&Game)>,
+}
+
+impl Surprise {
+    /// `on_open` shows one of the earlier rolls in the window
+    pub fn new(
+        client: Arc<HltbClient>,
+        settings: Settings,
+        compare_selection: CompareSelection,
+        on_open: impl Fn(&Game) + 'static,
+    ) -> Self {
+        let filters_label = gtk::Label::builder()
+            .wrap(true)
+            .xalign(0.0)
+            .css_classes(vec!["dim-label"])
+            .build();
+
+        let current = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(vec!["boxed-list"])
+            .build();
+
+        let earlier_title = gtk::Label::builder()
+            .label(gettext("Rolled Before"))
+            .xalign(0.0)
+            .hexpand(true)
+            .css_classes(vec!["heading"])
+            .build();
+        let clear_button = gtk::Button::builder()
+            .label(gettext("Clear"))
+            .css_classes(vec!["flat"])
+            .build();
+        let earlier_header = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        earlier_header.append(&earlier_title);
+        earlier_header.append(&clear_button);
+
+        let earlier = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(vec!["boxed-list"])
+            .build();
+        let earlier_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .visible(false)
+            .build();
+        earlier_box.append(&earlier_header);
+        earlier_box.append(&earlier);
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(18)
+            .margin_top(12)
+            .margin_bottom(24)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        content.append(&filters_label);
+        content.append(&current);
+        content.append(&earlier_box);
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .propagate_natural_height(true)
+            .child(&content)
+            .build();
+
+        let reroll_button = gtk::Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text(gettext("Reroll"))
+            .action_name("win.reroll")
+            .build();
+        let header_bar = adw::HeaderBar::new();
+        header_bar.pack_start(&reroll_button);
+
+        let toolbar_view = adw::ToolbarView::new();
+        toolbar_view.add_top_bar(&header_bar);
+        toolbar_view.set_content(Some(&scrolled));
+
+        let dialog = adw::Dialog::builder()
+            .title(gettext("Surprise Me"))
+            .content_width(480)
+            .content_height(560)
+            .child(&toolbar_view)
+            .build();
+
+        let surprise = Self {
+            dialog,
+            filters_label,
+            current,
+            earlier_box,
+            earlier,
+            client,
+            settings,
+            compare_selection,
+            filters: Rc::default(),
+            history: Rc::default(),
+            rolling: Rc::default(),
+            on_open: Rc::new(on_open),
+        };
+
+        let surprise_clone = surprise.clone();
+        clear_button.connect_clicked(move |_| {
+            let latest = surprise_clone.history.borrow().latest().cloned();
+            let mut history = surprise_clone.history.borrow_mut();
+            history.clear();
+            // The game on screen stays; only the ones before it are forgotten
+            if let Some(game) = latest {
+                history.push(game);
+            }
+            drop(history);
+            surprise_clone.show_earlier();
+        });
+
+        surprise
+    }
+
+    /// Rolls a game matching `filters`, which rerolls keep to
+    pub fn roll(&self, parent: &impl IsA<gtk::Widget>, filters: RollFilters) {
+        self.filters_label.set_label(&if filters.description.is_empty() {
+            gettext("Any game on HowLongToBeat")
+        } else {
+            gettext_f("Matching “{filters}”", &[("filters", &filters.description)])
+        });
+        *self.filters.borrow_mut() = filters;
+        self.reroll(parent);
+    }
+
+    /// Rolls again with the filters of the last roll
+    pub fn reroll(&self, parent: &impl IsA<gtk::Widget>) {
+        if self.dialog.parent().is_none() {
+            self.dialog.present(Some(parent));
+        }
+        if self.rolling.replace(true) {
+            return;
+        }
+
+        while let Some(child) = self.current.first_child() {
+            self.current.remove(&child);
+        }
+        let rolling_row = adw::ActionRow::builder().title(gettext("Rolling…")).build();
+        let spinner = gtk::Spinner::new();
+        spinner.start();
+        rolling_row.add_suffix(&spinner);
+        self.current.append(&rolling_row);
+
+        let surprise = self.clone();
+        glib::spawn_future_local(async move {
+            let filters = surprise.filters.borrow().clone();
+            let mut result = Ok(None);
+            // Try not to come up with a game that was rolled before
+            for _ in 0..=MAX_REROLLS {
+                result = surprise.client.random_game(&filters.terms, filters.options.clone()).await;
+                match &result {
+                    Ok(Some(game)) if surprise.history.borrow().has_rolled(game.game_id) => continue,
+                    _ => break,
+                }
+            }
+            surprise.rolling.set(false);
+
+            match result {
+                Ok(Some(game)) => {
+                    eprintln!("Rolled {}", game.game_name);
+                    surprise.history.borrow_mut().push(game);
+                    surprise.show_latest();
+                    surprise.show_earlier();
+                }
+                Ok(None) => surprise.show_message(
+                    &gettext("Nothing to Roll"),
+                    &gettext("No game matches these filters"),
+                ),
+                Err(e) => {
+                    eprintln!("Roll failed: {}", e);
+                    surprise.show_message(&gettext("Couldn't Roll"), &e.to_string());
+                }
+            }
+        });
+    }
+
+    fn show_latest(&self) {
+        while let Some(child) = self.current.first_child() {
+            self.current.remove(&child);
+        }
+        if let Some(game) = self.history.borrow().latest() {
+            let row = create_game_row(game, &self.settings.time_formatter(), &self.compare_selection);
+            row.set_expanded(true);
+            self.current.append(&row);
+        }
+    }
+
+    fn show_message(&self, title: &str, message: &str) {
+        while let Some(child) = self.current.first_child() {
+            self.current.remove(&child);
+        }
+        let row = adw::ActionRow::builder()
+            .title(title)
+            .subtitle(message)
+            .use_markup(false)
+            .build();
+        self.current.append(&row);
+    }
+
+    fn show_earlier(&self) {
+        while let Some(child) = self.earlier.first_child() {
+            self.earlier.remove(&child);
+        }
+
+        let time_category = self.settings.time_category();
+        let time_formatter = self.settings.time_formatter();
+        let history = self.history.borrow();
+        for game in history.earlier() {
+            let row = adw::ActionRow::builder()
+                .title(&game.game_name)
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            if let Some(hours) = time_category.hours(game) {
+                row.set_subtitle(&gettext_f(
+                    "{category}: {time}",
+                    &[("category", &time_category.label()), ("time", &time_formatter.format(hours))],
+                ));
+            }
+            row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+
+            let surprise = self.clone();
+            let game = game.clone();
+            row.connect_activated(move |_| {
+                surprise.dialog.close();
+                (surprise.on_open)(&game);
+            });
+            self.earlier.append(&row);
+        }
+        self.earlier_box.set_visible(!history.earlier().is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+//! Checks that user-facing strings are marked for translation and that the files containing
+//! them are listed for xgettext.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Calls that mark their first string argument for translation
+const MARKERS: &[&str] = &["gettext(", "gettext_f(", "ngettext(", "ngettext_f("];
+
+// Names that read the same in every language
+const PROPER_NOUNS: &[&str] = &["HowLongToBeat", "Steam", "Lutris", "Heroic"];
+
+fn source_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src")
+}
+
+/// `src/ui.rs` and its submodules
+fn ui_sources() -> Vec<PathBuf> {
+    let mut sources = vec![source_root().join("ui.rs")];
+    for entry in fs::read_dir(source_root().join("ui")).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|extension| extension == "rs") {
+            sources.push(path);
+        }
+    }
+    sources.sort();
+    sources
+}
+
+fn rust_sources(dir: &Path, sources: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            rust_sources(&path, sources);
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            sources.push(path);
+        }
+    }
+}
+
+/// A string literal and where it starts in the code with comments and literals blanked out
+struct Literal {
+    line: usize,
+    text: String,
+    offset: usize,
+}
+
+/// Finds the string literals in `code`, up to its test module. Also returns the code without
+/// comments and with every literal reduced to `""`, which is what the literals' offsets index.
+fn string_literals(code: &str) -> (Vec<Literal>, String) {
+    let code = code.split("#[cfg(test)]").next().unwrap_or_default();
+    let chars: Vec<char> = code.chars().collect();
+    let mut literals = Vec::new();
+    let mut preceding = String::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '"' => {
+                let start_line = line;
+                let mut text = String::new();
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        text.push(chars[i]);
+                        i += 1;
+                    }
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    text.push(chars[i]);
+                    i += 1;
+                }
+                i += 1;
+                literals.push(Literal {
+                    line: start_line,
+                    text,
+                    offset: preceding.len(),
+                });
+                preceding.push_str("\"\"");
+            }
+            // Character literals like '"', as opposed to lifetimes
+            '\'' if chars.get(i + 2) == Some(&'\'') || chars.get(i + 1) == Some(&'\\') => {
+                i += 1;
+                while i < chars.len() && chars[i] != '\'' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i += 1;
+                preceding.push_str("' '");
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                preceding.push(c);
+                i += 1;
+            }
+        }
+    }
+    (literals, preceding)
+}
+
+/// Whether a literal reads as text for people rather than an id, a key or a format pattern
+fn is_prose(text: &str) -> bool {
+    let has_words = text.chars().any(|c| c.is_alphabetic());
+    let starts_upper = text.chars().next().is_some_and(|c| c.is_uppercase());
+    has_words && (starts_upper || text.contains(' ')) && !PROPER_NOUNS.contains(&text)
+}
+
+/// Whether the literal is an argument that never reaches the user
+fn is_diagnostic(preceding: &str) -> bool {
+    let statement = preceding.rsplit([';', '{', '}']).next().unwrap_or_default();
+    ["eprintln!(", "println!(", "expect("].iter().any(|call| statement.contains(call))
+}
+
+fn is_marked(code: &str, literals: &[Literal], index: usize) -> bool {
+    let preceding = code[..literals[index].offset].trim_end();
+    if MARKERS.iter().any(|marker| preceding.ends_with(marker)) {
+        return true;
+    }
+    // The plural form of ngettext follows the singular one
+    let Some(after_singular) = preceding.strip_suffix(',') else {
+        return false;
+    };
+    index > 0
+        && after_singular.trim_end().ends_with("\"\"")
+        && ["ngettext(", "ngettext_f("]
+            .iter()
+            .any(|marker| code[..literals[index - 1].offset].trim_end().ends_with(marker))
+}
+
+fn unmarked_strings(code: &str) -> Vec<(usize, String)> {
+    let (literals, code) = string_literals(code);
+    (0..literals.len())
+        .filter(|&index| is_prose(&literals[index].text))
+        .filter(|&index| !is_diagnostic(&code[..literals[index].offset]))
+        .filter(|&index| !is_marked(&code, &literals, index))
+        .map(|index| (literals[index].line, literals[index].text.clone()))
+        .collect()
+}
+
+#[test]
+fn test_ui_strings_are_translatable() {
+    let mut unmarked = Vec::new();
+    for path in ui_sources() {
+        let code = fs::read_to_string(&path).unwrap();
+        for (line, text) in unmarked_strings(&code) {
+            unmarked.push(format!("{}:{}: \"{}\"", path.display(), line, text));
+        }
+    }
+    assert!(
+        unmarked.is_empty(),
+        "Wrap these strings in gettext() or one of its variants:\n{}",
+        unmarked.join("\n")
+    );
+}
+
+#[test]
+fn test_detects_unmarked_strings() {
+    let code = r#"
+        // A "Commented Out" string
+        let label = gtk::Label::builder().label("Main Story").css_classes(vec!["dim-label"]).build();
+        let marked = gettext("Main Story");
+        let counted = ngettext_f("{count} game", "{count} games", n, &[("count", &n.to_string())]);
+        let quote = '"';
+        row.set_title(&format!("{} ratings", count));
+        eprintln!("Search failed: {}", e);
+        let name = "HowLongToBeat";
+    "#;
+    assert_eq!(
+        unmarked_strings(code),
+        vec![(3, "Main Story".to_string()), (7, "{} ratings".to_string())]
+    );
+}
+
+#[test]
+fn test_potfiles_lists_translated_sources() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let potfiles = fs::read_to_string(root.join("po").join("POTFILES.in")).unwrap();
+    let listed: Vec<&str> = potfiles
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    for file in &listed {
+        assert!(root.join(file).exists(), "po/POTFILES.in lists missing file {}", file);
+    }
+
+    let mut sources = Vec::new();
+    rust_sources(&source_root(), &mut sources);
+    for path in sources {
+        let relative = path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/");
+        // The gettext wrappers themselves have nothing to translate
+        if relative == "src/i18n.rs" {
+            continue;
+        }
+        let code = fs::read_to_string(&path).unwrap();
+        if MARKERS.iter().any(|marker| code.contains(marker)) {
+            assert!(
+                listed.contains(&relative.as_str()),
+                "{} has translatable strings but isn't listed in po/POTFILES.in",
+                relative
+            );
+        }
+    }
+}